@@ -1,22 +1,41 @@
 use crate::exec::{
-    try_approve_pending_unpacks, try_cancel_pending, try_carry_fardel, try_change_admin,
-    try_comment_on_fardel, try_delete_comment, try_follow,
-    try_generate_viewing_key, try_hide_fardel, try_rate_fardel, try_register, try_seal_fardel,
-    try_set_constants, try_set_description, try_set_handle, try_set_private_settings,
-    try_set_profile_img, try_set_view_settings, try_set_viewing_key, try_store_ban,
-    try_store_block, try_store_deactivate, try_store_frozen_contract, try_unfollow,
-    try_unhide_fardel, try_unpack_fardel, try_unrate_fardel, try_remove_fardel,
+    try_accept_admin, try_add_delegate, try_approve_pending_unpacks, try_cancel_admin_change,
+    try_cancel_pending, try_carry_fardel, try_change_admin, try_comment_on_fardel,
+    try_delete_comment, try_delete_message, try_extend_seal_time, try_follow,
+    try_generate_viewing_key, try_hide_fardel, try_import_batch, try_mark_notifications_read,
+    try_migrate, try_mute, try_rate_fardel, try_receive_import_key, try_refund_all_pending,
+    try_register, try_remove_delegate, try_remove_fardel, try_remove_follower, try_reserve_handles,
+    try_respond_follow_request, try_restock, try_revise_fardel, try_revoke_permit,
+    try_revoke_viewing_key, try_schedule_publish, try_seal_fardel, try_send_message,
+    try_set_account_privacy, try_set_audience, try_set_constants, try_set_description,
+    try_set_handle, try_set_import_source, try_set_message_fee, try_set_pause_flags,
+    try_set_private_settings, try_set_profile_img, try_set_view_settings, try_set_viewing_key,
+    try_start_export, try_store_ban, try_store_block, try_store_deactivate,
+    try_store_emergency_mode, try_store_frozen_contract, try_sweep_expired, try_unfollow,
+    try_unhide_fardel, try_unpack_fardel, try_unrate_fardel,
 };
 use crate::msg::{HandleMsg, InitMsg, QueryMsg};
+use crate::permit::{has_permission, validate_permit, Permission, Permit};
 use crate::query::{
-    query_get_comments, query_get_fardel_by_id, query_get_fardels, query_get_fardels_batch,
-    query_get_followers, query_get_following, query_get_handle, query_get_pending_approvals,
-    query_get_profile, query_get_purchase_transactions, query_get_rating,
-    query_get_registered_addresses, query_get_sale_transactions, query_get_unpacked,
-    query_is_following, query_is_handle_available, query_is_pending_unpack, query_get_profile_by_index,
+    query_export_account_list, query_export_accounts, query_export_comments, query_export_fardels,
+    query_export_restocks, query_export_revisions, query_export_settings, query_follows_me,
+    query_get_ban_status, query_get_bans, query_get_blocked, query_get_comments,
+    query_get_contract_status, query_get_delegates, query_get_fardel_by_id,
+    query_get_fardel_revisions, query_get_fardels, query_get_fardels_batch,
+    query_get_follow_requests, query_get_followers, query_get_following, query_get_handle,
+    query_get_handle_collisions, query_get_messages, query_get_mutes, query_get_mutuals,
+    query_get_notifications, query_get_pending_admin, query_get_pending_approvals,
+    query_get_profile, query_get_profile_by_index, query_get_purchase_transactions,
+    query_get_rating, query_get_registered_addresses, query_get_reserved_handles,
+    query_get_restocks, query_get_sale_transactions, query_get_unpacked, query_get_viewing_keys,
+    query_is_following, query_is_handle_available, query_is_pending_unpack,
 };
-use crate::state::{is_frozen, Config, Constants, ReadonlyConfig};
-use crate::user_state::{is_banned, read_viewing_key};
+use crate::state::{
+    get_contract_address, get_pause_flags, get_schema_version, is_emergency, is_frozen,
+    set_block_time, set_contract_address, set_schema_version, Config, Constants, PauseFlags,
+    ReadonlyConfig, CURRENT_SCHEMA_VERSION,
+};
+use crate::user_state::{get_named_viewing_keys, is_banned, is_banned_at, read_viewing_key};
 use crate::utils::space_pad;
 use crate::validation::{
     valid_max_contents_data_len, valid_max_description_len, valid_max_handle_len,
@@ -26,8 +45,8 @@ use crate::validation::{
 };
 use crate::viewing_key::VIEWING_KEY_SIZE;
 use cosmwasm_std::{
-    Api, Env, Extern, HandleResponse, InitResponse, Querier, QueryResult, StdError, StdResult,
-    Storage,
+    Api, CanonicalAddr, Env, Extern, HandleResponse, InitResponse, Querier, QueryResult,
+    ReadonlyStorage, StdError, StdResult, Storage,
 };
 use secret_toolkit::crypto::sha_256;
 
@@ -39,6 +58,38 @@ pub const DEFAULT_MAX_COST: u128 = 5000000_u128;
 
 pub const DENOM: &str = "uscrt";
 
+// maximum number of addresses an account can delegate to
+pub const MAX_DELEGATES: usize = 10;
+
+// maximum number of extra parts a fardel can be carried with
+pub const MAX_FARDEL_PARTS: usize = 5;
+
+// maximum number of handles in a fardel's allow-list audience
+pub const MAX_ALLOW_LIST_SIZE: usize = 100;
+
+// maximum number of muted handles, and of muted tags, per account
+pub const MAX_MUTES: usize = 100;
+
+// maximum length in bytes of a direct message
+pub const MAX_MESSAGE_LEN: usize = 1000;
+
+// maximum number of handles the admin can reserve
+pub const MAX_RESERVED_HANDLES: usize = 500;
+
+// limits on named viewing keys per account
+pub const MAX_NAMED_VIEWING_KEYS: u32 = 10;
+pub const MAX_VIEWING_KEY_NAME_LEN: usize = 32;
+
+// time in seconds that a proposed admin has to accept the proposal
+pub const ADMIN_PROPOSAL_PERIOD: u64 = 604800_u64;
+
+// time in seconds that a handle released by a rename still leads to its previous owner
+//   and cannot be taken by anyone else
+pub const HANDLE_COOLDOWN: u64 = 2592000_u64;
+
+// number of previous handles shown in a profile
+pub const MAX_PROFILE_PREVIOUS_HANDLES: u32 = 10;
+
 pub fn init<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
     env: Env,
@@ -46,7 +97,7 @@ pub fn init<S: Storage, A: Api, Q: Querier>(
 ) -> StdResult<InitResponse> {
     let admin = deps
         .api
-        .canonical_address(&(msg.admin.unwrap_or_else(|| env.message.sender)))?;
+        .canonical_address(&(msg.admin.unwrap_or_else(|| env.message.sender.clone())))?;
 
     let prng_seed_hashed = sha_256(&msg.prng_seed.0);
 
@@ -91,6 +142,9 @@ pub fn init<S: Storage, A: Api, Q: Querier>(
         max_profile_img_size,
         prng_seed: prng_seed_hashed.to_vec(),
     })?;
+    set_block_time(&mut deps.storage, env.block.time)?;
+    set_contract_address(&mut deps.storage, &env.contract.address)?;
+    set_schema_version(&mut deps.storage, CURRENT_SCHEMA_VERSION)?;
 
     Ok(InitResponse::default())
 }
@@ -110,14 +164,28 @@ pub fn handle<S: Storage, A: Api, Q: Querier>(
     env: Env,
     msg: HandleMsg,
 ) -> StdResult<HandleResponse> {
+    set_block_time(&mut deps.storage, env.block.time)?;
+    // contracts instantiated before query permits did not record their address
+    if get_contract_address(&deps.storage).is_err() {
+        set_contract_address(&mut deps.storage, &env.contract.address)?;
+    }
+
     // permission check to make sure not banned (admin cannot be banned accidentally)
     let constants = Config::from_storage(&mut deps.storage).constants()?;
     let sender = deps.api.canonical_address(&env.message.sender)?;
-    if (sender != constants.admin)
-        && (is_banned(&deps.storage, &sender) || is_frozen(&deps.storage))
-    {
+    // in an emergency banned accounts can still cancel their pending unpacks to get their
+    //   coins back
+    let ban_exempt = is_emergency(&deps.storage) && matches!(msg, HandleMsg::CancelPending { .. });
+    let banned = !ban_exempt && is_banned_at(&deps.storage, &sender, env.block.time);
+    if (sender != constants.admin) && (banned || is_locked(&deps.storage, &msg)) {
         return Err(StdError::unauthorized());
     }
+    if (sender != constants.admin) && is_paused(&get_pause_flags(&deps.storage), &msg) {
+        return Err(StdError::generic_err("This function is currently paused."));
+    }
+    if get_schema_version(&deps.storage) < CURRENT_SCHEMA_VERSION && !runs_before_migration(&msg) {
+        return Err(StdError::generic_err("Storage must be migrated first."));
+    }
 
     let response = match msg {
         // Admin
@@ -150,14 +218,46 @@ pub fn handle<S: Storage, A: Api, Q: Querier>(
             max_description_len,
         ),
         HandleMsg::ChangeAdmin { admin, .. } => try_change_admin(deps, env, admin),
+        HandleMsg::AcceptAdmin { .. } => try_accept_admin(deps, env),
+        HandleMsg::CancelAdminChange { .. } => try_cancel_admin_change(deps, env),
         HandleMsg::FreezeContract { .. } => try_store_frozen_contract(deps, env, true),
         HandleMsg::UnfreezeContract { .. } => try_store_frozen_contract(deps, env, false),
+        HandleMsg::EnterEmergencyMode { .. } => try_store_emergency_mode(deps, env, true),
+        HandleMsg::ExitEmergencyMode { .. } => try_store_emergency_mode(deps, env, false),
+        HandleMsg::RefundAllPending { owner, limit, .. } => {
+            try_refund_all_pending(deps, env, owner, limit)
+        }
+        HandleMsg::Migrate { limit, .. } => try_migrate(deps, env, limit),
+        HandleMsg::StartExport {
+            target,
+            target_code_hash,
+            limit,
+            ..
+        } => try_start_export(deps, env, target, target_code_hash, limit),
+        HandleMsg::SetImportSource { source, .. } => try_set_import_source(deps, env, source),
+        HandleMsg::ReceiveImportKey { key, .. } => try_receive_import_key(deps, env, key),
+        HandleMsg::ImportBatch { records, .. } => try_import_batch(deps, env, records),
+        HandleMsg::SetPauseFlags {
+            carry,
+            unpack,
+            social,
+            ..
+        } => try_set_pause_flags(deps, env, carry, unpack, social),
+        HandleMsg::SetMessageFee { fee, .. } => try_set_message_fee(deps, env, fee),
+        HandleMsg::ReserveHandles { handles, .. } => try_reserve_handles(deps, env, handles, true),
+        HandleMsg::UnreserveHandles { handles, .. } => {
+            try_reserve_handles(deps, env, handles, false)
+        }
         HandleMsg::Ban {
-            handle, address, ..
-        } => try_store_ban(deps, env, handle, address, true),
+            handle,
+            address,
+            expiry,
+            reason,
+            ..
+        } => try_store_ban(deps, env, handle, address, true, expiry, reason),
         HandleMsg::Unban {
             handle, address, ..
-        } => try_store_ban(deps, env, handle, address, false),
+        } => try_store_ban(deps, env, handle, address, false, None, None),
         HandleMsg::RemoveFardel {
             fardel_id, ..
         } => try_remove_fardel(deps, env, fardel_id, true),
@@ -192,21 +292,63 @@ pub fn handle<S: Storage, A: Api, Q: Querier>(
             try_set_view_settings(deps, env, view_settings)
         }
         HandleMsg::SetPrivateSettings {
-            private_settings, ..
-        } => try_set_private_settings(deps, env, private_settings),
+            private_settings,
+            notification_opt_outs,
+            ..
+        } => try_set_private_settings(deps, env, private_settings, notification_opt_outs),
         HandleMsg::SetProfileImg { img, .. } => try_set_profile_img(deps, env, img),
-        HandleMsg::GenerateViewingKey { entropy, .. } => {
-            try_generate_viewing_key(deps, env, entropy)
-        }
-        HandleMsg::SetViewingKey { key, .. } => try_set_viewing_key(deps, env, key),
+        HandleMsg::GenerateViewingKey {
+            entropy,
+            name,
+            scopes,
+            ..
+        } => try_generate_viewing_key(deps, env, entropy, name, scopes),
+        HandleMsg::SetViewingKey {
+            key, name, scopes, ..
+        } => try_set_viewing_key(deps, env, key, name, scopes),
+        HandleMsg::RevokeViewingKey { name, .. } => try_revoke_viewing_key(deps, env, name),
+        HandleMsg::AddDelegate {
+            delegate,
+            permissions,
+            expiry,
+            ..
+        } => try_add_delegate(deps, env, delegate, permissions, expiry),
+        HandleMsg::RemoveDelegate { delegate, .. } => try_remove_delegate(deps, env, delegate),
+        HandleMsg::RevokePermit { permit_name, .. } => try_revoke_permit(deps, env, permit_name),
         HandleMsg::Deactivate { .. } => try_store_deactivate(deps, env, true),
         HandleMsg::Reactivate { .. } => try_store_deactivate(deps, env, false),
+        HandleMsg::SetAccountPrivacy { private, .. } => try_set_account_privacy(deps, env, private),
 
         // Other accounts
-        HandleMsg::Block { handle, .. } => try_store_block(deps, env, handle, true),
-        HandleMsg::Unblock { handle, .. } => try_store_block(deps, env, handle, false),
+        HandleMsg::Block {
+            handle,
+            purge_comments,
+            limit,
+            ..
+        } => try_store_block(
+            deps,
+            env,
+            handle,
+            true,
+            purge_comments.unwrap_or(false),
+            limit,
+        ),
+        HandleMsg::Unblock { handle, .. } => try_store_block(deps, env, handle, false, false, None),
+        HandleMsg::Mute { handles, tags, .. } => try_mute(deps, env, handles, tags, true),
+        HandleMsg::Unmute { handles, tags, .. } => try_mute(deps, env, handles, tags, false),
         HandleMsg::Follow { handle, .. } => try_follow(deps, env, handle),
         HandleMsg::Unfollow { handle, .. } => try_unfollow(deps, env, handle),
+        HandleMsg::RemoveFollower { handle, .. } => try_remove_follower(deps, env, handle),
+        HandleMsg::RespondFollowRequest { handle, accept, .. } => {
+            try_respond_follow_request(deps, env, handle, accept)
+        }
+        HandleMsg::SendMessage {
+            to_handle, text, ..
+        } => try_send_message(deps, env, to_handle, text),
+        HandleMsg::DeleteMessage {
+            message_id, sent, ..
+        } => try_delete_message(deps, env, message_id, sent.unwrap_or(false)),
+        HandleMsg::MarkNotificationsRead { .. } => try_mark_notifications_read(deps, env),
 
         // My fardels
         HandleMsg::CarryFardel {
@@ -218,6 +360,10 @@ pub fn handle<S: Storage, A: Api, Q: Querier>(
             approval_req,
             img,
             seal_time,
+            publish_time,
+            on_behalf_of,
+            parts,
+            audience,
             ..
         } => try_carry_fardel(
             deps,
@@ -230,17 +376,80 @@ pub fn handle<S: Storage, A: Api, Q: Querier>(
             approval_req,
             img,
             seal_time,
+            publish_time,
+            on_behalf_of,
+            parts,
+            audience,
         ),
-        HandleMsg::SealFardel { fardel_id, .. } => try_seal_fardel(deps, env, fardel_id),
-        HandleMsg::HideFardel { fardel_id, .. } => try_hide_fardel(deps, env, fardel_id),
-        HandleMsg::UnhideFardel { fardel_id, .. } => try_unhide_fardel(deps, env, fardel_id),
-        HandleMsg::ApprovePendingUnpacks { number, .. } => {
-            try_approve_pending_unpacks(deps, env, number)
-        }
+        HandleMsg::SealFardel {
+            fardel_id,
+            on_behalf_of,
+            ..
+        } => try_seal_fardel(deps, env, fardel_id, on_behalf_of),
+        HandleMsg::HideFardel {
+            fardel_id,
+            on_behalf_of,
+            ..
+        } => try_hide_fardel(deps, env, fardel_id, on_behalf_of),
+        HandleMsg::UnhideFardel {
+            fardel_id,
+            on_behalf_of,
+            ..
+        } => try_unhide_fardel(deps, env, fardel_id, on_behalf_of),
+        HandleMsg::ApprovePendingUnpacks {
+            number,
+            on_behalf_of,
+            ..
+        } => try_approve_pending_unpacks(deps, env, number, on_behalf_of),
+        HandleMsg::ReviseFardel {
+            fardel_id,
+            contents_data,
+            public_message,
+            tags,
+            part,
+            ..
+        } => try_revise_fardel(
+            deps,
+            env,
+            fardel_id,
+            contents_data,
+            public_message,
+            tags,
+            part,
+        ),
+        HandleMsg::SchedulePublish {
+            fardel_id,
+            publish_time,
+            ..
+        } => try_schedule_publish(deps, env, fardel_id, publish_time),
+        HandleMsg::SetAudience {
+            fardel_id,
+            audience,
+            ..
+        } => try_set_audience(deps, env, fardel_id, audience),
+        HandleMsg::ExtendSealTime {
+            fardel_id,
+            seal_time,
+            ..
+        } => try_extend_seal_time(deps, env, fardel_id, seal_time),
+        HandleMsg::Restock {
+            fardel_id,
+            additional_count,
+            new_seal_time,
+            ..
+        } => try_restock(deps, env, fardel_id, additional_count, new_seal_time),
+        HandleMsg::SweepExpired { limit, .. } => try_sweep_expired(deps, env, limit),
 
         // Other fardels
-        HandleMsg::UnpackFardel { fardel_id, .. } => try_unpack_fardel(deps, env, fardel_id),
-        HandleMsg::CancelPending { fardel_id, .. } => try_cancel_pending(deps, env, fardel_id),
+        HandleMsg::UnpackFardel {
+            fardel_id,
+            pubkey,
+            part,
+            ..
+        } => try_unpack_fardel(deps, env, fardel_id, pubkey, part),
+        HandleMsg::CancelPending {
+            fardel_id, part, ..
+        } => try_cancel_pending(deps, env, fardel_id, part),
         HandleMsg::RateFardel {
             fardel_id, rating, ..
         } => try_rate_fardel(deps, env, fardel_id, rating),
@@ -254,13 +463,66 @@ pub fn handle<S: Storage, A: Api, Q: Querier>(
         HandleMsg::DeleteComment {
             fardel_id,
             comment_id,
+            on_behalf_of,
             ..
-        } => try_delete_comment(deps, env, fardel_id, comment_id),
+        } => try_delete_comment(deps, env, fardel_id, comment_id, on_behalf_of),
     };
 
     pad_response(response)
 }
 
+// returns true if non-admin handle functions are locked by a freeze or emergency mode
+//   a proposed admin must still be able to accept, the import source must be able to send
+//   the import key to a frozen contract, and in emergency mode users can still cancel
+//   pending unpacks to get their escrow back
+fn is_locked<S: ReadonlyStorage>(storage: &S, msg: &HandleMsg) -> bool {
+    match msg {
+        HandleMsg::AcceptAdmin { .. } | HandleMsg::ReceiveImportKey { .. } => false,
+        HandleMsg::CancelPending { .. } => is_frozen(storage) && !is_emergency(storage),
+        _ => is_frozen(storage) || is_emergency(storage),
+    }
+}
+
+// returns true if the handle message can run before Migrate has finished, any other could
+//   write records in the current layout where reads still expect an older one
+fn runs_before_migration(msg: &HandleMsg) -> bool {
+    matches!(
+        msg,
+        HandleMsg::Migrate { .. }
+            | HandleMsg::FreezeContract { .. }
+            | HandleMsg::UnfreezeContract { .. }
+            | HandleMsg::ChangeAdmin { .. }
+            | HandleMsg::AcceptAdmin { .. }
+            | HandleMsg::CancelAdminChange { .. }
+    )
+}
+
+// returns true if the handle message belongs to a group of functions that is paused
+fn is_paused(flags: &PauseFlags, msg: &HandleMsg) -> bool {
+    match msg {
+        HandleMsg::CarryFardel { .. }
+        | HandleMsg::ReviseFardel { .. }
+        | HandleMsg::SchedulePublish { .. }
+        | HandleMsg::SetAudience { .. }
+        | HandleMsg::ExtendSealTime { .. }
+        | HandleMsg::Restock { .. } => flags.carry,
+        HandleMsg::UnpackFardel { .. } | HandleMsg::ApprovePendingUnpacks { .. } => flags.unpack,
+        HandleMsg::Follow { .. }
+        | HandleMsg::Unfollow { .. }
+        | HandleMsg::RemoveFollower { .. }
+        | HandleMsg::Mute { .. }
+        | HandleMsg::Unmute { .. }
+        | HandleMsg::RespondFollowRequest { .. }
+        | HandleMsg::RateFardel { .. }
+        | HandleMsg::UnrateFardel { .. }
+        | HandleMsg::CommentOnFardel { .. }
+        | HandleMsg::DeleteComment { .. }
+        | HandleMsg::SendMessage { .. }
+        | HandleMsg::DeleteMessage { .. } => flags.social,
+        _ => false,
+    }
+}
+
 pub fn query<S: Storage, A: Api, Q: Querier>(deps: &Extern<S, A, Q>, msg: QueryMsg) -> QueryResult {
     match msg {
         QueryMsg::GetProfile { handle } => query_get_profile(deps, handle),
@@ -277,6 +539,14 @@ pub fn query<S: Storage, A: Api, Q: Querier>(deps: &Extern<S, A, Q>, msg: QueryM
             page,
             page_size,
         } => query_get_comments(deps, &None, fardel_id, page, page_size),
+        QueryMsg::GetRestocks {
+            fardel_id,
+            page,
+            page_size,
+        } => query_get_restocks(deps, fardel_id, page, page_size),
+        QueryMsg::GetPendingAdmin {} => query_get_pending_admin(deps),
+        QueryMsg::GetContractStatus {} => query_get_contract_status(deps),
+        QueryMsg::WithPermit { permit, query } => permit_queries(deps, permit, *query),
         _ => authenticated_queries(deps, msg),
     }
 }
@@ -290,6 +560,8 @@ fn authenticated_queries<S: Storage, A: Api, Q: Querier>(
     for address in addresses {
         let canonical_addr = deps.api.canonical_address(address)?;
 
+        // the default key can run every query
+        let mut scopes: Option<Vec<Permission>> = None;
         let expected_key = read_viewing_key(&deps.storage, &canonical_addr);
 
         if expected_key.is_none() {
@@ -297,90 +569,378 @@ fn authenticated_queries<S: Storage, A: Api, Q: Querier>(
             // in a way which will allow to time the command and determine if a viewing key doesn't exist
             key.check_viewing_key(&[0u8; VIEWING_KEY_SIZE]);
         } else if key.check_viewing_key(expected_key.unwrap().as_slice()) {
-            // permission check to make sure not banned (admin cannot be banned accidentally)
-            let constants = ReadonlyConfig::from_storage(&deps.storage).constants()?;
-            if (canonical_addr != constants.admin) && (is_banned(&deps.storage, &canonical_addr)) {
-                return Err(StdError::unauthorized());
+            scopes = Some(vec![Permission::Owner]);
+        }
+
+        // named keys are all checked so the matching one cannot be found by timing
+        for named_key in get_named_viewing_keys(&deps.storage, &canonical_addr)? {
+            if key.check_viewing_key(&named_key.hashed_key)
+                && !named_key.revoked
+                && scopes.is_none()
+            {
+                scopes = Some(named_key.scopes);
             }
+        }
 
-            return match msg {
-                // Base
-                QueryMsg::GetSaleTransactions {
-                    address,
-                    page,
-                    page_size,
-                    ..
-                } => query_get_sale_transactions(&deps, &address, page, page_size),
-                QueryMsg::GetPurchaseTransactions {
-                    address,
-                    page,
-                    page_size,
-                    ..
-                } => query_get_purchase_transactions(&deps, &address, page, page_size),
-                QueryMsg::GetHandle { address, .. } => query_get_handle(&deps, &address),
-                QueryMsg::GetFollowing {
-                    address,
-                    page,
-                    page_size,
-                    ..
-                } => query_get_following(&deps, &address, page, page_size),
-                QueryMsg::IsFollowing {
-                    address, handle, ..
-                } => query_is_following(&deps, &address, handle),
-                QueryMsg::GetFollowers {
-                    address,
-                    page,
-                    page_size,
-                    ..
-                } => query_get_followers(&deps, &address, page, page_size),
-                QueryMsg::GetFardelByIdAuth {
-                    address, fardel_id, ..
-                } => query_get_fardel_by_id(&deps, &Some(address), fardel_id),
-                QueryMsg::GetFardelsAuth {
-                    address,
-                    handle,
-                    page,
-                    page_size,
-                    ..
-                } => query_get_fardels(&deps, &Some(address), handle, page, page_size),
-                QueryMsg::IsPendingUnpack {
-                    address, fardel_id, ..
-                } => query_is_pending_unpack(&deps, &address, fardel_id),
-                QueryMsg::GetUnpacked {
-                    address,
-                    page,
-                    page_size,
-                    ..
-                } => query_get_unpacked(&deps, &address, page, page_size),
-                QueryMsg::GetPendingApprovals {
-                    address, number, ..
-                } => query_get_pending_approvals(&deps, &address, number),
-                QueryMsg::GetCommentsAuth {
-                    address,
-                    fardel_id,
-                    page,
-                    page_size,
-                    ..
-                } => query_get_comments(&deps, &Some(address), fardel_id, page, page_size),
-                QueryMsg::GetRating {
-                    address, fardel_id, ..
-                } => query_get_rating(&deps, &address, fardel_id),
-                QueryMsg::GetFardelsBatch {
-                    address,
-                    start,
-                    count,
-                    ..
-                } => query_get_fardels_batch(&deps, &address, start, count),
-                QueryMsg::GetRegisteredAddresses {
-                    address,
-                    start,
-                    count,
-                    ..
-                } => query_get_registered_addresses(&deps, &address, start, count),
-                _ => panic!("This query type does not require authentication"),
-            };
+        if let Some(scopes) = scopes {
+            let permission = msg.permission().unwrap_or(Permission::Owner);
+            if !has_permission(&scopes, &permission) {
+                return Err(StdError::generic_err(
+                    "Viewing key does not grant access to this query.",
+                ));
+            }
+            return authorized_query(deps, &canonical_addr, msg);
         }
     }
 
     Err(StdError::unauthorized())
 }
+
+fn permit_queries<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    permit: Permit,
+    msg: QueryMsg,
+) -> QueryResult {
+    let permission = match msg.permission() {
+        Some(permission) => permission,
+        None => {
+            return Err(StdError::generic_err(
+                "This query type does not require authentication",
+            ))
+        }
+    };
+    let contract_address = get_contract_address(&deps.storage)?;
+    let signer = validate_permit(&deps.storage, &permit, &contract_address)?;
+    if !permit.check_permission(&permission) {
+        return Err(StdError::generic_err(
+            "Permit does not grant permission for this query.",
+        ));
+    }
+
+    // the query must be for the signer's own account
+    let (addresses, _) = msg.get_validation_params();
+    for address in addresses {
+        if deps.api.canonical_address(address)? != signer {
+            return Err(StdError::unauthorized());
+        }
+    }
+
+    authorized_query(deps, &signer, msg)
+}
+
+// runs an authenticated query once the address has been verified by viewing key or permit
+fn authorized_query<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    canonical_addr: &CanonicalAddr,
+    msg: QueryMsg,
+) -> QueryResult {
+    // permission check to make sure not banned (admin cannot be banned accidentally)
+    //   banned users can still look up their own ban
+    let constants = ReadonlyConfig::from_storage(&deps.storage).constants()?;
+    let ban_status = matches!(msg, QueryMsg::GetBanStatus { .. });
+    if (*canonical_addr != constants.admin)
+        && is_banned(&deps.storage, canonical_addr)
+        && !ban_status
+    {
+        return Err(StdError::unauthorized());
+    }
+
+    match msg {
+        // Base
+        QueryMsg::GetSaleTransactions {
+            address,
+            page,
+            page_size,
+            ..
+        } => query_get_sale_transactions(&deps, &address, page, page_size),
+        QueryMsg::GetPurchaseTransactions {
+            address,
+            page,
+            page_size,
+            ..
+        } => query_get_purchase_transactions(&deps, &address, page, page_size),
+        QueryMsg::GetHandle { address, .. } => query_get_handle(&deps, &address),
+        QueryMsg::GetViewingKeys { address, .. } => query_get_viewing_keys(&deps, &address),
+        QueryMsg::GetDelegates { address, .. } => query_get_delegates(&deps, &address),
+        QueryMsg::GetFollowing {
+            address,
+            page,
+            page_size,
+            ..
+        } => query_get_following(&deps, &address, page, page_size),
+        QueryMsg::IsFollowing {
+            address, handle, ..
+        } => query_is_following(&deps, &address, handle),
+        QueryMsg::GetFollowers {
+            address,
+            page,
+            page_size,
+            ..
+        } => query_get_followers(&deps, &address, page, page_size),
+        QueryMsg::GetFollowRequests {
+            address,
+            page,
+            page_size,
+            ..
+        } => query_get_follow_requests(&deps, &address, page, page_size),
+        QueryMsg::GetMutuals {
+            address,
+            page,
+            page_size,
+            ..
+        } => query_get_mutuals(&deps, &address, page, page_size),
+        QueryMsg::FollowsMe {
+            address, handle, ..
+        } => query_follows_me(&deps, &address, handle),
+        QueryMsg::GetBlocked {
+            address,
+            page,
+            page_size,
+            ..
+        } => query_get_blocked(&deps, &address, page, page_size),
+        QueryMsg::GetMutes { address, .. } => query_get_mutes(&deps, &address),
+        QueryMsg::GetInbox {
+            address,
+            page,
+            page_size,
+            ..
+        } => query_get_messages(&deps, &address, false, page, page_size),
+        QueryMsg::GetOutbox {
+            address,
+            page,
+            page_size,
+            ..
+        } => query_get_messages(&deps, &address, true, page, page_size),
+        QueryMsg::GetNotifications {
+            address,
+            since,
+            before,
+            page_size,
+            ..
+        } => query_get_notifications(&deps, &address, since, before, page_size),
+        QueryMsg::GetFardelByIdAuth {
+            address, fardel_id, ..
+        } => query_get_fardel_by_id(&deps, &Some(address), fardel_id),
+        QueryMsg::GetFardelsAuth {
+            address,
+            handle,
+            page,
+            page_size,
+            ..
+        } => query_get_fardels(&deps, &Some(address), handle, page, page_size),
+        QueryMsg::IsPendingUnpack {
+            address, fardel_id, ..
+        } => query_is_pending_unpack(&deps, &address, fardel_id),
+        QueryMsg::GetUnpacked {
+            address,
+            page,
+            page_size,
+            ..
+        } => query_get_unpacked(&deps, &address, page, page_size),
+        QueryMsg::GetFardelRevisions {
+            address,
+            fardel_id,
+            page,
+            page_size,
+            ..
+        } => query_get_fardel_revisions(&deps, &address, fardel_id, page, page_size),
+        QueryMsg::GetPendingApprovals {
+            address, number, ..
+        } => query_get_pending_approvals(&deps, &address, number),
+        QueryMsg::GetCommentsAuth {
+            address,
+            fardel_id,
+            page,
+            page_size,
+            ..
+        } => query_get_comments(&deps, &Some(address), fardel_id, page, page_size),
+        QueryMsg::GetRating {
+            address, fardel_id, ..
+        } => query_get_rating(&deps, &address, fardel_id),
+        QueryMsg::GetBanStatus { address, .. } => query_get_ban_status(&deps, &address),
+        QueryMsg::GetFardelsBatch {
+            address,
+            start,
+            count,
+            ..
+        } => query_get_fardels_batch(&deps, &address, start, count),
+        QueryMsg::GetRegisteredAddresses {
+            address,
+            start,
+            count,
+            ..
+        } => query_get_registered_addresses(&deps, &address, start, count),
+        QueryMsg::GetBans {
+            address,
+            start,
+            count,
+            ..
+        } => query_get_bans(&deps, &address, start, count),
+        QueryMsg::GetReservedHandles { address, .. } => query_get_reserved_handles(&deps, &address),
+        QueryMsg::GetHandleCollisions {
+            address,
+            start,
+            count,
+            ..
+        } => query_get_handle_collisions(&deps, &address, start, count),
+        QueryMsg::ExportFardels {
+            address,
+            start,
+            count,
+            ..
+        } => query_export_fardels(&deps, &address, start, count),
+        QueryMsg::ExportComments {
+            address,
+            fardel_id,
+            start,
+            count,
+            ..
+        } => query_export_comments(&deps, &address, fardel_id, start, count),
+        QueryMsg::ExportRevisions {
+            address,
+            fardel_id,
+            start,
+            count,
+            ..
+        } => query_export_revisions(&deps, &address, fardel_id, start, count),
+        QueryMsg::ExportRestocks {
+            address,
+            fardel_id,
+            start,
+            count,
+            ..
+        } => query_export_restocks(&deps, &address, fardel_id, start, count),
+        QueryMsg::ExportSettings { address, .. } => query_export_settings(&deps, &address),
+        QueryMsg::ExportAccounts {
+            address,
+            start,
+            count,
+            ..
+        } => query_export_accounts(&deps, &address, start, count),
+        QueryMsg::ExportAccountList {
+            address,
+            owner,
+            list,
+            start,
+            count,
+            ..
+        } => query_export_account_list(&deps, &address, &owner, list, start, count),
+        _ => panic!("This query type does not require authentication"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fardel_state::tests::{assert_upgraded_fardel, store_v1_fardel};
+    use crate::fardel_state::VersionedFardel;
+    use crate::msg::{HandleAnswer, ResponseStatus};
+    use crate::state::{get_bin_data, PREFIX_ACCOUNTS, PREFIX_FARDELS};
+    use crate::user_state::tests::{assert_upgraded_account, store_v1_account};
+    use crate::user_state::VersionedAccount;
+    use cosmwasm_std::testing::{mock_dependencies, mock_env, MockApi, MockQuerier, MockStorage};
+    use cosmwasm_std::{from_binary, Binary, HumanAddr};
+    use cosmwasm_storage::ReadonlyPrefixedStorage;
+    use secret_toolkit::storage::AppendStore;
+
+    fn init_helper() -> Extern<MockStorage, MockApi, MockQuerier> {
+        let mut deps = mock_dependencies(20, &[]);
+        let env = mock_env("admin", &[]);
+        let init_msg = InitMsg {
+            admin: None,
+            transaction_fee: None,
+            max_query_page_size: None,
+            max_cost: None,
+            max_public_message_len: None,
+            max_tag_len: None,
+            max_number_of_tags: None,
+            max_fardel_img_size: None,
+            max_contents_data_len: None,
+            max_handle_len: None,
+            max_profile_img_size: None,
+            max_description_len: None,
+            max_view_settings_len: None,
+            max_private_settings_len: None,
+            prng_seed: Binary::from(b"seed".to_vec()),
+        };
+        init(&mut deps, env, init_msg).unwrap();
+        deps
+    }
+
+    #[test]
+    fn migrate_upgrades_v1_records_in_pages() {
+        let mut deps = init_helper();
+        let alice = deps
+            .api
+            .canonical_address(&HumanAddr("alice".to_string()))
+            .unwrap();
+        let bob = deps
+            .api
+            .canonical_address(&HumanAddr("bob".to_string()))
+            .unwrap();
+        let alice_account = store_v1_account(&mut deps.storage, &alice, "alice");
+        let bob_account = store_v1_account(&mut deps.storage, &bob, "bob");
+        let alice_first = store_v1_fardel(&mut deps.storage, &alice, 1);
+        let bob_first = store_v1_fardel(&mut deps.storage, &bob, 2);
+        let alice_second = store_v1_fardel(&mut deps.storage, &alice, 3);
+        set_schema_version(&mut deps.storage, 1).unwrap();
+
+        // lists must not change while they are migrated
+        let msg = HandleMsg::Migrate {
+            limit: Some(1),
+            padding: None,
+        };
+        assert!(handle(&mut deps, mock_env("admin", &[]), msg).is_err());
+        let msg = HandleMsg::FreezeContract { padding: None };
+        handle(&mut deps, mock_env("admin", &[]), msg).unwrap();
+
+        let mut calls = 0;
+        loop {
+            let msg = HandleMsg::Migrate {
+                limit: Some(1),
+                padding: None,
+            };
+            let response = handle(&mut deps, mock_env("admin", &[]), msg).unwrap();
+            calls += 1;
+            match from_binary(&response.data.unwrap()).unwrap() {
+                HandleAnswer::Migrate { status, done, .. } => {
+                    assert_eq!(status, ResponseStatus::Success);
+                    if done {
+                        break;
+                    }
+                }
+                _ => panic!("Unexpected answer to Migrate"),
+            }
+            assert!(calls < 50, "Migrate did not finish");
+        }
+        // one record per call, so it took several pages
+        assert!(calls > 5);
+        assert_eq!(get_schema_version(&deps.storage), CURRENT_SCHEMA_VERSION);
+
+        // every record is now tagged with the current layout
+        for (owner, index, v1) in vec![
+            (&alice, 0, &alice_first),
+            (&bob, 0, &bob_first),
+            (&alice, 1, &alice_second),
+        ] {
+            let store = ReadonlyPrefixedStorage::multilevel(
+                &[PREFIX_FARDELS, owner.as_slice()],
+                &deps.storage,
+            );
+            let store = AppendStore::<VersionedFardel, _>::attach(&store)
+                .unwrap()
+                .unwrap();
+            match store.get_at(index).unwrap() {
+                VersionedFardel::V2(fardel) => assert_upgraded_fardel(&fardel, v1),
+                _ => panic!("Migrated fardel is not tagged as v2"),
+            }
+        }
+        for (owner, v1) in vec![(&alice, &alice_account), (&bob, &bob_account)] {
+            let store = ReadonlyPrefixedStorage::new(PREFIX_ACCOUNTS, &deps.storage);
+            match get_bin_data(&store, owner.as_slice()).unwrap() {
+                VersionedAccount::V2(account) => assert_upgraded_account(&account, v1),
+                _ => panic!("Migrated account is not tagged as v2"),
+            }
+        }
+    }
+}
@@ -1,42 +1,103 @@
-use crate::contract::DENOM;
+use crate::contract::{
+    ADMIN_PROPOSAL_PERIOD, DENOM, MAX_ALLOW_LIST_SIZE, MAX_DELEGATES, MAX_FARDEL_PARTS,
+    MAX_MESSAGE_LEN, MAX_MUTES, MAX_NAMED_VIEWING_KEYS, MAX_RESERVED_HANDLES,
+    MAX_VIEWING_KEY_NAME_LEN,
+};
 use crate::fardel_state::{
-    decrement_fardel_unpack_count, get_fardel_by_global_id, get_fardel_by_hash, get_fardel_owner,
-    get_global_id_by_hash, get_sealed_status, get_total_fardel_count, hide_fardel, remove_fardel, unremove_fardel,
-    increment_fardel_unpack_count, seal_fardel, store_fardel, store_fardel_img, unhide_fardel,
-    Fardel,
+    add_fardel_part, append_restock, append_revision, audience_allows,
+    decrement_fardel_unpack_count, get_fardel_by_global_id, get_fardel_by_hash, get_fardel_id_at,
+    get_fardel_key, get_fardel_owner, get_fardel_parts, get_global_id_by_hash,
+    get_number_of_fardels, get_number_of_revisions, get_part_of, get_sealed_status,
+    get_total_fardel_count, hide_fardel, increment_fardel_unpack_count, is_fardel_removed,
+    is_published, is_sealed, remove_fardel, revise_fardel, seal_fardel, set_audience, set_countable,
+    set_publish_time, set_seal_time, store_fardel, store_fardel_img, store_fardel_key,
+    store_fardel_unpack_count, unhide_fardel, unremove_fardel, unseal_fardel, upgrade_stored_fardel,
+    Fardel, StoredAudience, StoredRestock, StoredRevision,
+};
+use crate::message_state::{
+    delete_message, get_message_fee, push_message, send_message, set_message_fee, StoredMessage,
 };
 use crate::msg::{
-    Fee, HandleAnswer, ResponseStatus, ResponseStatus::Failure, ResponseStatus::Success,
+    AccountExport, AccountListEntry, Audience, AudienceExport, EncryptedContents, FardelExport,
+    FardelPart, Fee, HandleAnswer, HandleMsg, ImportRecord, MutesExport, NotificationType,
+    ResponseStatus, ResponseStatus::Failure, ResponseStatus::Success,
 };
+use crate::notification_state::{
+    mark_notifications_read, notify, push_notification, set_notification_opt_outs,
+    set_notifications_read, StoredNotification,
+};
+use crate::permit::Permission;
 use crate::social_state::{
-    add_downvote_fardel, add_upvote_fardel, comment_on_fardel, delete_comment, get_comment_by_id,
-    get_rating, has_rated, is_blocked_by, remove_following, remove_rated, set_rated,
-    store_account_block, store_following, subtract_downvote_fardel, subtract_upvote_fardel,
+    add_downvote_fardel, add_upvote_fardel, backfill_blocked_list, clear_follow_request,
+    comment_on_fardel, compact_follow_lists, delete_comment, get_block_purge, get_comment_by_id,
+    get_follow_request, get_mutes, get_number_of_comments, get_rating, has_rated, is_blocked_by,
+    is_following, purge_comments_by, remove_following, remove_following_address, remove_rated,
+    remove_rating_by, set_block_purge, set_downvotes, set_mutes, set_rated, set_upvotes,
+    store_account_block, store_follow_request, store_following, store_following_address,
+    subtract_downvote_fardel, subtract_upvote_fardel, BlockPurge, StoredMutes,
+};
+use crate::state::{
+    clear_import_key, clear_pending_admin, get_export_cursor, get_import_key, get_import_source,
+    get_migration_cursor, get_pause_flags, get_pending_admin, get_schema_version, get_sweep_cursor,
+    is_emergency, is_frozen, set_emergency, set_export_cursor, set_export_key, set_frozen,
+    set_import_key, set_import_source, set_migration_cursor, set_pause_flags, set_pending_admin,
+    set_schema_version, set_sweep_cursor, Config, MigrationCursor, ReadonlyConfig,
+    CURRENT_SCHEMA_VERSION,
 };
-use crate::state::{get_new_admin, set_new_admin, get_new_admin_count, set_new_admin_count, set_frozen, Config, ReadonlyConfig};
 use crate::tx_state::{append_purchase_tx, append_sale_tx};
 use crate::u256_math::*;
 use crate::unpack_state::{
-    cancel_pending_unpack, get_pending_approvals_from_start, get_pending_start,
-    get_pending_unpacked_status_by_fardel_id, get_unpacked_status_by_fardel_id, set_pending_start,
-    store_pending_unpack, store_unpack, PendingUnpackApproval,
+    cancel_pending_unpack, cancel_pending_unpacks_from_start, get_number_of_pending_from_start,
+    get_pending_approvals_from_start, get_pending_pubkey, get_pending_start,
+    get_pending_unpacked_status_by_fardel_id, get_unpacked_status_by_fardel_id, set_pending_pubkey,
+    set_pending_start, store_encrypted_unpack, store_pending_unpack, store_unpack,
+    PendingUnpackApproval,
 };
 use crate::user_state::{
-    delete_handle_map, get_account, get_account_for_handle, is_banned, is_deactivated,
-    map_handle_to_account, store_account, store_account_ban, store_account_deactivated,
-    store_account_img, write_viewing_key, Account, address_list_add,
+    address_list_add, get_account, get_account_for_handle, get_delegation,
+    get_number_of_active_delegates, get_registered_address, get_reserved_handles,
+    get_total_number_registered_accounts, import_named_viewing_key, import_released_handle,
+    is_banned_at, is_deactivated, is_private_account, is_reserved_handle, map_handle_to_account,
+    map_normalized_handle, normalize_handle, release_handle, revoke_delegation,
+    revoke_named_viewing_key, revoke_permit, set_reserved_handles, store_account,
+    store_account_ban, store_account_deactivated, store_account_img, store_account_private,
+    store_delegation, upgrade_account_ban, upgrade_handle_map, upgrade_stored_account,
+    write_named_viewing_key, write_viewing_key, write_viewing_key_hash, Account,
+    DelegatePermission, ReleasedHandle, StoredNamedViewingKey,
+};
+use crate::utils::{
+    decrypt_export_data, encrypt_contents_for_pubkey, export_entry_label, valid_pubkey,
 };
 use crate::validation::{
-    has_whitespace, valid_max_contents_data_len, valid_max_description_len, valid_max_handle_len,
-    valid_max_number_of_tags, valid_max_public_message_len, valid_max_query_page_size,
-    valid_max_tag_len, valid_max_thumbnail_img_size, valid_seal_time,
+    valid_expiry, valid_handle_chars, valid_max_contents_data_len, valid_max_description_len,
+    valid_max_handle_len, valid_max_number_of_tags, valid_max_public_message_len,
+    valid_max_query_page_size, valid_max_tag_len, valid_max_thumbnail_img_size, valid_publish_time,
+    valid_seal_time,
 };
 use crate::viewing_key::ViewingKey;
 use cosmwasm_std::{
-    to_binary, Api, BankMsg, Coin, CosmosMsg, Env, Extern, HandleResponse,
-    HumanAddr, Querier, StdError, StdResult, Storage, Uint128, CanonicalAddr, //debug_print,
+    from_slice,
+    to_binary,
+    Api,
+    BankMsg,
+    Binary,
+    CanonicalAddr, //debug_print,
+    Coin,
+    CosmosMsg,
+    Env,
+    Extern,
+    HandleResponse,
+    HumanAddr,
+    Querier,
+    ReadonlyStorage,
+    StdError,
+    StdResult,
+    Storage,
+    Uint128,
+    WasmMsg,
 };
 use primitive_types::U256;
+use secret_toolkit::crypto::{sha_256, Prng};
 use std::convert::TryFrom;
 use twox_hash::xxh3::hash128_with_seed;
 
@@ -112,55 +173,92 @@ pub fn try_change_admin<S: Storage, A: Api, Q: Querier>(
     env: Env,
     new_admin: HumanAddr,
 ) -> StdResult<HandleResponse> {
-    let new_admin_count = get_new_admin_count(&deps.storage);
-    //debug_print!("{}", new_admin_count);
-    let current_new_admin = get_new_admin(&deps.storage);
-    //debug_print!("{:?}", current_new_admin);
+    let constants = ReadonlyConfig::from_storage(&deps.storage).constants()?;
 
-    let msg;
-    let mut config = Config::from_storage(&mut deps.storage);
-    let mut constants = config.constants()?;
+    // permission check
+    if deps.api.canonical_address(&env.message.sender)? != constants.admin {
+        return Err(StdError::unauthorized());
+    }
 
     let new_admin_canonical: CanonicalAddr = deps.api.canonical_address(&new_admin)?;
+    let deadline = env.block.time + ADMIN_PROPOSAL_PERIOD;
+    set_pending_admin(&mut deps.storage, &new_admin_canonical, deadline)?;
+    let msg = format!(
+        "Proposed {} as new admin, must be accepted before {}",
+        new_admin, deadline
+    );
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::ChangeAdmin { status: Success, msg })?),
+    })
+}
+
+pub fn try_accept_admin<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+) -> StdResult<HandleResponse> {
+    let mut status: ResponseStatus = Success;
+    let mut msg: Option<String> = None;
+
+    let message_sender = deps.api.canonical_address(&env.message.sender)?;
+    match get_pending_admin(&deps.storage) {
+        Ok(pending_admin) => {
+            if pending_admin.address != message_sender {
+                return Err(StdError::unauthorized());
+            } else if pending_admin.deadline < env.block.time {
+                clear_pending_admin(&mut deps.storage);
+                status = Failure;
+                msg = Some(String::from("Admin proposal has expired."));
+            } else {
+                let mut config = Config::from_storage(&mut deps.storage);
+                let mut constants = config.constants()?;
+                constants.admin = message_sender;
+                config.set_constants(&constants)?;
+                clear_pending_admin(&mut deps.storage);
+            }
+        }
+        Err(_) => {
+            return Err(StdError::unauthorized());
+        }
+    }
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::AcceptAdmin { status, msg })?),
+    })
+}
+
+pub fn try_cancel_admin_change<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+) -> StdResult<HandleResponse> {
+    let mut status: ResponseStatus = Success;
+    let mut msg: Option<String> = None;
+
+    let constants = ReadonlyConfig::from_storage(&deps.storage).constants()?;
 
     // permission check
     if deps.api.canonical_address(&env.message.sender)? != constants.admin {
         return Err(StdError::unauthorized());
     }
 
-    match current_new_admin {
-        Ok(address) => {
-            //debug_print!("{:?}", address);
-            if address == new_admin_canonical { // check how many times reset requested
-                if new_admin_count >= 2 { // already requested this address twice, do reset
-                    //debug_print!("first if");
-                    constants.admin = new_admin_canonical;
-                    config.set_constants(&constants)?;
-                    set_new_admin_count(&mut deps.storage, 0_u8)?;
-                    msg = format!("Successfully reset admin to {}", new_admin);
-                } else {
-                    //debug_print!("first else");
-                    set_new_admin_count(&mut deps.storage, new_admin_count + 1)?;
-                    msg = format!("Request {} to reset admin to {}", new_admin_count + 1, new_admin);
-                }
-            } else { // new address
-                //debug_print!("second else");
-                set_new_admin(&mut deps.storage, &new_admin_canonical)?;
-                set_new_admin_count(&mut deps.storage, 1_u8)?;
-                msg = format!("Request 1 to reset admin to {}", new_admin);
-            }
-        },
+    match get_pending_admin(&deps.storage) {
+        Ok(_) => {
+            clear_pending_admin(&mut deps.storage);
+        }
         Err(_) => {
-            set_new_admin(&mut deps.storage, &new_admin_canonical)?;
-            set_new_admin_count(&mut deps.storage, 1_u8)?;
-            msg = format!("Request 1 to reset admin to {}", new_admin);
+            status = Failure;
+            msg = Some(String::from("No pending admin proposal."));
         }
     }
 
     Ok(HandleResponse {
         messages: vec![],
         log: vec![],
-        data: Some(to_binary(&HandleAnswer::ChangeAdmin { status: Success, msg })?),
+        data: Some(to_binary(&HandleAnswer::CancelAdminChange { status, msg })?),
     })
 }
 
@@ -178,6 +276,11 @@ pub fn try_store_frozen_contract<S: Storage, A: Api, Q: Querier>(
     }
 
     set_frozen(&mut deps.storage, frozen)?;
+    if !frozen {
+        clear_import_key(&mut deps.storage);
+        // accounts can get pending unpacks again, a later export checks them all over
+        set_export_cursor(&mut deps.storage, 0)?;
+    }
 
     if frozen {
         Ok(HandleResponse {
@@ -198,244 +301,1288 @@ pub fn try_store_frozen_contract<S: Storage, A: Api, Q: Querier>(
     }
 }
 
-pub fn try_store_ban<S: Storage, A: Api, Q: Querier>(
+pub fn try_store_emergency_mode<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
     env: Env,
-    handle: Option<String>,
-    address: Option<HumanAddr>,
-    banned: bool,
+    emergency: bool,
 ) -> StdResult<HandleResponse> {
-    let mut status = Success;
-    let mut msg = None;
-
-    let config = Config::from_storage(&mut deps.storage);
-    let constants = config.constants()?;
+    let constants = ReadonlyConfig::from_storage(&deps.storage).constants()?;
 
     // permission check
     if deps.api.canonical_address(&env.message.sender)? != constants.admin {
         return Err(StdError::unauthorized());
     }
 
-    // check if address given first
-    if address.is_some() {
-        store_account_ban(
-            &mut deps.storage,
-            &deps.api.canonical_address(&address.unwrap())?,
-            banned,
-        )?;
-    } else if handle.is_some() {
-        // otherwise use handle
-        let account = get_account_for_handle(&deps.storage, &handle.unwrap())?;
-        store_account_ban(&mut deps.storage, &account, banned)?;
-    } else {
-        status = Failure;
-        msg = Some(String::from("No handle or address given."));
-    }
+    set_emergency(&mut deps.storage, emergency)?;
 
-    if banned {
+    if emergency {
         Ok(HandleResponse {
             messages: vec![],
             log: vec![],
-            data: Some(to_binary(&HandleAnswer::Ban { status, msg })?),
+            data: Some(to_binary(&HandleAnswer::EnterEmergencyMode {
+                status: Success,
+            })?),
         })
     } else {
         Ok(HandleResponse {
             messages: vec![],
             log: vec![],
-            data: Some(to_binary(&HandleAnswer::Unban { status, msg })?),
+            data: Some(to_binary(&HandleAnswer::ExitEmergencyMode {
+                status: Success,
+            })?),
         })
     }
 }
 
-pub fn try_remove_fardel<S: Storage, A: Api, Q: Querier>(
+pub fn try_refund_all_pending<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
     env: Env,
-    fardel_id: Uint128,
-    removed: bool,
+    owner: HumanAddr,
+    limit: Option<i32>,
 ) -> StdResult<HandleResponse> {
     let mut status: ResponseStatus = Success;
-    let mut msg: Option<String> = None;
+    let msg: Option<String>;
+    let mut messages: Vec<CosmosMsg> = vec![];
+    let limit = limit.unwrap_or_else(|| 10_i32);
 
-    let config = Config::from_storage(&mut deps.storage);
-    let constants = config.constants()?;
+    let constants = ReadonlyConfig::from_storage(&deps.storage).constants()?;
 
     // permission check
     if deps.api.canonical_address(&env.message.sender)? != constants.admin {
         return Err(StdError::unauthorized());
     }
 
-    let fardel_id = fardel_id.u128();
+    let owner = deps.api.canonical_address(&owner)?;
 
-    match get_fardel_by_hash(&deps.storage, fardel_id) {
-        Ok(_) => {
-            let global_id = get_global_id_by_hash(&deps.storage, fardel_id)?;
-            if removed {
-                remove_fardel(&mut deps.storage, global_id)?;
-            } else {
-                unremove_fardel(&mut deps.storage, global_id)?;
+    if !is_emergency(&deps.storage) {
+        status = Failure;
+        msg = Some(String::from("Contract is not in emergency mode."));
+    } else if limit < 1 {
+        status = Failure;
+        msg = Some(String::from("invalid limit"));
+    } else {
+        let canceled = cancel_pending_unpacks_from_start(&mut deps.storage, &owner, limit as u32)?;
+        for pending_unpack in canceled.iter() {
+            decrement_fardel_unpack_count(&mut deps.storage, pending_unpack.fardel_id);
+            // the owner is told the same way as when the unpacker cancels
+            notify(
+                &mut deps.storage,
+                &owner,
+                NotificationType::UnpackCanceled,
+                &pending_unpack.unpacker,
+                Some(pending_unpack.fardel_id),
+                env.block.time,
+            )?;
+
+            // return escrowed coins to the unpacker
+            if pending_unpack.coin.amount.u128() > 0 {
+                messages.push(CosmosMsg::Bank(BankMsg::Send {
+                    from_address: env.contract.address.clone(),
+                    to_address: deps.api.human_address(&pending_unpack.unpacker)?,
+                    amount: vec![pending_unpack.coin.clone()],
+                }));
             }
         }
-        _ => {
-            status = Failure;
-            msg = Some(String::from("No Fardel with given id."));
-        }
+        msg = Some(format!("Refunded {} pending unpacks.", canceled.len()));
     }
 
+    let remaining = get_number_of_pending_from_start(&deps.storage, &owner)? as i32;
+
     Ok(HandleResponse {
-        messages: vec![],
+        messages,
         log: vec![],
-        data: Some(to_binary(&HandleAnswer::RemoveFardel { status, msg })?),
+        data: Some(to_binary(&HandleAnswer::RefundAllPending {
+            status,
+            msg,
+            remaining,
+        })?),
     })
 }
 
-// all user functions
-
-pub fn try_register<S: Storage, A: Api, Q: Querier>(
+pub fn try_migrate<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
     env: Env,
-    handle: String,
-    description: Option<String>,
-    view_settings: Option<String>,
-    private_settings: Option<String>,
-    img: Option<String>,
-    entropy: Option<String>,
+    limit: Option<i32>,
 ) -> StdResult<HandleResponse> {
     let mut status: ResponseStatus = Success;
     let mut msg: Option<String> = None;
-    let mut key: Option<ViewingKey> = None;
-    let description = description.unwrap_or_else(|| String::from(""));
-    let view_settings = view_settings.unwrap_or_else(|| String::from(""));
-    let private_settings = private_settings.unwrap_or_else(|| String::from(""));
+    let mut done = false;
+    let limit = limit.unwrap_or_else(|| 50_i32);
 
     let constants = ReadonlyConfig::from_storage(&deps.storage).constants()?;
-    let handle = handle.trim().to_owned();
 
-    if handle.as_bytes().len() > constants.max_handle_len.into() || has_whitespace(&handle) {
-        // if handle is too long, set status message and do nothing else
-        status = Failure;
-        msg = Some(String::from("Handle is too long or has whitespace."));
-    } else if description.as_bytes().len() > constants.max_description_len.into() {
-        // if description is too long, set status message and do nothing else
-        status = Failure;
-        msg = Some(String::from("Description is too long."));
-    } else if view_settings.as_bytes().len() > constants.max_view_settings_len.into() {
-        status = Failure;
-        msg = Some(String::from("View settings are too long."));
-    } else if private_settings.as_bytes().len() > constants.max_private_settings_len.into() {
+    // permission check
+    if deps.api.canonical_address(&env.message.sender)? != constants.admin {
+        return Err(StdError::unauthorized());
+    }
+    // lists are rewritten over several calls, users must not change them in between
+    if !is_frozen(&deps.storage) {
+        return Err(StdError::generic_err(
+            "Freeze the contract before migrating.",
+        ));
+    }
+
+    if get_schema_version(&deps.storage) >= CURRENT_SCHEMA_VERSION {
+        done = true;
+        msg = Some(String::from("Storage is already at the current version."));
+    } else if limit < 1 {
         status = Failure;
-        msg = Some(String::from("Private settings are too long."));
+        msg = Some(String::from("invalid limit"));
     } else {
-        let message_sender = deps.api.canonical_address(&env.message.sender)?;
-        let handle_owner = get_account_for_handle(&deps.storage, &handle);
-        if handle_owner.is_ok() && handle_owner.unwrap() != message_sender {
-            status = Failure;
-            msg = Some(String::from("Handle is already in use."));
-        } else {
-            // check if previously registered
-            match get_account(&mut deps.storage, &message_sender) {
-                Ok(stored_account) => {
-                    // yes, delete old handle if it is different
-                    let account = stored_account.into_humanized(&deps.api)?;
-                    let old_handle = account.handle;
-                    if !handle.eq(&old_handle) {
-                        delete_handle_map(&mut deps.storage, old_handle);
-                    }
-                }
-                _ => {
-                    // new registration
-                    address_list_add(&mut deps.storage, &message_sender)?;
-                }
-            }
+        let mut budget = limit as u32;
+        let mut cursor = get_migration_cursor(&deps.storage);
+        let total_fardels = get_total_fardel_count(&deps.storage);
+        let total_accounts = get_total_number_registered_accounts(&deps.storage)?;
+
+        // fardels first, by global id
+        while budget > 0 && cursor.fardel < total_fardels {
+            upgrade_stored_fardel(&mut deps.storage, cursor.fardel)?;
+            cursor.fardel += 1;
+            budget -= 1;
+        }
 
-            let stored_account = Account {
-                owner: env.message.sender.clone(),
-                handle: handle.clone(),
-                description,
-                view_settings,
-                private_settings,
-            }
-            .into_stored(&deps.api)?;
-            map_handle_to_account(&mut deps.storage, &message_sender, handle.clone())?;
-            store_account(&mut deps.storage, stored_account, &message_sender)?;
+        // then accounts, by registration order
+        while budget > 0 && cursor.account < total_accounts {
+            let address = get_registered_address(&deps.storage, cursor.account)?;
+            upgrade_stored_account(&mut deps.storage, &address)?;
+            cursor.account += 1;
+            budget -= 1;
+        }
 
-            // if profile img sent, then store this as well
-            if img.is_some() {
-                let img: Vec<u8> = img.unwrap().as_bytes().to_vec();
-                if img.len() as u32 > constants.max_fardel_img_size {
-                    status = Failure;
-                    msg = Some(String::from(
-                        "Account registered, but profile image was too many bytes.",
-                    ));
-                } else {
-                    store_account_img(&mut deps.storage, &message_sender, img)?;
-                }
+        // then each account's follow lists, entry by entry
+        while budget > 0 && cursor.follows < total_accounts {
+            let address = get_registered_address(&deps.storage, cursor.follows)?;
+            let compacted = compact_follow_lists(
+                &mut deps.storage,
+                &address,
+                &mut cursor.follow_entries,
+                &mut budget,
+            )?;
+            // an account with a long list can run out of budget and continue on the next call
+            if compacted {
+                cursor.follows += 1;
+                budget -= 1;
             }
+        }
 
-            // if entropy was sent, then generate and return a viewing key as well
-            if entropy.is_some() {
-                let prng_seed = constants.prng_seed;
-                let viewing_key = ViewingKey::new(&env, &prng_seed, (&entropy.unwrap()).as_ref());
-                write_viewing_key(&mut deps.storage, &message_sender, &viewing_key);
-                key = Some(viewing_key);
+        // then each account's handle, collisions are recorded for GetHandleCollisions
+        while budget > 0 && cursor.handles < total_accounts {
+            let address = get_registered_address(&deps.storage, cursor.handles)?;
+            upgrade_handle_map(&mut deps.storage, &address)?;
+            cursor.handles += 1;
+            budget -= 1;
+        }
+
+        // then each account's blocked list, entry by entry
+        while budget > 0 && cursor.blocks < total_accounts {
+            let address = get_registered_address(&deps.storage, cursor.blocks)?;
+            let backfilled = backfill_blocked_list(
+                &mut deps.storage,
+                &address,
+                &mut cursor.block_entries,
+                &mut budget,
+            )?;
+            if backfilled {
+                cursor.blocks += 1;
+                budget -= 1;
             }
         }
+
+        // then each account's ban, legacy bans are added to the ban list
+        while budget > 0 && cursor.bans < total_accounts {
+            let address = get_registered_address(&deps.storage, cursor.bans)?;
+            upgrade_account_ban(&mut deps.storage, &address)?;
+            cursor.bans += 1;
+            budget -= 1;
+        }
+
+        if cursor.fardel >= total_fardels
+            && cursor.account >= total_accounts
+            && cursor.follows >= total_accounts
+            && cursor.handles >= total_accounts
+            && cursor.blocks >= total_accounts
+            && cursor.bans >= total_accounts
+        {
+            // the next schema version's migration starts from the beginning
+            set_migration_cursor(&mut deps.storage, &MigrationCursor::default())?;
+            set_schema_version(&mut deps.storage, CURRENT_SCHEMA_VERSION)?;
+            done = true;
+        } else {
+            set_migration_cursor(&mut deps.storage, &cursor)?;
+        }
     }
 
     Ok(HandleResponse {
         messages: vec![],
         log: vec![],
-        data: Some(to_binary(&HandleAnswer::Register { status, key, msg })?),
+        data: Some(to_binary(&HandleAnswer::Migrate { status, msg, done })?),
     })
 }
 
-pub fn try_set_handle<S: Storage, A: Api, Q: Querier>(
+pub fn try_start_export<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
     env: Env,
-    handle: String,
+    target: HumanAddr,
+    target_code_hash: String,
+    limit: Option<i32>,
 ) -> StdResult<HandleResponse> {
-    let mut status: ResponseStatus = Success;
-    let mut msg: Option<String> = None;
-
+    let limit = limit.unwrap_or_else(|| 50_i32);
     let constants = ReadonlyConfig::from_storage(&deps.storage).constants()?;
-    let handle = handle.trim().to_owned();
 
-    if handle.as_bytes().len() > constants.max_handle_len.into() || has_whitespace(&handle) {
-        // if handle is too long, set status message and do nothing else
-        status = Failure;
-        msg = Some(String::from("Handle is too long or has whitespace."));
-    } else {
-        match get_account_for_handle(&deps.storage, &handle) {
-            Ok(_) => {
-                status = Failure;
-                msg = Some(String::from("Handle is already in use."))
-            }
-            Err(_) => {
-                let message_sender = deps.api.canonical_address(&env.message.sender)?;
-                let mut description = String::from("");
-                let mut view_settings = String::from("");
-                let mut private_settings = String::from("");
+    // permission check
+    if deps.api.canonical_address(&env.message.sender)? != constants.admin {
+        return Err(StdError::unauthorized());
+    }
+    // older layouts are not exported, e.g. blocks made before the blocked list existed are
+    //   only listed once Migrate has backfilled it
+    if get_schema_version(&deps.storage) < CURRENT_SCHEMA_VERSION {
+        return Err(StdError::generic_err("Run Migrate before exporting."));
+    }
+    // users must not change anything while it is being exported
+    if !is_frozen(&deps.storage) {
+        return Err(StdError::generic_err(
+            "Freeze the contract before exporting.",
+        ));
+    }
+    if limit < 1 {
+        return Ok(HandleResponse {
+            messages: vec![],
+            log: vec![],
+            data: Some(to_binary(&HandleAnswer::StartExport {
+                status: Failure,
+                msg: Some(String::from("invalid limit")),
+                done: false,
+            })?),
+        });
+    }
 
-                // check if previously registered
-                match get_account(&mut deps.storage, &message_sender) {
-                    Ok(stored_account) => {
-                        // yes, delete old handle if it is different
-                        let account = stored_account.into_humanized(&deps.api)?;
-                        let old_handle = account.handle;
-                        description = account.description;
-                        view_settings = account.view_settings;
-                        private_settings = account.private_settings;
-                        if !handle.eq(&old_handle) {
-                            delete_handle_map(&mut deps.storage, old_handle);
-                        }
-                    }
-                    _ => {
-                        // new registration
-                        address_list_add(&mut deps.storage, &message_sender)?;
-                    }
-                }
-                let stored_account = Account {
-                    owner: env.message.sender,
-                    handle: handle.clone(),
+    // escrowed funds can't be moved to the new contract, pending unpacks must be refunded first
+    let total_accounts = get_total_number_registered_accounts(&deps.storage)?;
+    let mut cursor = get_export_cursor(&deps.storage);
+    let mut checked = 0;
+    while checked < limit && cursor < total_accounts {
+        let address = get_registered_address(&deps.storage, cursor)?;
+        if get_number_of_pending_from_start(&deps.storage, &address)? > 0 {
+            return Err(StdError::generic_err(format!(
+                "Account {} has pending unpacks, refund them with RefundAllPending before exporting.",
+                deps.api.human_address(&address)?
+            )));
+        }
+        cursor += 1;
+        checked += 1;
+    }
+    set_export_cursor(&mut deps.storage, cursor)?;
+    if cursor < total_accounts {
+        return Ok(HandleResponse {
+            messages: vec![],
+            log: vec![],
+            data: Some(to_binary(&HandleAnswer::StartExport {
+                status: Success,
+                msg: Some(format!(
+                    "Checked {} of {} accounts.",
+                    cursor, total_accounts
+                )),
+                done: false,
+            })?),
+        });
+    }
+
+    let mut rng_entropy: Vec<u8> = vec![];
+    rng_entropy.extend_from_slice(&env.block.height.to_be_bytes());
+    rng_entropy.extend_from_slice(&env.block.time.to_be_bytes());
+    rng_entropy.extend_from_slice(target.0.as_bytes());
+    let mut rng = Prng::new(&constants.prng_seed, &rng_entropy);
+    let export_key = sha_256(&rng.rand_bytes());
+    set_export_key(&mut deps.storage, &export_key)?;
+
+    // send the key straight to the new contract, it never goes through the admin
+    let receive_msg = HandleMsg::ReceiveImportKey {
+        key: Binary(export_key.to_vec()),
+        padding: None,
+    };
+    let messages = vec![CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: target,
+        callback_code_hash: target_code_hash,
+        msg: to_binary(&receive_msg)?,
+        send: vec![],
+    })];
+
+    Ok(HandleResponse {
+        messages,
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::StartExport {
+            status: Success,
+            msg: None,
+            done: true,
+        })?),
+    })
+}
+
+pub fn try_set_import_source<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    source: HumanAddr,
+) -> StdResult<HandleResponse> {
+    let constants = ReadonlyConfig::from_storage(&deps.storage).constants()?;
+
+    // permission check
+    if deps.api.canonical_address(&env.message.sender)? != constants.admin {
+        return Err(StdError::unauthorized());
+    }
+
+    let source = deps.api.canonical_address(&source)?;
+    set_import_source(&mut deps.storage, &source)?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::SetImportSource {
+            status: Success,
+        })?),
+    })
+}
+
+pub fn try_receive_import_key<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    key: Binary,
+) -> StdResult<HandleResponse> {
+    // only the import source contract can send the key
+    let source = get_import_source(&deps.storage)
+        .map_err(|_| StdError::generic_err("Import source has not been set."))?;
+    if deps.api.canonical_address(&env.message.sender)? != source {
+        return Err(StdError::unauthorized());
+    }
+    // records are replayed as they were exported, so they can only go into an empty contract
+    //   that users can't write to until the import is done
+    if !is_frozen(&deps.storage) {
+        return Err(StdError::generic_err(
+            "The contract must be frozen to import.",
+        ));
+    }
+    if get_total_number_registered_accounts(&deps.storage)? > 0
+        || get_total_fardel_count(&deps.storage) > 0
+    {
+        return Err(StdError::generic_err(
+            "Can only import into a contract with no accounts or fardels.",
+        ));
+    }
+
+    set_import_key(&mut deps.storage, key.as_slice())?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::ReceiveImportKey {
+            status: Success,
+        })?),
+    })
+}
+
+pub fn try_import_batch<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    records: Vec<ImportRecord>,
+) -> StdResult<HandleResponse> {
+    let constants = ReadonlyConfig::from_storage(&deps.storage).constants()?;
+
+    // permission check
+    if deps.api.canonical_address(&env.message.sender)? != constants.admin {
+        return Err(StdError::unauthorized());
+    }
+    // the key is cleared when the contract is unfrozen
+    let import_key = get_import_key(&deps.storage)?;
+
+    let mut imported: i32 = 0;
+    for record in records {
+        match record {
+            ImportRecord::Settings(settings) => {
+                set_reserved_handles(&mut deps.storage, &settings.reserved_handles)?;
+                set_message_fee(&mut deps.storage, settings.message_fee.u128())?;
+            }
+            ImportRecord::Account(account) => {
+                import_account(deps, &import_key, account)?;
+            }
+            ImportRecord::Fardel(fardel) => {
+                import_fardel(deps, &import_key, fardel)?;
+            }
+            ImportRecord::Comments {
+                fardel_id,
+                comments,
+            } => {
+                let fardel_id = fardel_id.u128();
+                if fardel_id >= get_total_fardel_count(&deps.storage) {
+                    return Err(StdError::generic_err(
+                        "Comments must be imported after their fardel.",
+                    ));
+                }
+                for comment in comments {
+                    let commenter = deps.api.canonical_address(&comment.commenter)?;
+                    comment_on_fardel(&mut deps.storage, &commenter, fardel_id, comment.text)?;
+                    if comment.deleted {
+                        let comment_id = get_number_of_comments(&deps.storage, fardel_id) - 1;
+                        delete_comment(&mut deps.storage, fardel_id, comment_id)?;
+                    }
+                }
+            }
+            ImportRecord::Revisions {
+                fardel_id,
+                revisions,
+            } => {
+                let fardel_id = fardel_id.u128();
+                if fardel_id >= get_total_fardel_count(&deps.storage) {
+                    return Err(StdError::generic_err(
+                        "Revisions must be imported after their fardel.",
+                    ));
+                }
+                for revision in revisions {
+                    let idx = get_number_of_revisions(&deps.storage, fardel_id);
+                    let label = [
+                        b"revision".as_ref(),
+                        &fardel_id.to_be_bytes(),
+                        &idx.to_be_bytes(),
+                    ]
+                    .concat();
+                    let contents_data = decrypt_export_data(
+                        &import_key,
+                        &label,
+                        revision.contents_data.as_slice(),
+                    )?;
+                    append_revision(
+                        &mut deps.storage,
+                        fardel_id,
+                        &StoredRevision {
+                            public_message: revision.public_message.as_bytes().to_vec(),
+                            tags: revision
+                                .tags
+                                .iter()
+                                .map(|tag| tag.as_bytes().to_vec())
+                                .collect(),
+                            contents_data,
+                            timestamp: revision.timestamp as u64,
+                        },
+                    )?;
+                }
+            }
+            ImportRecord::Restocks {
+                fardel_id,
+                restocks,
+            } => {
+                let fardel_id = fardel_id.u128();
+                if fardel_id >= get_total_fardel_count(&deps.storage) {
+                    return Err(StdError::generic_err(
+                        "Restocks must be imported after their fardel.",
+                    ));
+                }
+                for restock in restocks {
+                    append_restock(
+                        &mut deps.storage,
+                        fardel_id,
+                        &StoredRestock {
+                            added: restock.added as u16,
+                            countable: restock.countable as u16,
+                            seal_time: restock.seal_time as u64,
+                            timestamp: restock.timestamp as u64,
+                        },
+                    )?;
+                }
+            }
+            ImportRecord::AccountList {
+                owner,
+                start,
+                entries,
+            } => {
+                let owner = deps.api.canonical_address(&owner)?;
+                for (offset, entry) in entries.into_iter().enumerate() {
+                    let idx = start as u32 + offset as u32;
+                    import_account_list_entry(deps, &import_key, &owner, idx, entry)?;
+                }
+            }
+        }
+        imported += 1;
+    }
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::ImportBatch {
+            status: Success,
+            msg: None,
+            imported,
+        })?),
+    })
+}
+
+fn import_account<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    import_key: &[u8],
+    account: AccountExport,
+) -> StdResult<()> {
+    let owner = deps.api.canonical_address(&account.address)?;
+    if get_account(&deps.storage, &owner).is_ok() {
+        return Err(StdError::generic_err(format!(
+            "Account {} has already been imported.",
+            account.address
+        )));
+    }
+
+    let private_label = [b"private-settings".as_ref(), owner.as_slice()].concat();
+    let private_settings = decrypt_export_data(
+        import_key,
+        &private_label,
+        account.private_settings.as_slice(),
+    )?;
+    let private_settings = String::from_utf8(private_settings)
+        .map_err(|_| StdError::generic_err("Invalid private settings."))?;
+
+    address_list_add(&mut deps.storage, &owner)?;
+    let stored_account = Account {
+        owner: account.address.clone(),
+        handle: account.handle.clone(),
+        description: account.description,
+        view_settings: account.view_settings,
+        private_settings,
+    }
+    .into_stored(&deps.api)?;
+    store_account(&mut deps.storage, stored_account, &owner)?;
+    if !account.handle.is_empty() {
+        // handles from older contracts may only differ by case
+        map_normalized_handle(&mut deps.storage, &owner, account.handle)?;
+    }
+    if !account.img.is_empty() {
+        store_account_img(&mut deps.storage, &owner, account.img.as_bytes().to_vec())?;
+    }
+    if let Some(viewing_key) = account.viewing_key {
+        let key_label = [b"viewing-key".as_ref(), owner.as_slice()].concat();
+        let hashed_key = decrypt_export_data(import_key, &key_label, viewing_key.as_slice())?;
+        write_viewing_key_hash(&mut deps.storage, &owner, &hashed_key);
+    }
+    if account.deactivated {
+        store_account_deactivated(&mut deps.storage, &owner, true)?;
+    }
+    if account.private {
+        store_account_private(&mut deps.storage, &owner, true)?;
+    }
+    if let Some(ban) = account.ban {
+        store_account_ban(
+            &mut deps.storage,
+            &owner,
+            ban.banned,
+            ban.expiry as u64,
+            ban.reason.as_bytes().to_vec(),
+            ban.timestamp as u64,
+        )?;
+    }
+    for (idx, named) in account.named_viewing_keys.into_iter().enumerate() {
+        let named_label = [
+            b"named-viewing-key".as_ref(),
+            owner.as_slice(),
+            &(idx as u32).to_be_bytes(),
+        ]
+        .concat();
+        let hashed_key =
+            decrypt_export_data(import_key, &named_label, named.hashed_key.as_slice())?;
+        import_named_viewing_key(
+            &mut deps.storage,
+            &owner,
+            &StoredNamedViewingKey {
+                name: named.name.as_bytes().to_vec(),
+                hashed_key,
+                scopes: named.scopes,
+                timestamp: named.timestamp as u64,
+                revoked: named.revoked,
+            },
+        )?;
+    }
+    let mutes_label = [b"mutes".as_ref(), owner.as_slice()].concat();
+    let mutes: MutesExport = from_slice(&decrypt_export_data(
+        import_key,
+        &mutes_label,
+        account.mutes.as_slice(),
+    )?)?;
+    if !mutes.accounts.is_empty() || !mutes.tags.is_empty() {
+        let mutes = StoredMutes {
+            accounts: mutes
+                .accounts
+                .iter()
+                .map(|muted| deps.api.canonical_address(muted))
+                .collect::<StdResult<Vec<CanonicalAddr>>>()?,
+            tags: mutes.tags,
+        };
+        set_mutes(&mut deps.storage, &owner, &mutes)?;
+    }
+    if !account.notification_opt_outs.is_empty() {
+        set_notification_opt_outs(&mut deps.storage, &owner, &account.notification_opt_outs)?;
+    }
+    // the read marker is an index into the notifications, which are imported as a list
+    set_notifications_read(&mut deps.storage, &owner, account.notifications_read as u32)?;
+    Ok(())
+}
+
+fn import_fardel<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    import_key: &[u8],
+    fardel: FardelExport,
+) -> StdResult<()> {
+    // fardels must arrive in order so global ids are preserved
+    let global_id = fardel.global_id.u128();
+    if global_id != get_total_fardel_count(&deps.storage) {
+        return Err(StdError::generic_err(format!(
+            "Expected fardel with global id {}.",
+            get_total_fardel_count(&deps.storage)
+        )));
+    }
+
+    let owner = deps.api.canonical_address(&fardel.owner)?;
+    let label = [b"contents".as_ref(), &global_id.to_be_bytes()].concat();
+    let contents_data = decrypt_export_data(import_key, &label, fardel.contents_data.as_slice())?;
+    let tags: Vec<Vec<u8>> = fardel
+        .tags
+        .iter()
+        .map(|tag| tag.as_bytes().to_vec())
+        .collect();
+
+    store_fardel(
+        &mut deps.storage,
+        fardel.hash_id.u128(),
+        &owner,
+        fardel.public_message.as_bytes().to_vec(),
+        tags,
+        contents_data,
+        fardel.cost.u128(),
+        fardel.countable as u16,
+        fardel.approval_req,
+        fardel.seal_time as u64,
+        fardel.timestamp as u64,
+    )?;
+    if !fardel.img.is_empty() {
+        store_fardel_img(&mut deps.storage, global_id, fardel.img.as_bytes().to_vec())?;
+    }
+    if let Some(contents_key) = fardel.contents_key {
+        let key_label = [b"contents-key".as_ref(), &global_id.to_be_bytes()].concat();
+        let key = decrypt_export_data(import_key, &key_label, contents_key.as_slice())?;
+        store_fardel_key(&mut deps.storage, global_id, &key)?;
+    }
+    if fardel.sealed {
+        seal_fardel(&mut deps.storage, global_id)?;
+    }
+    if fardel.hidden {
+        hide_fardel(&mut deps.storage, global_id)?;
+    }
+    if fardel.removed {
+        remove_fardel(&mut deps.storage, global_id)?;
+    }
+    store_fardel_unpack_count(&mut deps.storage, global_id, fardel.unpack_count as u64)?;
+    if let Some(part_of) = fardel.part_of {
+        add_fardel_part(&mut deps.storage, &owner, part_of.u128(), global_id)?;
+    }
+    set_publish_time(&mut deps.storage, global_id, fardel.publish_time as u64)?;
+    let audience = match fardel.audience {
+        AudienceExport::Public => StoredAudience::Public,
+        AudienceExport::Followers => StoredAudience::Followers,
+        AudienceExport::AllowList { addresses } => StoredAudience::AllowList(
+            addresses
+                .iter()
+                .map(|address| deps.api.canonical_address(address))
+                .collect::<StdResult<Vec<CanonicalAddr>>>()?,
+        ),
+    };
+    set_audience(&mut deps.storage, global_id, &audience)?;
+    set_upvotes(&mut deps.storage, global_id, fardel.upvotes as u32)?;
+    set_downvotes(&mut deps.storage, global_id, fardel.downvotes as u32)?;
+    Ok(())
+}
+
+fn import_account_list_entry<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    import_key: &[u8],
+    owner: &CanonicalAddr,
+    idx: u32,
+    entry: AccountListEntry,
+) -> StdResult<()> {
+    match entry {
+        AccountListEntry::Following { who, active } => {
+            let label = export_entry_label(b"following", owner, idx);
+            let followed = decrypt_export_address(import_key, &label, &who)?;
+            // inactive relations are dropped, follower counts are rebuilt as they are replayed
+            if active && !is_following(&deps.storage, owner, &followed) {
+                store_following_address(&mut deps.storage, owner, &followed)?;
+            }
+        }
+        AccountListEntry::Unpacked {
+            fardel_id,
+            rating,
+            encrypted_contents,
+        } => {
+            let fardel_id = fardel_id.u128();
+            // owners already unpacked their own fardels when they were imported
+            if !get_unpacked_status_by_fardel_id(&deps.storage, owner, fardel_id).unpacked {
+                store_unpack(&mut deps.storage, owner, fardel_id)?;
+            }
+            // vote counts are carried in the fardel record, only the rater's record is restored
+            if let Some(rating) = rating {
+                set_rated(&mut deps.storage, owner, fardel_id, rating)?;
+            }
+            if let Some(encrypted_contents) = encrypted_contents {
+                store_encrypted_unpack(&mut deps.storage, owner, fardel_id, &encrypted_contents)?;
+            }
+        }
+        AccountListEntry::Sale {
+            fardel_id,
+            unpacker,
+            amount,
+            fee,
+            timestamp,
+        } => {
+            let unpacker = deps.api.canonical_address(&unpacker)?;
+            append_sale_tx(
+                &mut deps.storage,
+                owner.clone(),
+                unpacker,
+                fardel_id.u128(),
+                amount.u128(),
+                fee.u128(),
+                timestamp as u64,
+            )?;
+        }
+        AccountListEntry::Purchase {
+            fardel_id,
+            owner: fardel_owner,
+            amount,
+            fee,
+            timestamp,
+        } => {
+            let fardel_owner = deps.api.canonical_address(&fardel_owner)?;
+            append_purchase_tx(
+                &mut deps.storage,
+                fardel_owner,
+                owner.clone(),
+                fardel_id.u128(),
+                amount.u128(),
+                fee.u128(),
+                timestamp as u64,
+            )?;
+        }
+        AccountListEntry::Blocked { who } => {
+            let label = export_entry_label(b"blocked", owner, idx);
+            let blocked = decrypt_export_address(import_key, &label, &who)?;
+            store_account_block(&mut deps.storage, owner, &blocked, true)?;
+        }
+        AccountListEntry::FollowRequest {
+            requester,
+            timestamp,
+        } => {
+            let label = export_entry_label(b"follow-request", owner, idx);
+            let requester = decrypt_export_address(import_key, &label, &requester)?;
+            store_follow_request(&mut deps.storage, owner, &requester, timestamp as u64)?;
+        }
+        AccountListEntry::Received {
+            from,
+            text,
+            timestamp,
+            deleted,
+        } => {
+            let label = export_entry_label(b"inbox-from", owner, idx);
+            let from = decrypt_export_address(import_key, &label, &from)?;
+            let label = export_entry_label(b"inbox", owner, idx);
+            import_message(
+                deps, import_key, &label, owner, false, from, text, timestamp, deleted,
+            )?;
+        }
+        AccountListEntry::Sent {
+            to,
+            text,
+            timestamp,
+            deleted,
+        } => {
+            let label = export_entry_label(b"outbox-to", owner, idx);
+            let to = decrypt_export_address(import_key, &label, &to)?;
+            let label = export_entry_label(b"outbox", owner, idx);
+            import_message(
+                deps, import_key, &label, owner, true, to, text, timestamp, deleted,
+            )?;
+        }
+        AccountListEntry::Notification {
+            kind,
+            actor,
+            fardel_id,
+            timestamp,
+        } => {
+            let label = export_entry_label(b"notification-actor", owner, idx);
+            let actor = decrypt_export_address(import_key, &label, &actor)?;
+            push_notification(
+                &mut deps.storage,
+                owner,
+                &StoredNotification {
+                    kind,
+                    actor,
+                    fardel_id: fardel_id.map(|fardel_id| fardel_id.u128()),
+                    timestamp: timestamp as u64,
+                },
+            )?;
+        }
+        AccountListEntry::PreviousHandle { handle, timestamp } => {
+            import_released_handle(
+                &mut deps.storage,
+                &ReleasedHandle {
+                    owner: owner.clone(),
+                    handle: handle.as_bytes().to_vec(),
+                    timestamp: timestamp as u64,
+                },
+            )?;
+        }
+        AccountListEntry::Delegate {
+            who,
+            permissions,
+            expiry,
+            revoked,
+        } => {
+            let delegate = deps.api.canonical_address(&who)?;
+            store_delegation(
+                &mut deps.storage,
+                owner,
+                &delegate,
+                permissions,
+                expiry as u64,
+            )?;
+            if revoked {
+                // a fresh delegation is active at time 0, so this always marks it revoked
+                revoke_delegation(&mut deps.storage, owner, &delegate, 0)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+// decrypts the other account of an exported list entry
+fn decrypt_export_address(
+    import_key: &[u8],
+    label: &[u8],
+    address: &Binary,
+) -> StdResult<CanonicalAddr> {
+    let address = decrypt_export_data(import_key, label, address.as_slice())?;
+    Ok(CanonicalAddr(Binary(address)))
+}
+
+// appends an exported copy of a message to the owner's inbox (or outbox, if sent), the other
+//   copy is imported with the other account's list
+fn import_message<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    import_key: &[u8],
+    label: &[u8],
+    owner: &CanonicalAddr,
+    sent: bool,
+    other: CanonicalAddr,
+    text: Binary,
+    timestamp: i32,
+    deleted: bool,
+) -> StdResult<()> {
+    let text = decrypt_export_data(import_key, label, text.as_slice())?;
+    push_message(
+        &mut deps.storage,
+        owner,
+        sent,
+        &StoredMessage {
+            other,
+            text,
+            timestamp: timestamp as u64,
+            deleted,
+        },
+    )
+}
+
+pub fn try_set_pause_flags<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    carry: Option<bool>,
+    unpack: Option<bool>,
+    social: Option<bool>,
+) -> StdResult<HandleResponse> {
+    let constants = ReadonlyConfig::from_storage(&deps.storage).constants()?;
+
+    // permission check
+    if deps.api.canonical_address(&env.message.sender)? != constants.admin {
+        return Err(StdError::unauthorized());
+    }
+
+    let mut flags = get_pause_flags(&deps.storage);
+    if let Some(carry) = carry {
+        flags.carry = carry;
+    }
+    if let Some(unpack) = unpack {
+        flags.unpack = unpack;
+    }
+    if let Some(social) = social {
+        flags.social = social;
+    }
+    set_pause_flags(&mut deps.storage, &flags)?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::SetPauseFlags { status: Success })?),
+    })
+}
+
+pub fn try_set_message_fee<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    fee: Uint128,
+) -> StdResult<HandleResponse> {
+    let constants = ReadonlyConfig::from_storage(&deps.storage).constants()?;
+
+    // permission check
+    if deps.api.canonical_address(&env.message.sender)? != constants.admin {
+        return Err(StdError::unauthorized());
+    }
+
+    set_message_fee(&mut deps.storage, fee.u128())?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::SetMessageFee { status: Success })?),
+    })
+}
+
+pub fn try_reserve_handles<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    handles: Vec<String>,
+    reserve: bool,
+) -> StdResult<HandleResponse> {
+    let mut status = Success;
+    let mut msg = None;
+    let constants = ReadonlyConfig::from_storage(&deps.storage).constants()?;
+
+    // permission check
+    if deps.api.canonical_address(&env.message.sender)? != constants.admin {
+        return Err(StdError::unauthorized());
+    }
+
+    let mut reserved = get_reserved_handles(&deps.storage);
+    for handle in handles {
+        let handle = normalize_handle(handle.trim());
+        if reserve {
+            if !reserved.contains(&handle) {
+                reserved.push(handle);
+            }
+        } else {
+            reserved.retain(|reserved| *reserved != handle);
+        }
+    }
+
+    if reserved.len() > MAX_RESERVED_HANDLES {
+        status = Failure;
+        msg = Some(format!(
+            "Cannot reserve more than {} handles.",
+            MAX_RESERVED_HANDLES
+        ));
+    } else {
+        set_reserved_handles(&mut deps.storage, &reserved)?;
+    }
+
+    let data = if reserve {
+        HandleAnswer::ReserveHandles { status, msg }
+    } else {
+        HandleAnswer::UnreserveHandles { status, msg }
+    };
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&data)?),
+    })
+}
+
+pub fn try_store_ban<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    handle: Option<String>,
+    address: Option<HumanAddr>,
+    banned: bool,
+    expiry: Option<i32>,
+    reason: Option<String>,
+) -> StdResult<HandleResponse> {
+    let mut status = Success;
+    let mut msg = None;
+
+    let config = Config::from_storage(&mut deps.storage);
+    let constants = config.constants()?;
+
+    // permission check
+    if deps.api.canonical_address(&env.message.sender)? != constants.admin {
+        return Err(StdError::unauthorized());
+    }
+
+    // 0 expiry means the ban is permanent
+    let expiry = valid_expiry(expiry)?;
+    let reason = reason.unwrap_or_else(|| String::from(""));
+
+    if reason.as_bytes().len() > constants.max_description_len.into() {
+        status = Failure;
+        msg = Some(String::from("Ban reason is too long."));
+    } else if banned && expiry > 0 && expiry <= env.block.time {
+        status = Failure;
+        msg = Some(String::from("Ban expiry is in the past."));
+    // check if address given first
+    } else if address.is_some() {
+        store_account_ban(
+            &mut deps.storage,
+            &deps.api.canonical_address(&address.unwrap())?,
+            banned,
+            expiry,
+            reason.as_bytes().to_vec(),
+            env.block.time,
+        )?;
+    } else if handle.is_some() {
+        // otherwise use handle
+        let account = get_account_for_handle(&deps.storage, &handle.unwrap())?;
+        store_account_ban(
+            &mut deps.storage,
+            &account,
+            banned,
+            expiry,
+            reason.as_bytes().to_vec(),
+            env.block.time,
+        )?;
+    } else {
+        status = Failure;
+        msg = Some(String::from("No handle or address given."));
+    }
+
+    if banned {
+        Ok(HandleResponse {
+            messages: vec![],
+            log: vec![],
+            data: Some(to_binary(&HandleAnswer::Ban { status, msg })?),
+        })
+    } else {
+        Ok(HandleResponse {
+            messages: vec![],
+            log: vec![],
+            data: Some(to_binary(&HandleAnswer::Unban { status, msg })?),
+        })
+    }
+}
+
+pub fn try_remove_fardel<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    fardel_id: Uint128,
+    removed: bool,
+) -> StdResult<HandleResponse> {
+    let mut status: ResponseStatus = Success;
+    let mut msg: Option<String> = None;
+
+    let config = Config::from_storage(&mut deps.storage);
+    let constants = config.constants()?;
+
+    // permission check
+    if deps.api.canonical_address(&env.message.sender)? != constants.admin {
+        return Err(StdError::unauthorized());
+    }
+
+    let fardel_id = fardel_id.u128();
+
+    match get_fardel_by_hash(&deps.storage, fardel_id) {
+        Ok(_) => {
+            let global_id = get_global_id_by_hash(&deps.storage, fardel_id)?;
+            // parts go with the fardel they belong to
+            let parts = get_fardel_parts(&deps.storage, global_id);
+            for id in std::iter::once(global_id).chain(parts) {
+                if removed {
+                    remove_fardel(&mut deps.storage, id)?;
+                } else {
+                    unremove_fardel(&mut deps.storage, id)?;
+                }
+            }
+        }
+        _ => {
+            status = Failure;
+            msg = Some(String::from("No Fardel with given id."));
+        }
+    }
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::RemoveFardel { status, msg })?),
+    })
+}
+
+// all user functions
+
+pub fn try_register<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    handle: String,
+    description: Option<String>,
+    view_settings: Option<String>,
+    private_settings: Option<String>,
+    img: Option<String>,
+    entropy: Option<String>,
+) -> StdResult<HandleResponse> {
+    let mut status: ResponseStatus = Success;
+    let mut msg: Option<String> = None;
+    let mut key: Option<ViewingKey> = None;
+    let description = description.unwrap_or_else(|| String::from(""));
+    let view_settings = view_settings.unwrap_or_else(|| String::from(""));
+    let private_settings = private_settings.unwrap_or_else(|| String::from(""));
+
+    let constants = ReadonlyConfig::from_storage(&deps.storage).constants()?;
+    let handle = handle.trim().to_owned();
+
+    let message_sender = deps.api.canonical_address(&env.message.sender)?;
+
+    if handle.as_bytes().len() > constants.max_handle_len.into() || !valid_handle_chars(&handle) {
+        // if handle is too long, set status message and do nothing else
+        status = Failure;
+        msg = Some(String::from(
+            "Handle is too long or has characters other than letters, numbers, '_', '-' or '.'.",
+        ));
+    } else if is_reserved_handle(&deps.storage, &handle) && message_sender != constants.admin {
+        status = Failure;
+        msg = Some(String::from("Handle is reserved."));
+    } else if description.as_bytes().len() > constants.max_description_len.into() {
+        // if description is too long, set status message and do nothing else
+        status = Failure;
+        msg = Some(String::from("Description is too long."));
+    } else if view_settings.as_bytes().len() > constants.max_view_settings_len.into() {
+        status = Failure;
+        msg = Some(String::from("View settings are too long."));
+    } else if private_settings.as_bytes().len() > constants.max_private_settings_len.into() {
+        status = Failure;
+        msg = Some(String::from("Private settings are too long."));
+    } else {
+        let handle_owner = get_account_for_handle(&deps.storage, &handle);
+        if handle_owner.is_ok() && handle_owner.unwrap() != message_sender {
+            status = Failure;
+            msg = Some(String::from("Handle is already in use."));
+        } else {
+            // check if previously registered
+            match get_account(&mut deps.storage, &message_sender) {
+                Ok(stored_account) => {
+                    // yes, release old handle if it is different, a case-only rename keeps
+                    //   the same handle map entry
+                    let account = stored_account.into_humanized(&deps.api)?;
+                    let old_handle = account.handle;
+                    if normalize_handle(&handle) != normalize_handle(&old_handle) {
+                        release_handle(
+                            &mut deps.storage,
+                            &message_sender,
+                            old_handle,
+                            env.block.time,
+                        )?;
+                    }
+                }
+                _ => {
+                    // new registration
+                    address_list_add(&mut deps.storage, &message_sender)?;
+                }
+            }
+
+            let stored_account = Account {
+                owner: env.message.sender.clone(),
+                handle: handle.clone(),
+                description,
+                view_settings,
+                private_settings,
+            }
+            .into_stored(&deps.api)?;
+            map_handle_to_account(&mut deps.storage, &message_sender, handle.clone())?;
+            store_account(&mut deps.storage, stored_account, &message_sender)?;
+
+            // if profile img sent, then store this as well
+            if img.is_some() {
+                let img: Vec<u8> = img.unwrap().as_bytes().to_vec();
+                if img.len() as u32 > constants.max_fardel_img_size {
+                    status = Failure;
+                    msg = Some(String::from(
+                        "Account registered, but profile image was too many bytes.",
+                    ));
+                } else {
+                    store_account_img(&mut deps.storage, &message_sender, img)?;
+                }
+            }
+
+            // if entropy was sent, then generate and return a viewing key as well
+            if entropy.is_some() {
+                let prng_seed = constants.prng_seed;
+                let viewing_key = ViewingKey::new(&env, &prng_seed, (&entropy.unwrap()).as_ref());
+                write_viewing_key(&mut deps.storage, &message_sender, &viewing_key);
+                key = Some(viewing_key);
+            }
+        }
+    }
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::Register { status, key, msg })?),
+    })
+}
+
+pub fn try_set_handle<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    handle: String,
+) -> StdResult<HandleResponse> {
+    let mut status: ResponseStatus = Success;
+    let mut msg: Option<String> = None;
+
+    let constants = ReadonlyConfig::from_storage(&deps.storage).constants()?;
+    let handle = handle.trim().to_owned();
+
+    let message_sender = deps.api.canonical_address(&env.message.sender)?;
+
+    if handle.as_bytes().len() > constants.max_handle_len.into() || !valid_handle_chars(&handle) {
+        // if handle is too long, set status message and do nothing else
+        status = Failure;
+        msg = Some(String::from(
+            "Handle is too long or has characters other than letters, numbers, '_', '-' or '.'.",
+        ));
+    } else if is_reserved_handle(&deps.storage, &handle) && message_sender != constants.admin {
+        status = Failure;
+        msg = Some(String::from("Handle is reserved."));
+    } else {
+        match get_account_for_handle(&deps.storage, &handle) {
+            // an account can change the case of its own handle
+            Ok(handle_owner) if handle_owner != message_sender => {
+                status = Failure;
+                msg = Some(String::from("Handle is already in use."))
+            }
+            _ => {
+                let mut description = String::from("");
+                let mut view_settings = String::from("");
+                let mut private_settings = String::from("");
+
+                // check if previously registered
+                match get_account(&mut deps.storage, &message_sender) {
+                    Ok(stored_account) => {
+                        // yes, release old handle if it is different, a case-only rename
+                        //   keeps the same handle map entry
+                        let account = stored_account.into_humanized(&deps.api)?;
+                        let old_handle = account.handle;
+                        description = account.description;
+                        view_settings = account.view_settings;
+                        private_settings = account.private_settings;
+                        if normalize_handle(&handle) != normalize_handle(&old_handle) {
+                            release_handle(
+                                &mut deps.storage,
+                                &message_sender,
+                                old_handle,
+                                env.block.time,
+                            )?;
+                        }
+                    }
+                    _ => {
+                        // new registration
+                        address_list_add(&mut deps.storage, &message_sender)?;
+                    }
+                }
+                let stored_account = Account {
+                    owner: env.message.sender,
+                    handle: handle.clone(),
                     description,
                     view_settings,
                     private_settings,
@@ -544,6 +1691,7 @@ pub fn try_set_private_settings<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
     env: Env,
     private_settings: String,
+    notification_opt_outs: Option<Vec<NotificationType>>,
 ) -> StdResult<HandleResponse> {
     let mut status: ResponseStatus = Success;
     let mut msg: Option<String> = None;
@@ -568,6 +1716,9 @@ pub fn try_set_private_settings<S: Storage, A: Api, Q: Querier>(
                 }
                 .into_stored(&deps.api)?;
                 store_account(&mut deps.storage, stored_account, &message_sender)?;
+                if let Some(opt_outs) = notification_opt_outs {
+                    set_notification_opt_outs(&mut deps.storage, &message_sender, &opt_outs)?;
+                }
             }
             _ => {
                 status = Failure;
@@ -579,57 +1730,241 @@ pub fn try_set_private_settings<S: Storage, A: Api, Q: Querier>(
     Ok(HandleResponse {
         messages: vec![],
         log: vec![],
-        data: Some(to_binary(&HandleAnswer::SetPrivateSettings {
-            status,
-            msg,
-        })?),
+        data: Some(to_binary(&HandleAnswer::SetPrivateSettings {
+            status,
+            msg,
+        })?),
+    })
+}
+
+pub fn try_set_profile_img<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    img: String,
+) -> StdResult<HandleResponse> {
+    let constants = ReadonlyConfig::from_storage(&deps.storage).constants()?;
+
+    let mut status: ResponseStatus = Success;
+    let mut msg: Option<String> = None;
+    let message_sender = deps.api.canonical_address(&env.message.sender)?;
+
+    let img: Vec<u8> = img.as_bytes().to_vec();
+    if img.len() as u32 > constants.max_profile_img_size {
+        status = Failure;
+        msg = Some(String::from("Profile image is too large."));
+    } else {
+        store_account_img(&mut deps.storage, &message_sender, img)?;
+    }
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::SetProfileImg { status, msg })?),
+    })
+}
+
+pub fn try_generate_viewing_key<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    entropy: String,
+    name: Option<String>,
+    scopes: Option<Vec<Permission>>,
+) -> StdResult<HandleResponse> {
+    let constants = ReadonlyConfig::from_storage(&deps.storage).constants()?;
+    let prng_seed = constants.prng_seed;
+
+    let key = ViewingKey::new(&env, &prng_seed, (&entropy).as_ref());
+
+    let message_sender = deps.api.canonical_address(&env.message.sender)?;
+
+    if let Some(error) = store_viewing_key(deps, &env, &message_sender, &key, name, scopes)? {
+        return Err(StdError::generic_err(error));
+    }
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::GenerateViewingKey { key })?),
+    })
+}
+
+// writes the default key if there is no name, otherwise a named key
+//   returns a failure message if the named key could not be stored
+fn store_viewing_key<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: &Env,
+    owner: &CanonicalAddr,
+    key: &ViewingKey,
+    name: Option<String>,
+    scopes: Option<Vec<Permission>>,
+) -> StdResult<Option<String>> {
+    let name = match name {
+        Some(name) => name,
+        None => {
+            write_viewing_key(&mut deps.storage, owner, key);
+            return Ok(None);
+        }
+    };
+
+    if name.is_empty() || name.len() > MAX_VIEWING_KEY_NAME_LEN {
+        return Ok(Some(format!(
+            "Key name must be between 1 and {} bytes.",
+            MAX_VIEWING_KEY_NAME_LEN
+        )));
+    }
+    let scopes = scopes.unwrap_or_else(|| vec![Permission::Owner]);
+    if scopes.is_empty() {
+        return Ok(Some(String::from("Key must have at least one scope.")));
+    }
+
+    let stored = write_named_viewing_key(
+        &mut deps.storage,
+        owner,
+        &name,
+        key,
+        scopes,
+        env.block.time,
+        MAX_NAMED_VIEWING_KEYS,
+    )?;
+    if !stored {
+        return Ok(Some(format!(
+            "Cannot have more than {} named viewing keys.",
+            MAX_NAMED_VIEWING_KEYS
+        )));
+    }
+    Ok(None)
+}
+
+pub fn try_revoke_viewing_key<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    name: String,
+) -> StdResult<HandleResponse> {
+    let mut status = Success;
+    let mut msg = None;
+
+    let message_sender = deps.api.canonical_address(&env.message.sender)?;
+    if !revoke_named_viewing_key(&mut deps.storage, &message_sender, &name)? {
+        status = Failure;
+        msg = Some(String::from("No viewing key with that name."));
+    }
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::RevokeViewingKey { status, msg })?),
+    })
+}
+
+pub fn try_add_delegate<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    delegate: HumanAddr,
+    permissions: Vec<DelegatePermission>,
+    expiry: Option<i32>,
+) -> StdResult<HandleResponse> {
+    let mut status: ResponseStatus = Success;
+    let mut msg: Option<String> = None;
+
+    let owner = deps.api.canonical_address(&env.message.sender)?;
+    let delegate = deps.api.canonical_address(&delegate)?;
+    // 0 expiry means the delegation does not expire
+    let expiry = valid_expiry(expiry)?;
+
+    if delegate == owner {
+        status = Failure;
+        msg = Some(String::from("Cannot delegate to yourself."));
+    } else if permissions.is_empty() {
+        status = Failure;
+        msg = Some(String::from(
+            "Delegation must have at least one permission.",
+        ));
+    } else if expiry > 0 && expiry <= env.block.time {
+        status = Failure;
+        msg = Some(String::from("Delegation expiry is in the past."));
+    } else if !get_delegation(&deps.storage, &owner, &delegate)
+        .map_or(false, |delegation| delegation.is_active(env.block.time))
+        && get_number_of_active_delegates(&deps.storage, &owner, env.block.time)? >= MAX_DELEGATES
+    {
+        status = Failure;
+        msg = Some(format!(
+            "Cannot have more than {} delegates.",
+            MAX_DELEGATES
+        ));
+    } else {
+        store_delegation(&mut deps.storage, &owner, &delegate, permissions, expiry)?;
+    }
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::AddDelegate { status, msg })?),
     })
 }
 
-pub fn try_set_profile_img<S: Storage, A: Api, Q: Querier>(
+pub fn try_remove_delegate<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
     env: Env,
-    img: String,
+    delegate: HumanAddr,
 ) -> StdResult<HandleResponse> {
-    let constants = ReadonlyConfig::from_storage(&deps.storage).constants()?;
-
     let mut status: ResponseStatus = Success;
     let mut msg: Option<String> = None;
-    let message_sender = deps.api.canonical_address(&env.message.sender)?;
 
-    let img: Vec<u8> = img.as_bytes().to_vec();
-    if img.len() as u32 > constants.max_profile_img_size {
+    let owner = deps.api.canonical_address(&env.message.sender)?;
+    let delegate = deps.api.canonical_address(&delegate)?;
+    if !revoke_delegation(&mut deps.storage, &owner, &delegate, env.block.time)? {
         status = Failure;
-        msg = Some(String::from("Profile image is too large."));
-    } else {
-        store_account_img(&mut deps.storage, &message_sender, img)?;
+        msg = Some(String::from("That address is not a delegate."));
     }
 
     Ok(HandleResponse {
         messages: vec![],
         log: vec![],
-        data: Some(to_binary(&HandleAnswer::SetProfileImg { status, msg })?),
+        data: Some(to_binary(&HandleAnswer::RemoveDelegate { status, msg })?),
     })
 }
 
-pub fn try_generate_viewing_key<S: Storage, A: Api, Q: Querier>(
+// returns the account a handle function acts for, which is the sender unless on_behalf_of
+//   is an account that has made the sender a delegate with the given permission
+fn get_acting_account<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    env: &Env,
+    on_behalf_of: Option<HumanAddr>,
+    permission: DelegatePermission,
+) -> StdResult<CanonicalAddr> {
+    let sender = deps.api.canonical_address(&env.message.sender)?;
+    let owner = match on_behalf_of {
+        Some(owner) => deps.api.canonical_address(&owner)?,
+        None => return Ok(sender),
+    };
+    if owner == sender {
+        return Ok(sender);
+    }
+
+    match get_delegation(&deps.storage, &owner, &sender) {
+        Some(delegation)
+            if delegation.allows(&permission, env.block.time)
+                && !is_banned_at(&deps.storage, &owner, env.block.time)
+                && !is_deactivated(&deps.storage, &owner) =>
+        {
+            Ok(owner)
+        }
+        _ => Err(StdError::unauthorized()),
+    }
+}
+
+pub fn try_revoke_permit<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
     env: Env,
-    entropy: String,
+    permit_name: String,
 ) -> StdResult<HandleResponse> {
-    let constants = ReadonlyConfig::from_storage(&deps.storage).constants()?;
-    let prng_seed = constants.prng_seed;
-
-    let key = ViewingKey::new(&env, &prng_seed, (&entropy).as_ref());
-
     let message_sender = deps.api.canonical_address(&env.message.sender)?;
-
-    write_viewing_key(&mut deps.storage, &message_sender, &key);
+    revoke_permit(&mut deps.storage, &message_sender, &permit_name)?;
 
     Ok(HandleResponse {
         messages: vec![],
         log: vec![],
-        data: Some(to_binary(&HandleAnswer::GenerateViewingKey { key })?),
+        data: Some(to_binary(&HandleAnswer::RevokePermit { status: Success })?),
     })
 }
 
@@ -637,9 +1972,11 @@ pub fn try_set_viewing_key<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
     env: Env,
     key: String,
+    name: Option<String>,
+    scopes: Option<Vec<Permission>>,
 ) -> StdResult<HandleResponse> {
     let mut status = Success;
-    let mut msg = None;
+    let msg;
 
     if key.as_bytes().len() < 8 {
         status = Failure;
@@ -649,7 +1986,10 @@ pub fn try_set_viewing_key<S: Storage, A: Api, Q: Querier>(
 
         let message_sender = deps.api.canonical_address(&env.message.sender)?;
 
-        write_viewing_key(&mut deps.storage, &message_sender, &vk);
+        msg = store_viewing_key(deps, &env, &message_sender, &vk, name, scopes)?;
+        if msg.is_some() {
+            status = Failure;
+        }
     }
 
     Ok(HandleResponse {
@@ -686,19 +2026,89 @@ pub fn try_store_deactivate<S: Storage, A: Api, Q: Querier>(
     }
 }
 
+pub fn try_set_account_privacy<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    private: bool,
+) -> StdResult<HandleResponse> {
+    let message_sender = deps.api.canonical_address(&env.message.sender)?;
+
+    // pending follow requests are kept, so they can still be answered after going public
+    store_account_private(&mut deps.storage, &message_sender, private)?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::SetAccountPrivacy {
+            status: Success,
+        })?),
+    })
+}
+
 pub fn try_store_block<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
     env: Env,
     handle: String,
     block: bool,
+    purge_comments: bool,
+    limit: Option<i32>,
 ) -> StdResult<HandleResponse> {
     let mut status = Success;
     let mut msg = None;
+    let mut done = true;
+    let limit = limit.unwrap_or_else(|| 50_i32);
 
     let blocker = deps.api.canonical_address(&env.message.sender)?;
     match get_account_for_handle(&deps.storage, &handle) {
+        Ok(_) if block && purge_comments && limit < 1 => {
+            status = Failure;
+            msg = Some(String::from("invalid limit"));
+        }
         Ok(blocked) => {
             store_account_block(&mut deps.storage, &blocker, &blocked, block)?;
+            if block {
+                // sever follows (and pending requests) both ways
+                remove_following_address(&mut deps.storage, &blocker, &blocked)?;
+                remove_following_address(&mut deps.storage, &blocked, &blocker)?;
+                clear_follow_request(&mut deps.storage, &blocker, &blocked)?;
+                clear_follow_request(&mut deps.storage, &blocked, &blocker)?;
+            }
+            if block && purge_comments {
+                // each comment checked and each fardel finished costs one unit of budget,
+                //   the purge picks up where it stopped when Block is sent again
+                let mut budget = limit as u32;
+                let mut progress = get_block_purge(&deps.storage, &blocker, &blocked);
+                let number_of_fardels = get_number_of_fardels(&deps.storage, &blocker);
+                while budget > 0 && progress.fardel < number_of_fardels {
+                    let fardel_id = get_fardel_id_at(&deps.storage, &blocker, progress.fardel)?;
+                    let purged = purge_comments_by(
+                        &mut deps.storage,
+                        fardel_id,
+                        &blocked,
+                        &mut progress.comment,
+                        &mut budget,
+                    )?;
+                    if purged && budget > 0 {
+                        remove_rating_by(&mut deps.storage, fardel_id, &blocked)?;
+                        progress.fardel += 1;
+                        progress.comment = 0;
+                        budget -= 1;
+                    }
+                }
+                done = progress.fardel >= number_of_fardels;
+                if done {
+                    progress = BlockPurge::default();
+                }
+                set_block_purge(&mut deps.storage, &blocker, &blocked, &progress)?;
+            } else if !block {
+                // an unfinished purge starts over if the account is blocked again
+                set_block_purge(
+                    &mut deps.storage,
+                    &blocker,
+                    &blocked,
+                    &BlockPurge::default(),
+                )?;
+            }
         }
         _ => {
             status = Failure;
@@ -710,7 +2120,7 @@ pub fn try_store_block<S: Storage, A: Api, Q: Querier>(
         Ok(HandleResponse {
             messages: vec![],
             log: vec![],
-            data: Some(to_binary(&HandleAnswer::Block { status, msg })?),
+            data: Some(to_binary(&HandleAnswer::Block { status, msg, done })?),
         })
     } else {
         Ok(HandleResponse {
@@ -721,6 +2131,74 @@ pub fn try_store_block<S: Storage, A: Api, Q: Querier>(
     }
 }
 
+pub fn try_mute<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    handles: Option<Vec<String>>,
+    tags: Option<Vec<String>>,
+    mute: bool,
+) -> StdResult<HandleResponse> {
+    let mut status = Success;
+    let mut msg = None;
+
+    let constants = ReadonlyConfig::from_storage(&deps.storage).constants()?;
+    let message_sender = deps.api.canonical_address(&env.message.sender)?;
+    let mut mutes = get_mutes(&deps.storage, &message_sender);
+
+    for handle in handles.unwrap_or_default() {
+        match get_account_for_handle(&deps.storage, &handle) {
+            Ok(account) => {
+                if mute && !mutes.accounts.contains(&account) {
+                    mutes.accounts.push(account);
+                } else if !mute {
+                    mutes.accounts.retain(|muted| muted != &account);
+                }
+            }
+            _ => {
+                status = Failure;
+                msg = Some(format!("Handle {} not in use.", handle));
+            }
+        }
+    }
+    for tag in tags.unwrap_or_default() {
+        // same limit as fardel tags, a longer tag could never match
+        if tag.as_bytes().len() > constants.max_tag_len.into() {
+            status = Failure;
+            msg = Some(String::from("Invalid tag"));
+            continue;
+        }
+        let tag = tag.to_lowercase();
+        if mute && !mutes.tags.contains(&tag) {
+            mutes.tags.push(tag);
+        } else if !mute {
+            mutes.tags.retain(|muted| muted != &tag);
+        }
+    }
+    if mutes.accounts.len() > MAX_MUTES || mutes.tags.len() > MAX_MUTES {
+        status = Failure;
+        msg = Some(String::from("Too many mutes."));
+    }
+
+    // nothing is saved if any part failed
+    if status == Success {
+        set_mutes(&mut deps.storage, &message_sender, &mutes)?;
+    }
+
+    if mute {
+        Ok(HandleResponse {
+            messages: vec![],
+            log: vec![],
+            data: Some(to_binary(&HandleAnswer::Mute { status, msg })?),
+        })
+    } else {
+        Ok(HandleResponse {
+            messages: vec![],
+            log: vec![],
+            data: Some(to_binary(&HandleAnswer::Unmute { status, msg })?),
+        })
+    }
+}
+
 pub fn try_follow<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
     env: Env,
@@ -729,7 +2207,7 @@ pub fn try_follow<S: Storage, A: Api, Q: Querier>(
     let message_sender = deps.api.canonical_address(&env.message.sender)?;
 
     let account_to_follow = get_account_for_handle(&deps.storage, &handle)?;
-    if is_banned(&deps.storage, &account_to_follow) {
+    if is_banned_at(&deps.storage, &account_to_follow, env.block.time) {
         return Err(StdError::generic_err("Account has been banned."));
     } else if is_deactivated(&deps.storage, &account_to_follow) {
         return Err(StdError::generic_err("Account has been deactivated."));
@@ -737,12 +2215,55 @@ pub fn try_follow<S: Storage, A: Api, Q: Querier>(
         return Err(StdError::unauthorized());
     };
 
-    store_following(&mut deps.storage, &message_sender, handle)?;
+    // already following, nothing to store or count again
+    if is_following(&deps.storage, &message_sender, &account_to_follow) {
+        return Ok(HandleResponse {
+            messages: vec![],
+            log: vec![],
+            data: Some(to_binary(&HandleAnswer::Follow {
+                status: Success,
+                pending: false,
+            })?),
+        });
+    }
+
+    // following a private account sends a request
+    let pending = is_private_account(&deps.storage, &account_to_follow)
+        && account_to_follow != message_sender;
+    if pending {
+        store_follow_request(
+            &mut deps.storage,
+            &account_to_follow,
+            &message_sender,
+            env.block.time,
+        )?;
+        notify(
+            &mut deps.storage,
+            &account_to_follow,
+            NotificationType::FollowRequest,
+            &message_sender,
+            None,
+            env.block.time,
+        )?;
+    } else {
+        store_following(&mut deps.storage, &message_sender, handle)?;
+        notify(
+            &mut deps.storage,
+            &account_to_follow,
+            NotificationType::Follow,
+            &message_sender,
+            None,
+            env.block.time,
+        )?;
+    }
 
     Ok(HandleResponse {
         messages: vec![],
         log: vec![],
-        data: Some(to_binary(&HandleAnswer::Follow { status: Success })?),
+        data: Some(to_binary(&HandleAnswer::Follow {
+            status: Success,
+            pending,
+        })?),
     })
 }
 
@@ -752,6 +2273,9 @@ pub fn try_unfollow<S: Storage, A: Api, Q: Querier>(
     handle: String,
 ) -> StdResult<HandleResponse> {
     let message_sender = deps.api.canonical_address(&env.message.sender)?;
+    let followed_addr = get_account_for_handle(&deps.storage, &handle)?;
+    // also cancels a pending follow request
+    clear_follow_request(&mut deps.storage, &followed_addr, &message_sender)?;
     remove_following(&mut deps.storage, &message_sender, handle)?;
 
     Ok(HandleResponse {
@@ -761,6 +2285,225 @@ pub fn try_unfollow<S: Storage, A: Api, Q: Querier>(
     })
 }
 
+pub fn try_remove_follower<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    handle: String,
+) -> StdResult<HandleResponse> {
+    let mut status = Success;
+    let mut msg = None;
+
+    let message_sender = deps.api.canonical_address(&env.message.sender)?;
+    match get_account_for_handle(&deps.storage, &handle) {
+        Ok(follower) => {
+            // a pending request from them is dropped as well
+            clear_follow_request(&mut deps.storage, &message_sender, &follower)?;
+            if is_following(&deps.storage, &follower, &message_sender) {
+                remove_following_address(&mut deps.storage, &follower, &message_sender)?;
+            } else {
+                status = Failure;
+                msg = Some(String::from("That handle is not following you."));
+            }
+        }
+        Err(_) => {
+            status = Failure;
+            msg = Some(String::from("No account with that handle."));
+        }
+    }
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::RemoveFollower { status, msg })?),
+    })
+}
+
+pub fn try_respond_follow_request<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    handle: String,
+    accept: bool,
+) -> StdResult<HandleResponse> {
+    let mut status = Success;
+    let mut msg = None;
+
+    let message_sender = deps.api.canonical_address(&env.message.sender)?;
+    match get_account_for_handle(&deps.storage, &handle) {
+        Ok(requester) => {
+            if get_follow_request(&deps.storage, &message_sender, &requester).is_none() {
+                status = Failure;
+                msg = Some(String::from("No pending follow request from that handle."));
+            } else {
+                clear_follow_request(&mut deps.storage, &message_sender, &requester)?;
+                if accept {
+                    store_following_address(&mut deps.storage, &requester, &message_sender)?;
+                    notify(
+                        &mut deps.storage,
+                        &message_sender,
+                        NotificationType::Follow,
+                        &requester,
+                        None,
+                        env.block.time,
+                    )?;
+                    notify(
+                        &mut deps.storage,
+                        &requester,
+                        NotificationType::FollowAccepted,
+                        &message_sender,
+                        None,
+                        env.block.time,
+                    )?;
+                }
+            }
+        }
+        Err(_) => {
+            status = Failure;
+            msg = Some(String::from("No account with that handle."));
+        }
+    }
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::RespondFollowRequest {
+            status,
+            msg,
+        })?),
+    })
+}
+
+pub fn try_mark_notifications_read<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+) -> StdResult<HandleResponse> {
+    let message_sender = deps.api.canonical_address(&env.message.sender)?;
+    mark_notifications_read(&mut deps.storage, &message_sender)?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::MarkNotificationsRead {
+            status: Success,
+        })?),
+    })
+}
+
+pub fn try_send_message<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    to_handle: String,
+    text: String,
+) -> StdResult<HandleResponse> {
+    let mut status = Success;
+    let mut msg = None;
+    let mut messages: Vec<CosmosMsg> = vec![];
+
+    let message_sender = deps.api.canonical_address(&env.message.sender)?;
+    if get_account(&deps.storage, &message_sender).is_err() {
+        return Err(StdError::generic_err(
+            "Must register an account before sending messages.",
+        ));
+    } else if is_deactivated(&deps.storage, &message_sender) {
+        return Err(StdError::generic_err("Account has been deactivated."));
+    }
+
+    let recipient = get_account_for_handle(&deps.storage, &to_handle)?;
+    if is_banned_at(&deps.storage, &recipient, env.block.time) {
+        return Err(StdError::generic_err("Account has been banned."));
+    } else if is_deactivated(&deps.storage, &recipient) {
+        return Err(StdError::generic_err("Account has been deactivated."));
+    } else if is_blocked_by(&deps.storage, &recipient, &message_sender) {
+        return Err(StdError::unauthorized());
+    };
+
+    // the fee must be paid exactly, anything else is rejected so the funds are returned
+    let fee = get_message_fee(&deps.storage);
+    let sent_amount: u128 = env
+        .message
+        .sent_funds
+        .iter()
+        .map(|coin| {
+            if coin.denom == DENOM {
+                Ok(coin.amount.u128())
+            } else {
+                Err(StdError::generic_err("Wrong denomination."))
+            }
+        })
+        .sum::<StdResult<u128>>()?;
+    if sent_amount != fee {
+        return Err(StdError::generic_err(format!(
+            "Sending a message costs {} {}.",
+            fee, DENOM
+        )));
+    }
+
+    if text.as_bytes().len() > MAX_MESSAGE_LEN {
+        status = Failure;
+        msg = Some(String::from("Message is too long."));
+    } else if text.is_empty() {
+        status = Failure;
+        msg = Some(String::from("Message is empty."));
+    }
+
+    if status == Success {
+        send_message(
+            &mut deps.storage,
+            &message_sender,
+            &recipient,
+            text.into_bytes(),
+            env.block.time,
+        )?;
+        if fee > 0 {
+            let constants = ReadonlyConfig::from_storage(&deps.storage).constants()?;
+            messages.push(CosmosMsg::Bank(BankMsg::Send {
+                from_address: env.contract.address.clone(),
+                to_address: deps.api.human_address(&constants.admin)?,
+                amount: vec![Coin {
+                    denom: DENOM.to_string(),
+                    amount: Uint128(fee),
+                }],
+            }));
+        }
+    } else if fee > 0 {
+        // message was not sent, return the fee
+        messages.push(CosmosMsg::Bank(BankMsg::Send {
+            from_address: env.contract.address.clone(),
+            to_address: env.message.sender.clone(),
+            amount: env.message.sent_funds,
+        }));
+    }
+
+    Ok(HandleResponse {
+        messages,
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::SendMessage { status, msg })?),
+    })
+}
+
+pub fn try_delete_message<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    message_id: i32,
+    sent: bool,
+) -> StdResult<HandleResponse> {
+    let mut status = Success;
+    let mut msg = None;
+
+    let message_sender = deps.api.canonical_address(&env.message.sender)?;
+    if message_id < 0
+        || delete_message(&mut deps.storage, &message_sender, message_id as u32, sent).is_err()
+    {
+        status = Failure;
+        msg = Some(String::from("No message with that id."));
+    }
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::DeleteMessage { status, msg })?),
+    })
+}
+
 // carry a new fardel to the network
 pub fn try_carry_fardel<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
@@ -773,6 +2516,10 @@ pub fn try_carry_fardel<S: Storage, A: Api, Q: Querier>(
     approval_req: bool,
     img: Option<String>,
     seal_time: Option<i32>,
+    publish_time: Option<i32>,
+    on_behalf_of: Option<HumanAddr>,
+    parts: Option<Vec<FardelPart>>,
+    audience: Option<Audience>,
 ) -> StdResult<HandleResponse> {
     let mut status: ResponseStatus = Success;
     let mut msg: Option<String> = None;
@@ -781,6 +2528,12 @@ pub fn try_carry_fardel<S: Storage, A: Api, Q: Querier>(
     let config = ReadonlyConfig::from_storage(&deps.storage);
     let constants = config.constants()?;
 
+    let owner = get_acting_account(deps, &env, on_behalf_of, DelegatePermission::Carry)?;
+    let audience = match audience {
+        Some(audience) => get_stored_audience(&deps.storage, audience)?,
+        None => StoredAudience::Public,
+    };
+
     let mut tag_size_ok = true;
     for tag in tags.clone() {
         if tag.as_bytes().len() > constants.max_tag_len.into() {
@@ -801,6 +2554,13 @@ pub fn try_carry_fardel<S: Storage, A: Api, Q: Querier>(
     //let contents_data_size = contents_data.iter().fold(0_usize, |acc, x| acc + x.as_bytes().len());
     let contents_data_size = contents_data.as_bytes().len();
 
+    let parts = parts.unwrap_or_default();
+    let parts_ok = parts.len() <= MAX_FARDEL_PARTS
+        && parts.iter().all(|part| {
+            part.contents_data.as_bytes().len() <= constants.max_contents_data_len.into()
+                && part.cost.u128() <= constants.max_cost
+        });
+
     let countable_value: u16 = match countable {
         Some(value) => {
             u16::try_from(value).or_else(|_| Err(StdError::generic_err("invalid countable value")))
@@ -811,6 +2571,7 @@ pub fn try_carry_fardel<S: Storage, A: Api, Q: Querier>(
 
     if !tag_size_ok
         || !img_size_ok
+        || !parts_ok
         || (public_message.as_bytes().len() > constants.max_public_message_len.into())
         || (tags.len() > constants.max_number_of_tags.into())
         || (contents_data_size > constants.max_contents_data_len.into())
@@ -820,8 +2581,10 @@ pub fn try_carry_fardel<S: Storage, A: Api, Q: Querier>(
         msg = Some(String::from("Invalid fardel data"));
     } else {
         let stored_seal_time = valid_seal_time(seal_time)?;
-
-        let message_sender = deps.api.canonical_address(&env.message.sender)?;
+        let mut stored_publish_time = valid_publish_time(publish_time)?;
+        if stored_publish_time <= env.block.time {
+            stored_publish_time = 0;
+        }
 
         // generate fardel hash id using xx3h
 
@@ -859,7 +2622,7 @@ pub fn try_carry_fardel<S: Storage, A: Api, Q: Querier>(
         let global_id = store_fardel(
             &mut deps.storage,
             fardel.hash_id,
-            &message_sender,
+            &owner,
             fardel.public_message,
             fardel.tags,
             fardel.contents_data,
@@ -869,6 +2632,33 @@ pub fn try_carry_fardel<S: Storage, A: Api, Q: Querier>(
             fardel.seal_time,
             fardel.timestamp,
         )?;
+        get_contents_key(&mut deps.storage, &env, &constants.prng_seed, global_id)?;
+        set_publish_time(&mut deps.storage, global_id, stored_publish_time)?;
+        set_audience(&mut deps.storage, global_id, &audience)?;
+
+        // each extra part is stored as a fardel of its own, with no public data
+        for (idx, part) in parts.into_iter().enumerate() {
+            let mut part_hash_data = Vec::with_capacity(16 + 8);
+            part_hash_data.extend_from_slice(&fardel.hash_id.to_be_bytes());
+            part_hash_data.extend_from_slice(&(idx as u64 + 1).to_be_bytes());
+            let part_id = store_fardel(
+                &mut deps.storage,
+                hash128_with_seed(&part_hash_data, env.block.time),
+                &owner,
+                vec![],
+                vec![],
+                part.contents_data.as_bytes().to_vec(),
+                part.cost.u128(),
+                0,
+                part.approval_req,
+                fardel.seal_time,
+                fardel.timestamp,
+            )?;
+            add_fardel_part(&mut deps.storage, &owner, global_id, part_id)?;
+            get_contents_key(&mut deps.storage, &env, &constants.prng_seed, part_id)?;
+            set_publish_time(&mut deps.storage, part_id, stored_publish_time)?;
+            set_audience(&mut deps.storage, part_id, &audience)?;
+        }
         // if fardel img sent, then store it as well
         if img.is_some() {
             store_fardel_img(
@@ -895,19 +2685,22 @@ pub fn try_seal_fardel<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
     env: Env,
     fardel_id: Uint128,
+    on_behalf_of: Option<HumanAddr>,
 ) -> StdResult<HandleResponse> {
     let mut status: ResponseStatus = Success;
     let mut msg: Option<String> = None;
     let fardel_id = fardel_id.u128();
+    let actor = get_acting_account(deps, &env, on_behalf_of, DelegatePermission::Seal)?;
 
     match get_fardel_by_hash(&deps.storage, fardel_id) {
         Ok(_) => {
             let global_id = get_global_id_by_hash(&deps.storage, fardel_id)?;
-            let owner = deps
-                .api
-                .human_address(&get_fardel_owner(&deps.storage, global_id)?)?;
-            if owner.eq(&env.message.sender) {
+            let owner = get_fardel_owner(&deps.storage, global_id)?;
+            if owner == actor {
                 seal_fardel(&mut deps.storage, global_id)?;
+                for part_id in get_fardel_parts(&deps.storage, global_id) {
+                    seal_fardel(&mut deps.storage, part_id)?;
+                }
             } else {
                 status = Failure;
                 msg = Some(String::from("You are not the owner of that fardel."))
@@ -930,19 +2723,22 @@ pub fn try_hide_fardel<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
     env: Env,
     fardel_id: Uint128,
+    on_behalf_of: Option<HumanAddr>,
 ) -> StdResult<HandleResponse> {
     let mut status: ResponseStatus = Success;
     let mut msg: Option<String> = None;
     let fardel_id = fardel_id.u128();
+    let actor = get_acting_account(deps, &env, on_behalf_of, DelegatePermission::Hide)?;
 
     match get_fardel_by_hash(&deps.storage, fardel_id) {
         Ok(_) => {
             let global_id = get_global_id_by_hash(&deps.storage, fardel_id)?;
-            let owner = deps
-                .api
-                .human_address(&get_fardel_owner(&deps.storage, global_id)?)?;
-            if owner.eq(&env.message.sender) {
+            let owner = get_fardel_owner(&deps.storage, global_id)?;
+            if owner == actor {
                 hide_fardel(&mut deps.storage, global_id)?;
+                for part_id in get_fardel_parts(&deps.storage, global_id) {
+                    hide_fardel(&mut deps.storage, part_id)?;
+                }
             } else {
                 status = Failure;
                 msg = Some(String::from("You are not the owner of that fardel."))
@@ -965,19 +2761,22 @@ pub fn try_unhide_fardel<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
     env: Env,
     fardel_id: Uint128,
+    on_behalf_of: Option<HumanAddr>,
 ) -> StdResult<HandleResponse> {
     let mut status: ResponseStatus = Success;
     let mut msg: Option<String> = None;
     let fardel_id = fardel_id.u128();
+    let actor = get_acting_account(deps, &env, on_behalf_of, DelegatePermission::Hide)?;
 
     match get_fardel_by_hash(&deps.storage, fardel_id) {
         Ok(_) => {
             let global_id = get_global_id_by_hash(&deps.storage, fardel_id)?;
-            let owner = deps
-                .api
-                .human_address(&get_fardel_owner(&deps.storage, global_id)?)?;
-            if owner.eq(&env.message.sender) {
+            let owner = get_fardel_owner(&deps.storage, global_id)?;
+            if owner == actor {
                 unhide_fardel(&mut deps.storage, global_id)?;
+                for part_id in get_fardel_parts(&deps.storage, global_id) {
+                    unhide_fardel(&mut deps.storage, part_id)?;
+                }
             } else {
                 status = Failure;
                 msg = Some(String::from("You are not the owner of that fardel."))
@@ -1000,12 +2799,15 @@ pub fn try_approve_pending_unpacks<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
     env: Env,
     number: Option<i32>,
+    on_behalf_of: Option<HumanAddr>,
 ) -> StdResult<HandleResponse> {
     let mut status: ResponseStatus = Success;
     let mut msg: Option<String> = None;
     let number = number.unwrap_or_else(|| 10_i32);
 
-    let owner = deps.api.canonical_address(&env.message.sender.clone())?;
+    let owner = get_acting_account(deps, &env, on_behalf_of, DelegatePermission::ApproveUnpacks)?;
+    // payments go to the owner even when a delegate approves
+    let owner_address = deps.api.human_address(&owner)?;
     let mut messages: Vec<CosmosMsg> = vec![];
 
     if number < 1 {
@@ -1032,6 +2834,43 @@ pub fn try_approve_pending_unpacks<S: Storage, A: Api, Q: Querier>(
                 pending_approval.fardel_id,
             )?;
             // no need to increment # of unpacks for fardel because we already did that
+            if let Some(pubkey) = get_pending_pubkey(
+                &deps.storage,
+                &pending_approval.unpacker,
+                pending_approval.fardel_id,
+            ) {
+                let contents = get_fardel_by_global_id(&deps.storage, pending_approval.fardel_id)?
+                    .map(|fardel| fardel.contents_data)
+                    .unwrap_or_default();
+                let encrypted_contents = encrypt_contents(
+                    &mut deps.storage,
+                    &env,
+                    pending_approval.fardel_id,
+                    &pending_approval.unpacker,
+                    &pubkey,
+                    contents.as_bytes(),
+                )?;
+                store_encrypted_unpack(
+                    &mut deps.storage,
+                    &pending_approval.unpacker,
+                    pending_approval.fardel_id,
+                    &encrypted_contents,
+                )?;
+                set_pending_pubkey(
+                    &mut deps.storage,
+                    &pending_approval.unpacker,
+                    pending_approval.fardel_id,
+                    None,
+                )?;
+            }
+            notify(
+                &mut deps.storage,
+                &pending_approval.unpacker,
+                NotificationType::UnpackApproved,
+                &owner,
+                Some(pending_approval.fardel_id),
+                env.block.time,
+            )?;
 
             // handle the transaction
             let cost = pending_approval.coin.amount.u128();
@@ -1061,14 +2900,16 @@ pub fn try_approve_pending_unpacks<S: Storage, A: Api, Q: Querier>(
             })?;
 
             // push payment
-            messages.push(CosmosMsg::Bank(BankMsg::Send {
-                from_address: env.contract.address.clone(),
-                to_address: env.message.sender.clone(),
-                amount: vec![Coin {
-                    denom: DENOM.to_string(),
-                    amount: Uint128(payment_amount.low_u128()),
-                }],
-            }));
+            if payment_amount.low_u128() > 0 {
+                messages.push(CosmosMsg::Bank(BankMsg::Send {
+                    from_address: env.contract.address.clone(),
+                    to_address: owner_address.clone(),
+                    amount: vec![Coin {
+                        denom: DENOM.to_string(),
+                        amount: Uint128(payment_amount.low_u128()),
+                    }],
+                }));
+            }
 
             // sum commission
             total_commission += commission_amount.low_u128();
@@ -1118,23 +2959,376 @@ pub fn try_approve_pending_unpacks<S: Storage, A: Api, Q: Querier>(
     })
 }
 
+pub fn try_revise_fardel<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    fardel_id: Uint128,
+    contents_data: String,
+    public_message: Option<String>,
+    tags: Option<Vec<String>>,
+    part: Option<i32>,
+) -> StdResult<HandleResponse> {
+    let mut status: ResponseStatus = Success;
+    let mut msg: Option<String> = None;
+    let fardel_id = get_part_hash_id(&deps.storage, fardel_id.u128(), part)?;
+    let message_sender = deps.api.canonical_address(&env.message.sender)?;
+
+    let constants = ReadonlyConfig::from_storage(&deps.storage).constants()?;
+
+    let mut tags_ok = true;
+    if let Some(tags) = tags.clone() {
+        tags_ok = tags.len() <= constants.max_number_of_tags.into()
+            && tags
+                .iter()
+                .all(|tag| tag.as_bytes().len() <= constants.max_tag_len.into());
+    }
+    let mut public_message_ok = true;
+    if let Some(public_message) = public_message.clone() {
+        public_message_ok =
+            public_message.as_bytes().len() <= constants.max_public_message_len.into();
+    }
+
+    match get_global_id_by_hash(&deps.storage, fardel_id) {
+        Ok(global_id) => {
+            let owner = get_fardel_owner(&deps.storage, global_id)?;
+            if owner != message_sender {
+                status = Failure;
+                msg = Some(String::from("You are not the owner of that fardel."));
+            } else if is_fardel_removed(&deps.storage, global_id) {
+                status = Failure;
+                msg = Some(String::from("No Fardel with given id."));
+            } else if !tags_ok
+                || !public_message_ok
+                || contents_data.as_bytes().len() > constants.max_contents_data_len.into()
+            {
+                status = Failure;
+                msg = Some(String::from("Invalid fardel data"));
+            } else {
+                revise_fardel(
+                    &mut deps.storage,
+                    global_id,
+                    contents_data.as_bytes().to_vec(),
+                    public_message.map(|public_message| public_message.as_bytes().to_vec()),
+                    tags.map(|tags| tags.iter().map(|tag| tag.as_bytes().to_vec()).collect()),
+                    env.block.time,
+                )?;
+            }
+        }
+        _ => {
+            status = Failure;
+            msg = Some(String::from("No Fardel with given id."));
+        }
+    }
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::ReviseFardel { status, msg })?),
+    })
+}
+
+pub fn try_schedule_publish<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    fardel_id: Uint128,
+    publish_time: Option<i32>,
+) -> StdResult<HandleResponse> {
+    let mut status: ResponseStatus = Success;
+    let mut msg: Option<String> = None;
+    let fardel_id = fardel_id.u128();
+    let message_sender = deps.api.canonical_address(&env.message.sender)?;
+
+    // no time or a time that has already passed publishes immediately
+    let mut publish_time = valid_publish_time(publish_time)?;
+    if publish_time <= env.block.time {
+        publish_time = 0;
+    }
+
+    match get_global_id_by_hash(&deps.storage, fardel_id) {
+        Ok(global_id) => {
+            let owner = get_fardel_owner(&deps.storage, global_id)?;
+            if owner != message_sender {
+                status = Failure;
+                msg = Some(String::from("You are not the owner of that fardel."));
+            } else if is_fardel_removed(&deps.storage, global_id) {
+                status = Failure;
+                msg = Some(String::from("No Fardel with given id."));
+            } else if is_published(&deps.storage, global_id, env.block.time) {
+                // a live fardel cannot be taken back down by scheduling it again
+                status = Failure;
+                msg = Some(String::from("Fardel has already been published."));
+            } else {
+                set_publish_time(&mut deps.storage, global_id, publish_time)?;
+                for part_id in get_fardel_parts(&deps.storage, global_id) {
+                    set_publish_time(&mut deps.storage, part_id, publish_time)?;
+                }
+            }
+        }
+        _ => {
+            status = Failure;
+            msg = Some(String::from("No Fardel with given id."));
+        }
+    }
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::SchedulePublish { status, msg })?),
+    })
+}
+
+pub fn try_set_audience<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    fardel_id: Uint128,
+    audience: Audience,
+) -> StdResult<HandleResponse> {
+    let mut status: ResponseStatus = Success;
+    let mut msg: Option<String> = None;
+    let fardel_id = fardel_id.u128();
+    let message_sender = deps.api.canonical_address(&env.message.sender)?;
+
+    let audience = get_stored_audience(&deps.storage, audience)?;
+
+    match get_global_id_by_hash(&deps.storage, fardel_id) {
+        Ok(global_id) => {
+            let owner = get_fardel_owner(&deps.storage, global_id)?;
+            if owner != message_sender {
+                status = Failure;
+                msg = Some(String::from("You are not the owner of that fardel."));
+            } else {
+                set_audience(&mut deps.storage, global_id, &audience)?;
+                for part_id in get_fardel_parts(&deps.storage, global_id) {
+                    set_audience(&mut deps.storage, part_id, &audience)?;
+                }
+            }
+        }
+        _ => {
+            status = Failure;
+            msg = Some(String::from("No Fardel with given id."));
+        }
+    }
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::SetAudience { status, msg })?),
+    })
+}
+
+pub fn try_extend_seal_time<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    fardel_id: Uint128,
+    seal_time: i32,
+) -> StdResult<HandleResponse> {
+    let mut status: ResponseStatus = Success;
+    let mut msg: Option<String> = None;
+    let message_sender = deps.api.canonical_address(&env.message.sender)?;
+    let seal_time = valid_seal_time(Some(seal_time))?;
+
+    match get_fardel_by_hash(&deps.storage, fardel_id.u128()) {
+        Ok(Some(fardel)) => {
+            let global_id = fardel.global_id.u128();
+            let owner = get_fardel_owner(&deps.storage, global_id)?;
+            if owner != message_sender {
+                status = Failure;
+                msg = Some(String::from("You are not the owner of that fardel."));
+            } else if is_sealed(&deps.storage, global_id, fardel.seal_time, env.block.time) {
+                status = Failure;
+                msg = Some(String::from("Fardel has been sealed."));
+            } else if fardel.seal_time == 0 {
+                status = Failure;
+                msg = Some(String::from("Fardel does not have a seal time."));
+            } else if seal_time <= fardel.seal_time {
+                status = Failure;
+                msg = Some(String::from(
+                    "New seal time must be later than the current seal time.",
+                ));
+            } else {
+                set_seal_time(&mut deps.storage, global_id, seal_time)?;
+                for part_id in get_fardel_parts(&deps.storage, global_id) {
+                    set_seal_time(&mut deps.storage, part_id, seal_time)?;
+                }
+            }
+        }
+        _ => {
+            status = Failure;
+            msg = Some(String::from("No Fardel with given id."));
+        }
+    }
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::ExtendSealTime { status, msg })?),
+    })
+}
+
+pub fn try_restock<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    fardel_id: Uint128,
+    additional_count: Option<i32>,
+    new_seal_time: Option<i32>,
+) -> StdResult<HandleResponse> {
+    let mut status: ResponseStatus = Success;
+    let mut msg: Option<String> = None;
+    let message_sender = deps.api.canonical_address(&env.message.sender)?;
+
+    let added = match additional_count {
+        Some(value) => u16::try_from(value)
+            .or_else(|_| Err(StdError::generic_err("invalid additional_count value")))?,
+        None => 0_u16,
+    };
+
+    match get_fardel_by_hash(&deps.storage, fardel_id.u128()) {
+        Ok(Some(fardel)) => {
+            let global_id = fardel.global_id.u128();
+            let owner = get_fardel_owner(&deps.storage, global_id)?;
+            let seal_time = match new_seal_time {
+                Some(_) => valid_seal_time(new_seal_time)?,
+                None => fardel.seal_time,
+            };
+            let countable = fardel.countable.checked_add(added);
+            if owner != message_sender {
+                status = Failure;
+                msg = Some(String::from("You are not the owner of that fardel."));
+            } else if is_fardel_removed(&deps.storage, global_id)
+                // extra parts are restocked with the fardel they belong to
+                || get_part_of(&deps.storage, global_id).is_some()
+            {
+                status = Failure;
+                msg = Some(String::from("No Fardel with given id."));
+            } else if additional_count.is_none() && new_seal_time.is_none() {
+                status = Failure;
+                msg = Some(String::from("Nothing to restock."));
+            } else if added > 0 && fardel.countable == 0 {
+                status = Failure;
+                msg = Some(String::from("Fardel does not have a countable cap."));
+            } else if countable.is_none() {
+                status = Failure;
+                msg = Some(String::from("invalid additional_count value"));
+            } else if seal_time > 0 && seal_time <= env.block.time {
+                status = Failure;
+                msg = Some(String::from("New seal time must be in the future."));
+            } else {
+                let countable = countable.unwrap();
+                set_countable(&mut deps.storage, global_id, countable)?;
+                set_seal_time(&mut deps.storage, global_id, seal_time)?;
+                let parts = get_fardel_parts(&deps.storage, global_id);
+                for part_id in parts.clone() {
+                    set_seal_time(&mut deps.storage, part_id, seal_time)?;
+                }
+                // existing unpacks are untouched, the fardel just opens back up
+                //   unless it is still sold out after the restock
+                let restocked = get_fardel_by_global_id(&deps.storage, global_id)?;
+                if restocked.map_or(false, |f| !f.sold_out(&deps.storage)) {
+                    unseal_fardel(&mut deps.storage, global_id)?;
+                    for part_id in parts {
+                        unseal_fardel(&mut deps.storage, part_id)?;
+                    }
+                }
+                append_restock(
+                    &mut deps.storage,
+                    global_id,
+                    &StoredRestock {
+                        added,
+                        countable,
+                        seal_time,
+                        timestamp: env.block.time,
+                    },
+                )?;
+            }
+        }
+        _ => {
+            status = Failure;
+            msg = Some(String::from("No Fardel with given id."));
+        }
+    }
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::Restock { status, msg })?),
+    })
+}
+
+pub fn try_sweep_expired<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    limit: Option<i32>,
+) -> StdResult<HandleResponse> {
+    let mut status: ResponseStatus = Success;
+    let mut msg: Option<String> = None;
+    let mut sealed: i32 = 0;
+    let limit = limit.unwrap_or_else(|| 50_i32);
+
+    let total_fardels = get_total_fardel_count(&deps.storage);
+    if limit < 1 {
+        status = Failure;
+        msg = Some(String::from("invalid limit"));
+    } else if total_fardels > 0 {
+        // the cursor wraps around so repeated calls keep cycling through all fardels
+        let mut cursor = get_sweep_cursor(&deps.storage);
+        let mut checked: u128 = 0;
+        while checked < limit as u128 && checked < total_fardels {
+            if cursor >= total_fardels {
+                cursor = 0;
+            }
+            if let Some(fardel) = get_fardel_by_global_id(&deps.storage, cursor)? {
+                if fardel.seal_time > 0
+                    && fardel.seal_time < env.block.time
+                    && !get_sealed_status(&deps.storage, cursor)
+                {
+                    seal_fardel(&mut deps.storage, cursor)?;
+                    sealed += 1;
+                }
+            }
+            cursor += 1;
+            checked += 1;
+        }
+        set_sweep_cursor(&mut deps.storage, cursor)?;
+    }
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::SweepExpired {
+            status,
+            msg,
+            sealed,
+        })?),
+    })
+}
+
 pub fn try_unpack_fardel<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
     env: Env,
     fardel_id: Uint128,
+    pubkey: Option<Binary>,
+    part: Option<i32>,
 ) -> StdResult<HandleResponse> {
     let mut messages: Vec<CosmosMsg> = vec![];
     let mut status: ResponseStatus = Success;
     let mut pending: bool = false;
     let mut msg: Option<String> = None;
     let mut contents_data: Option<String> = None;
+    let mut encrypted_contents: Option<EncryptedContents> = None;
 
     // fardel id from hash
-    let fardel_id = fardel_id.u128();
+    let fardel_id = get_part_hash_id(&deps.storage, fardel_id.u128(), part)?;
     let message_sender = deps.api.canonical_address(&env.message.sender)?;
+    // checked up front, a pending unpack only uses the pubkey when it is approved
+    if let Some(pubkey) = &pubkey {
+        if !valid_pubkey(pubkey.as_slice()) {
+            return Err(StdError::generic_err("Invalid public key."));
+        }
+    }
 
+    // no funds sent counts as 0, which only unpacks a free fardel
     let sent_coins = env.message.sent_funds.clone();
-    if sent_coins[0].denom != DENOM {
+    if sent_coins.iter().any(|coin| coin.denom != DENOM) {
         status = Failure;
         msg = Some(String::from("Wrong denomination."))
     } else {
@@ -1146,16 +3340,28 @@ pub fn try_unpack_fardel<S: Storage, A: Api, Q: Querier>(
                         let global_id = f.global_id.u128();
 
                         // 1. Check if sender is blocked by fardel owner or the owner's account has been deactivated/banned
+                        //   or the sender is not in the fardel's audience, or does not follow a private owner
                         let owner = get_fardel_owner(&deps.storage, global_id)?;
-                        if is_banned(&deps.storage, &owner)
+                        let followers_only = is_private_account(&deps.storage, &owner)
+                            && owner != message_sender
+                            && !is_following(&deps.storage, &message_sender, &owner);
+                        if is_banned_at(&deps.storage, &owner, env.block.time)
                             || is_deactivated(&deps.storage, &owner)
+                            || followers_only
                             || is_blocked_by(&deps.storage, &owner, &message_sender)
+                            || !audience_allows(
+                                &deps.storage,
+                                global_id,
+                                &owner,
+                                Some(&message_sender),
+                            )
                         {
                             return Err(StdError::unauthorized());
                         }
 
                         let cost = f.cost.amount.u128();
-                        let sent_amount: u128 = sent_coins[0].amount.u128();
+                        let sent_amount: u128 =
+                            sent_coins.iter().map(|coin| coin.amount.u128()).sum();
 
                         // 2. check it has not already been unpacked by the user
                         if get_unpacked_status_by_fardel_id(
@@ -1179,17 +3385,21 @@ pub fn try_unpack_fardel<S: Storage, A: Api, Q: Querier>(
                             msg = Some(String::from(
                                 "You have a currently pending unpack for this fardel.",
                             ));
-                        // 4. check it is not sealed
+                        // 4. check it has been published
+                        } else if !is_published(&deps.storage, global_id, env.block.time) {
+                            status = Failure;
+                            msg = Some(String::from("Fardel has not been published yet."));
+                        // 5. check it is not sealed
                         } else if get_sealed_status(&deps.storage, global_id) {
                             status = Failure;
                             msg = Some(String::from("Fardel has been sealed."));
-                        // 5. check it has not expired, 0 seal_time means never expires
+                        // 6. check it has not expired, 0 seal_time means never expires
                         } else if f.seal_time > 0 && f.seal_time < env.block.time {
                             // it is past seal time, so seal it
                             seal_fardel(&mut deps.storage, global_id)?;
                             status = Failure;
                             msg = Some(String::from("Fardel has been sealed."));
-                        // 6. check that countable packages have not been all unpacked
+                        // 7. check that countable packages have not been all unpacked
                         } else if f.clone().sold_out(&deps.storage) {
                             // when approval required we don't seal here, just in case someone cancels 
                             //   a pending unpack and it re-opens
@@ -1198,7 +3408,7 @@ pub fn try_unpack_fardel<S: Storage, A: Api, Q: Querier>(
                             }
                             status = Failure;
                             msg = Some(String::from("Fardel is sold out."));
-                        // 7. check cost is correct
+                        // 8. check cost is correct
                         } else if sent_amount != cost {
                             status = Failure;
                             msg = Some(String::from(
@@ -1212,10 +3422,27 @@ pub fn try_unpack_fardel<S: Storage, A: Api, Q: Querier>(
                                     &owner,
                                     &message_sender,
                                     global_id,
-                                    env.message.sent_funds[0].clone(),
+                                    Coin {
+                                        denom: DENOM.to_string(),
+                                        amount: Uint128(sent_amount),
+                                    },
                                     env.block.time,
                                 )?;
+                                set_pending_pubkey(
+                                    &mut deps.storage,
+                                    &message_sender,
+                                    global_id,
+                                    pubkey.as_ref(),
+                                )?;
                                 increment_fardel_unpack_count(&mut deps.storage, global_id);
+                                notify(
+                                    &mut deps.storage,
+                                    &owner,
+                                    NotificationType::PendingUnpack,
+                                    &message_sender,
+                                    Some(global_id),
+                                    env.block.time,
+                                )?;
                                 pending = true;
                                 msg = Some(String::from(
                                     "Fardel unpack is pending approval by owner.",
@@ -1224,7 +3451,21 @@ pub fn try_unpack_fardel<S: Storage, A: Api, Q: Querier>(
                                 // do a full unpack
                                 store_unpack(&mut deps.storage, &message_sender, global_id)?;
                                 increment_fardel_unpack_count(&mut deps.storage, global_id);
-                                contents_data = Some(f.contents_data);
+                                match pubkey {
+                                    Some(pubkey) => {
+                                        encrypted_contents = Some(encrypt_contents(
+                                            &mut deps.storage,
+                                            &env,
+                                            global_id,
+                                            &message_sender,
+                                            &pubkey,
+                                            f.contents_data.as_bytes(),
+                                        )?);
+                                    }
+                                    None => {
+                                        contents_data = Some(f.contents_data);
+                                    }
+                                }
                             }
 
                             //let canonical_owner = Some(owner);
@@ -1258,15 +3499,18 @@ pub fn try_unpack_fardel<S: Storage, A: Api, Q: Querier>(
                                 })?;
                         
                                 // push payment
-                                let fardel_owner = deps.api.human_address(&owner.clone())?;
-                                messages.push(CosmosMsg::Bank(BankMsg::Send {
-                                    from_address: env.contract.address.clone(),
-                                    to_address: fardel_owner,
-                                    amount: vec![Coin {
-                                        denom: DENOM.to_string(),
-                                        amount: Uint128(payment_amount.low_u128()),
-                                    }],
-                                }));
+                                let payment_amount = payment_amount.low_u128();
+                                if payment_amount > 0 {
+                                    let fardel_owner = deps.api.human_address(&owner.clone())?;
+                                    messages.push(CosmosMsg::Bank(BankMsg::Send {
+                                        from_address: env.contract.address.clone(),
+                                        to_address: fardel_owner,
+                                        amount: vec![Coin {
+                                            denom: DENOM.to_string(),
+                                            amount: Uint128(payment_amount),
+                                        }],
+                                    }));
+                                }
                         
                                 // push commission
                                 let commission_amount = commission_amount.low_u128();
@@ -1299,7 +3543,7 @@ pub fn try_unpack_fardel<S: Storage, A: Api, Q: Querier>(
                                     commission_amount,
                                     env.block.time,
                                 )?;
-                            } else {
+                            } else if !env.message.sent_funds.is_empty() {
                                 // return coins to sender if there was a Failure
                                 messages.push(CosmosMsg::Bank(BankMsg::Send {
                                     from_address: env.contract.address.clone(),
@@ -1329,21 +3573,116 @@ pub fn try_unpack_fardel<S: Storage, A: Api, Q: Querier>(
             status,
             msg,
             contents_data,
+            encrypted_contents,
         })?),
     })
 }
 
+// resolves the handles in an allow-list audience to addresses
+fn get_stored_audience<S: ReadonlyStorage>(
+    storage: &S,
+    audience: Audience,
+) -> StdResult<StoredAudience> {
+    match audience {
+        Audience::Public => Ok(StoredAudience::Public),
+        Audience::Followers => Ok(StoredAudience::Followers),
+        Audience::AllowList { handles } => {
+            if handles.len() > MAX_ALLOW_LIST_SIZE {
+                return Err(StdError::generic_err("Too many handles in allow list."));
+            }
+            let mut allowed: Vec<CanonicalAddr> = vec![];
+            for handle in handles {
+                let address = get_account_for_handle(storage, &handle).map_err(|_| {
+                    StdError::generic_err(format!("No account with handle {}.", handle))
+                })?;
+                if !allowed.contains(&address) {
+                    allowed.push(address);
+                }
+            }
+            Ok(StoredAudience::AllowList(allowed))
+        }
+    }
+}
+
+// resolves a part of a multi-part fardel to the hash id of the fardel it is stored as
+fn get_part_hash_id<S: ReadonlyStorage>(
+    storage: &S,
+    fardel_id: u128,
+    part: Option<i32>,
+) -> StdResult<u128> {
+    match part {
+        None | Some(0) => Ok(fardel_id),
+        Some(part) => {
+            let global_id = get_global_id_by_hash(storage, fardel_id)?;
+            let parts = get_fardel_parts(storage, global_id);
+            if part < 0 || part as usize > parts.len() {
+                return Err(StdError::generic_err("Fardel does not have that part."));
+            }
+            match get_fardel_by_global_id(storage, parts[part as usize - 1])? {
+                Some(fardel) => Ok(fardel.hash_id.u128()),
+                None => Err(StdError::generic_err("Fardel does not have that part.")),
+            }
+        }
+    }
+}
+
+// gets the symmetric key for a fardel's contents, generating it for fardels carried before
+//   contents keys were added
+fn get_contents_key<S: Storage>(
+    storage: &mut S,
+    env: &Env,
+    prng_seed: &[u8],
+    fardel_id: u128,
+) -> StdResult<Vec<u8>> {
+    if let Some(key) = get_fardel_key(storage, fardel_id) {
+        return Ok(key);
+    }
+    let mut rng_entropy: Vec<u8> = vec![];
+    rng_entropy.extend_from_slice(&env.block.height.to_be_bytes());
+    rng_entropy.extend_from_slice(&env.block.time.to_be_bytes());
+    rng_entropy.extend_from_slice(&fardel_id.to_be_bytes());
+    let mut rng = Prng::new(prng_seed, &rng_entropy);
+    let key = sha_256(&rng.rand_bytes());
+    store_fardel_key(storage, fardel_id, &key)?;
+    Ok(key.to_vec())
+}
+
+// encrypts contents to the pubkey a buyer sent with their unpack
+fn encrypt_contents<S: Storage>(
+    storage: &mut S,
+    env: &Env,
+    fardel_id: u128,
+    unpacker: &CanonicalAddr,
+    pubkey: &Binary,
+    contents: &[u8],
+) -> StdResult<EncryptedContents> {
+    let constants = ReadonlyConfig::from_storage(storage).constants()?;
+    let contents_key = get_contents_key(storage, env, &constants.prng_seed, fardel_id)?;
+
+    let mut rng_entropy: Vec<u8> = vec![];
+    rng_entropy.extend_from_slice(&env.block.height.to_be_bytes());
+    rng_entropy.extend_from_slice(&env.block.time.to_be_bytes());
+    rng_entropy.extend_from_slice(unpacker.as_slice());
+    rng_entropy.extend_from_slice(&fardel_id.to_be_bytes());
+    let mut rng = Prng::new(&constants.prng_seed, &rng_entropy);
+    let ephemeral_seed = sha_256(&rng.rand_bytes());
+
+    encrypt_contents_for_pubkey(pubkey.as_slice(), &ephemeral_seed, &contents_key, contents)
+}
+
 pub fn try_cancel_pending<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
     env: Env,
     fardel_id: Uint128,
+    part: Option<i32>,
 ) -> StdResult<HandleResponse> {
     let mut status = Success;
     let mut msg: Option<String> = None;
     let mut refund = Uint128(0);
     let mut messages: Vec<CosmosMsg> = vec![];
 
-    let fardel_id = get_global_id_by_hash(&deps.storage, fardel_id.u128())?;
+    let fardel_id = get_part_hash_id(&deps.storage, fardel_id.u128(), part)?;
+    let fardel_id = get_global_id_by_hash(&deps.storage, fardel_id)?;
     let owner = get_fardel_owner(&deps.storage, fardel_id)?;
     match get_fardel_by_global_id(&deps.storage, fardel_id)? {
         Some(fardel) => {
@@ -1360,6 +3699,16 @@ pub fn try_cancel_pending<S: Storage, A: Api, Q: Querier>(
     decrement_fardel_unpack_count(&mut deps.storage, fardel_id);
 
     if status == Success {
+        notify(
+            &mut deps.storage,
+            &owner,
+            NotificationType::UnpackCanceled,
+            &unpacker,
+            Some(fardel_id),
+            env.block.time,
+        )?;
+    }
+    if status == Success && refund.u128() > 0 {
         // return coins to sender
         messages.push(CosmosMsg::Bank(BankMsg::Send {
             from_address: env.contract.address.clone(),
@@ -1397,12 +3746,22 @@ pub fn try_rate_fardel<S: Storage, A: Api, Q: Querier>(
         } else if has_rated(&deps.storage, &message_sender, fardel_id) {
             status = Failure;
             msg = Some(String::from("Cannot rate a fardel more than once."));
-        } else if rating {
-            set_rated(&mut deps.storage, &message_sender, fardel_id, true)?;
-            add_upvote_fardel(&mut deps.storage, fardel_id)?;
         } else {
-            set_rated(&mut deps.storage, &message_sender, fardel_id, false)?;
-            add_downvote_fardel(&mut deps.storage, fardel_id)?;
+            if rating {
+                set_rated(&mut deps.storage, &message_sender, fardel_id, true)?;
+                add_upvote_fardel(&mut deps.storage, fardel_id)?;
+            } else {
+                set_rated(&mut deps.storage, &message_sender, fardel_id, false)?;
+                add_downvote_fardel(&mut deps.storage, fardel_id)?;
+            }
+            notify(
+                &mut deps.storage,
+                &owner,
+                NotificationType::Rating,
+                &message_sender,
+                Some(fardel_id),
+                env.block.time,
+            )?;
         }
     } else {
         // fardel has not been unpacked by the user
@@ -1473,6 +3832,14 @@ pub fn try_comment_on_fardel<S: Storage, A: Api, Q: Querier>(
         } else {
             // add comment
             comment_on_fardel(&mut deps.storage, &message_sender, fardel_id, comment)?;
+            notify(
+                &mut deps.storage,
+                &owner,
+                NotificationType::Comment,
+                &message_sender,
+                Some(fardel_id),
+                env.block.time,
+            )?;
 
             // handle rating if it is here
             match rating {
@@ -1482,12 +3849,22 @@ pub fn try_comment_on_fardel<S: Storage, A: Api, Q: Querier>(
                         msg = Some(String::from(
                             "Comment left but cannot rate a fardel more than once.",
                         ));
-                    } else if r {
-                        set_rated(&mut deps.storage, &message_sender, fardel_id, true)?;
-                        add_upvote_fardel(&mut deps.storage, fardel_id)?;
                     } else {
-                        set_rated(&mut deps.storage, &message_sender, fardel_id, false)?;
-                        add_downvote_fardel(&mut deps.storage, fardel_id)?;
+                        if r {
+                            set_rated(&mut deps.storage, &message_sender, fardel_id, true)?;
+                            add_upvote_fardel(&mut deps.storage, fardel_id)?;
+                        } else {
+                            set_rated(&mut deps.storage, &message_sender, fardel_id, false)?;
+                            add_downvote_fardel(&mut deps.storage, fardel_id)?;
+                        }
+                        notify(
+                            &mut deps.storage,
+                            &owner,
+                            NotificationType::Rating,
+                            &message_sender,
+                            Some(fardel_id),
+                            env.block.time,
+                        )?;
                     }
                 }
                 _ => {}
@@ -1513,10 +3890,16 @@ pub fn try_delete_comment<S: Storage, A: Api, Q: Querier>(
     env: Env,
     fardel_id: Uint128,
     comment_id: i32,
+    on_behalf_of: Option<HumanAddr>,
 ) -> StdResult<HandleResponse> {
     let mut status: ResponseStatus = Success;
     let mut msg: Option<String> = None;
-    let message_sender = deps.api.canonical_address(&env.message.sender)?;
+    let actor = get_acting_account(
+        deps,
+        &env,
+        on_behalf_of,
+        DelegatePermission::ModerateComments,
+    )?;
 
     let fardel_id = get_global_id_by_hash(&deps.storage, fardel_id.u128())?;
 
@@ -1525,7 +3908,8 @@ pub fn try_delete_comment<S: Storage, A: Api, Q: Querier>(
         msg = Some(String::from("invalid comment_id"));
     } else {
         let comment = get_comment_by_id(&deps.storage, fardel_id, comment_id as u32)?;
-        if comment.commenter == message_sender {
+        // commenters can delete their own comments, carriers can moderate comments on their fardels
+        if comment.commenter == actor || get_fardel_owner(&deps.storage, fardel_id)? == actor {
             delete_comment(&mut deps.storage, fardel_id, comment_id as u32)?;
         } else {
             status = Failure;
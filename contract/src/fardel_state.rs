@@ -1,8 +1,11 @@
 use crate::contract::DENOM;
+use crate::social_state::is_following;
 use crate::state::{
-    get_bin_data, set_bin_data, KEY_FARDEL_COUNT, PREFIX_FARDELS, PREFIX_FARDEL_NUM_UNPACKS,
-    PREFIX_FARDEL_THUMBNAIL_IMGS, PREFIX_HASH_ID_MAPPINGS, PREFIX_HIDDEN, PREFIX_REMOVED,
-    PREFIX_ID_FARDEL_MAPPINGS, PREFIX_SEALED,
+    get_bin_data, get_schema_version, set_bin_data, KEY_FARDEL_COUNT, PREFIX_AUDIENCE,
+    PREFIX_FARDELS, PREFIX_FARDEL_KEYS, PREFIX_FARDEL_NUM_UNPACKS, PREFIX_FARDEL_PARTS,
+    PREFIX_FARDEL_REVISIONS, PREFIX_FARDEL_THUMBNAIL_IMGS, PREFIX_HASH_ID_MAPPINGS, PREFIX_HIDDEN,
+    PREFIX_ID_FARDEL_MAPPINGS, PREFIX_PART_COUNT, PREFIX_PART_OF, PREFIX_PUBLISH_TIME,
+    PREFIX_REMOVED, PREFIX_RESTOCKS, PREFIX_SEALED, PREFIX_TAGGED_FARDELS,
 };
 use crate::unpack_state::store_unpack;
 use cosmwasm_std::{CanonicalAddr, Coin, ReadonlyStorage, StdError, StdResult, Storage, Uint128};
@@ -15,11 +18,18 @@ use serde::{Deserialize, Serialize};
 // Fardels
 //
 // are stored using multilevel prefixed + appendstore keys:
-//    b"fardels" | {owner canonical addr} | {appendstore index} -> Fardel
+//    b"fardels" | {owner canonical addr} | {appendstore index} -> VersionedFardel
 //
 //  plus an additional mapping is stored to allow getting by global_id:
 //    b"id-to-fardel" | {global fardel id} -> GlobalIdToFardelMapping(owner, index)
 //
+//  VersionedFardel leads with the layout version, reads branch on it and upgrade older
+//    layouts on the fly. A new layout gets a new variant.
+//  v1 fardels were stored untagged, before the contract is at schema v2 only the first
+//    b"fardels-tagged" | {owner canonical addr} -> count
+//    of an owner's fardels are tagged. Migrate tags them in global id order, which is the
+//    order each owner's fardels were appended in.
+//
 #[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
 pub struct Fardel {
     pub global_id: Uint128,
@@ -68,6 +78,22 @@ impl Fardel {
     }
 }
 
+// a stored fardel, the variant is its layout version
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub enum VersionedFardel {
+    V1(StoredFardelV1),
+    V2(StoredFardel),
+}
+
+impl VersionedFardel {
+    pub fn upgrade(self) -> StoredFardel {
+        match self {
+            VersionedFardel::V1(fardel) => fardel.upgrade(),
+            VersionedFardel::V2(fardel) => fardel,
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct StoredFardel {
     pub global_id: u128,
@@ -82,6 +108,85 @@ pub struct StoredFardel {
     pub timestamp: u64,
 }
 
+// fardel layout before versioning was added
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct StoredFardelV1 {
+    pub global_id: u128,
+    pub hash_id: u128,
+    pub public_message: Vec<u8>,
+    pub tags: Vec<Vec<u8>>,
+    pub contents_data: Vec<u8>,
+    pub cost: u128,
+    pub countable: u16,
+    pub approval_req: bool,
+    pub seal_time: u64,
+    pub timestamp: u64,
+}
+
+impl StoredFardelV1 {
+    pub fn upgrade(self) -> StoredFardel {
+        StoredFardel {
+            global_id: self.global_id,
+            hash_id: self.hash_id,
+            public_message: self.public_message,
+            tags: self.tags,
+            contents_data: self.contents_data,
+            cost: self.cost,
+            countable: self.countable,
+            approval_req: self.approval_req,
+            seal_time: self.seal_time,
+            timestamp: self.timestamp,
+        }
+    }
+}
+
+// reads the owner's fardel at idx, upgrading it if stored in an older layout
+fn get_stored_fardel_at<S: ReadonlyStorage>(
+    storage: &S,
+    owner: &CanonicalAddr,
+    idx: u32,
+) -> StdResult<StoredFardel> {
+    let store = ReadonlyPrefixedStorage::multilevel(&[PREFIX_FARDELS, owner.as_slice()], storage);
+    if get_schema_version(storage) < 2 && idx >= get_tagged_fardel_count(storage, owner) {
+        let store = AppendStore::<StoredFardelV1, _>::attach(&store)
+            .ok_or_else(|| StdError::generic_err("No fardels stored for this account"))??;
+        return Ok(store.get_at(idx)?.upgrade());
+    }
+    let store = AppendStore::<VersionedFardel, _>::attach(&store)
+        .ok_or_else(|| StdError::generic_err("No fardels stored for this account"))??;
+    Ok(store.get_at(idx)?.upgrade())
+}
+
+// stores the owner's fardel at idx in the current layout
+fn set_stored_fardel_at<S: Storage>(
+    storage: &mut S,
+    owner: &CanonicalAddr,
+    idx: u32,
+    fardel: StoredFardel,
+) -> StdResult<()> {
+    let mut store = PrefixedStorage::multilevel(&[PREFIX_FARDELS, owner.as_slice()], storage);
+    let mut store = AppendStoreMut::<VersionedFardel, _>::attach_or_create(&mut store)?;
+    store.set_at(idx, &VersionedFardel::V2(fardel))
+}
+
+fn get_tagged_fardel_count<S: ReadonlyStorage>(storage: &S, owner: &CanonicalAddr) -> u32 {
+    let storage = ReadonlyPrefixedStorage::new(PREFIX_TAGGED_FARDELS, storage);
+    get_bin_data(&storage, owner.as_slice()).unwrap_or_else(|_| 0_u32)
+}
+
+// counts the owner's fardel at idx as tagged if all before it are, only needed before schema v2
+fn mark_fardel_tagged<S: Storage>(
+    storage: &mut S,
+    owner: &CanonicalAddr,
+    idx: u32,
+) -> StdResult<()> {
+    if get_schema_version(storage) < 2 && idx == get_tagged_fardel_count(storage, owner) {
+        let mut storage = PrefixedStorage::new(PREFIX_TAGGED_FARDELS, storage);
+        set_bin_data(&mut storage, owner.as_slice(), &(idx + 1))?;
+    }
+    Ok(())
+}
+
 impl StoredFardel {
     pub fn into_humanized(self) -> StdResult<Fardel> {
         let humanized_tags = self
@@ -172,10 +277,14 @@ fn append_fardel<S: Storage>(
     owner: &CanonicalAddr,
     fardel: StoredFardel,
 ) -> StdResult<u32> {
-    let mut storage = PrefixedStorage::multilevel(&[PREFIX_FARDELS, owner.as_slice()], store);
-    let mut storage = AppendStoreMut::<StoredFardel, _>::attach_or_create(&mut storage)?;
-    let idx = storage.len();
-    storage.push(&fardel)?;
+    let idx = {
+        let mut storage = PrefixedStorage::multilevel(&[PREFIX_FARDELS, owner.as_slice()], store);
+        let mut storage = AppendStoreMut::<VersionedFardel, _>::attach_or_create(&mut storage)?;
+        let idx = storage.len();
+        storage.push(&VersionedFardel::V2(fardel))?;
+        idx
+    };
+    mark_fardel_tagged(store, owner, idx)?;
     Ok(idx)
 }
 
@@ -272,13 +381,12 @@ pub fn get_fardel_by_global_id<S: ReadonlyStorage>(
         ReadonlyPrefixedStorage::multilevel(&[PREFIX_FARDELS, mapping.owner.as_slice()], storage);
     // Try to access the storage of fardels for the account.
     // If it doesn't exist yet, return None.
-    let store = if let Some(result) = AppendStore::<StoredFardel, _>::attach(&store) {
-        result?
-    } else {
+    if AppendStore::<VersionedFardel, _>::attach(&store).is_none() {
         return Ok(None);
-    };
+    }
 
-    let stored_fardel: StoredFardel = match store.get_at(mapping.index) {
+    let stored_fardel = get_stored_fardel_at(storage, &mapping.owner, mapping.index);
+    let stored_fardel: StoredFardel = match stored_fardel {
         Ok(f) => f,
         _ => {
             return Err(StdError::generic_err(format!(
@@ -327,37 +435,62 @@ pub fn get_fardels<S: ReadonlyStorage>(
     page: u32,
     page_size: u32,
 ) -> StdResult<Vec<Fardel>> {
-    let store = ReadonlyPrefixedStorage::multilevel(&[PREFIX_FARDELS, owner.as_slice()], storage);
+    let prefixed_store =
+        ReadonlyPrefixedStorage::multilevel(&[PREFIX_FARDELS, owner.as_slice()], storage);
 
     // Try to access the storage of fardels for the account.
     // If it doesn't exist yet, return an empty list.
-    let store = if let Some(result) = AppendStore::<StoredFardel, _>::attach(&store) {
+    let store = if let Some(result) = AppendStore::<VersionedFardel, _>::attach(&prefixed_store) {
         result?
     } else {
         return Ok(vec![]);
     };
 
     // Take `page_size` fardels starting from the latest fardel, potentially skipping `page * page_size`
-    // fardels from the start.
-    let fardel_iter = store
-        .iter()
+    // fardels from the start. Extra parts are stored alongside the owner's fardels but are
+    //   listed with the fardel they belong to, so they are skipped before paging.
+    // Read each index through get_stored_fardel_at so older layouts are upgraded
+    let fardels: StdResult<Vec<Fardel>> = (0..store.len())
         .rev()
+        .map(|idx| get_stored_fardel_at(storage, owner, idx))
+        .filter(|f| match f {
+            Ok(f) => get_part_of(storage, f.global_id).is_none(),
+            Err(_) => true,
+        })
         .skip((page * page_size) as _)
-        .take(page_size as _);
-    // The `and_then` here flattens the `StdResult<StdResult<Fardel>>` to an `StdResult<Fardel>`
-    let fardels: StdResult<Vec<Fardel>> = fardel_iter
-        .map(|fardel| fardel.map(|fardel| fardel.into_humanized()).and_then(|x| x))
+        .take(page_size as _)
+        .map(|f| f.and_then(|f| f.into_humanized()))
         .collect();
     fardels
 }
 
+// returns the global id of the owner's fardel at idx, in the order they were carried and
+//   counting extra parts
+pub fn get_fardel_id_at<S: ReadonlyStorage>(
+    storage: &S,
+    owner: &CanonicalAddr,
+    idx: u32,
+) -> StdResult<u128> {
+    get_stored_fardel_at(storage, owner, idx).map(|fardel| fardel.global_id)
+}
+
+// rewrites a fardel in the current layout, used by Migrate in global id order
+pub fn upgrade_stored_fardel<S: Storage>(storage: &mut S, fardel_id: u128) -> StdResult<()> {
+    let mapping_store = ReadonlyPrefixedStorage::new(PREFIX_ID_FARDEL_MAPPINGS, storage);
+    let mapping: GlobalIdFardelMapping = get_bin_data(&mapping_store, &fardel_id.to_be_bytes())?;
+
+    let fardel = get_stored_fardel_at(storage, &mapping.owner, mapping.index)?;
+    set_stored_fardel_at(storage, &mapping.owner, mapping.index, fardel)?;
+    mark_fardel_tagged(storage, &mapping.owner, mapping.index)
+}
+
 // returns total number of fardels for user
 pub fn get_number_of_fardels<S: ReadonlyStorage>(storage: &S, owner: &CanonicalAddr) -> u32 {
     let store = ReadonlyPrefixedStorage::multilevel(&[PREFIX_FARDELS, owner.as_slice()], storage);
 
     // Try to access the storage of fardels for the account.
     // If it doesn't exist yet, return 0.
-    if let Some(result) = AppendStore::<StoredFardel, _>::attach(&store) {
+    if let Some(result) = AppendStore::<VersionedFardel, _>::attach(&store) {
         return result.unwrap().len();
     } else {
         return 0;
@@ -376,12 +509,11 @@ pub fn seal_fardel<S: Storage>(store: &mut S, fardel_id: u128) -> StdResult<()>
     set_bin_data(&mut store, &fardel_id.to_be_bytes(), &true)
 }
 
-/* not implemented
+// only used by Restock
 pub fn unseal_fardel<S: Storage>(store: &mut S, fardel_id: u128) -> StdResult<()> {
     let mut store = PrefixedStorage::new(PREFIX_SEALED, store);
     set_bin_data(&mut store, &fardel_id.to_be_bytes(), &false)
 }
-*/
 
 // get sealed status of a given fardel
 //  true means sealed, false means not sealed
@@ -390,6 +522,125 @@ pub fn get_sealed_status<S: ReadonlyStorage>(store: &S, fardel_id: u128) -> bool
     get_bin_data(&store, &fardel_id.to_be_bytes()).unwrap_or_else(|_| false)
 }
 
+// sealed status taking seal_time into account, fardels past their seal_time count as sealed
+//   even if nothing has sealed them in storage yet
+pub fn is_sealed<S: ReadonlyStorage>(
+    store: &S,
+    fardel_id: u128,
+    seal_time: u64,
+    time: u64,
+) -> bool {
+    (seal_time > 0 && seal_time < time) || get_sealed_status(store, fardel_id)
+}
+
+// rewrites the seal_time of a stored fardel
+pub fn set_seal_time<S: Storage>(
+    storage: &mut S,
+    fardel_id: u128,
+    seal_time: u64,
+) -> StdResult<()> {
+    update_stored_fardel(storage, fardel_id, |fardel| fardel.seal_time = seal_time)
+}
+
+// rewrites the countable cap of a stored fardel
+pub fn set_countable<S: Storage>(
+    storage: &mut S,
+    fardel_id: u128,
+    countable: u16,
+) -> StdResult<()> {
+    update_stored_fardel(storage, fardel_id, |fardel| fardel.countable = countable)
+}
+
+fn update_stored_fardel<S: Storage, F: FnOnce(&mut StoredFardel)>(
+    storage: &mut S,
+    fardel_id: u128,
+    update: F,
+) -> StdResult<()> {
+    let mapping_store = ReadonlyPrefixedStorage::new(PREFIX_ID_FARDEL_MAPPINGS, storage);
+    let mapping: GlobalIdFardelMapping = get_bin_data(&mapping_store, &fardel_id.to_be_bytes())?;
+
+    let mut fardel = get_stored_fardel_at(storage, &mapping.owner, mapping.index)?;
+    update(&mut fardel);
+    set_stored_fardel_at(storage, &mapping.owner, mapping.index, fardel)
+}
+
+//
+//  Scheduled fardels
+//
+//    b"publish-time" | {global fardel id} -> timestamp
+//       until the publish time a fardel is only visible to its carrier and cannot be unpacked,
+//       no record in storage means it was published when carried
+//
+
+pub fn set_publish_time<S: Storage>(store: &mut S, fardel_id: u128, time: u64) -> StdResult<()> {
+    let mut store = PrefixedStorage::new(PREFIX_PUBLISH_TIME, store);
+    if time == 0 {
+        store.remove(&fardel_id.to_be_bytes());
+        return Ok(());
+    }
+    set_bin_data(&mut store, &fardel_id.to_be_bytes(), &time)
+}
+
+// 0 means there is no scheduled publish time
+pub fn get_publish_time<S: ReadonlyStorage>(store: &S, fardel_id: u128) -> u64 {
+    let store = ReadonlyPrefixedStorage::new(PREFIX_PUBLISH_TIME, store);
+    get_bin_data(&store, &fardel_id.to_be_bytes()).unwrap_or_else(|_| 0_u64)
+}
+
+pub fn is_published<S: ReadonlyStorage>(store: &S, fardel_id: u128, time: u64) -> bool {
+    get_publish_time(store, fardel_id) <= time
+}
+
+//
+//  Fardel audience
+//
+//    b"audience" | {global fardel id} -> StoredAudience
+//       no record in storage means the fardel is public
+//    extra parts get the same audience as the fardel they belong to
+//
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum StoredAudience {
+    Public,
+    Followers,
+    AllowList(Vec<CanonicalAddr>),
+}
+
+pub fn set_audience<S: Storage>(
+    store: &mut S,
+    fardel_id: u128,
+    audience: &StoredAudience,
+) -> StdResult<()> {
+    let mut store = PrefixedStorage::new(PREFIX_AUDIENCE, store);
+    if *audience == StoredAudience::Public {
+        store.remove(&fardel_id.to_be_bytes());
+        return Ok(());
+    }
+    set_bin_data(&mut store, &fardel_id.to_be_bytes(), audience)
+}
+
+pub fn get_audience<S: ReadonlyStorage>(store: &S, fardel_id: u128) -> StoredAudience {
+    let store = ReadonlyPrefixedStorage::new(PREFIX_AUDIENCE, store);
+    get_bin_data(&store, &fardel_id.to_be_bytes()).unwrap_or(StoredAudience::Public)
+}
+
+// returns true if the viewer (None when not logged in) is in the fardel's audience,
+//   the owner is always in it
+pub fn audience_allows<S: ReadonlyStorage>(
+    store: &S,
+    fardel_id: u128,
+    owner: &CanonicalAddr,
+    viewer: Option<&CanonicalAddr>,
+) -> bool {
+    match (get_audience(store, fardel_id), viewer) {
+        (StoredAudience::Public, _) => true,
+        (_, None) => false,
+        (_, Some(viewer)) if viewer == owner => true,
+        (StoredAudience::Followers, Some(viewer)) => is_following(store, viewer, owner),
+        (StoredAudience::AllowList(allowed), Some(viewer)) => allowed.contains(viewer),
+    }
+}
+
 //
 //  Hidden fardels
 //
@@ -454,3 +705,460 @@ pub fn get_fardel_img<S: ReadonlyStorage>(store: &S, fardel_id: u128) -> String
     let img_vec = get_bin_data(&storage, &fardel_id.to_be_bytes()).unwrap_or_else(|_| vec![]);
     String::from_utf8(img_vec).unwrap()
 }
+
+//
+// Fardel Contents Keys
+//
+//   each fardel has a symmetric key held by the contract that contents are encrypted with
+//   when a buyer asks for them to be delivered to a public key
+//
+
+pub fn store_fardel_key<S: Storage>(store: &mut S, fardel_id: u128, key: &[u8]) -> StdResult<()> {
+    let mut storage = PrefixedStorage::new(PREFIX_FARDEL_KEYS, store);
+    set_bin_data(&mut storage, &fardel_id.to_be_bytes(), &key.to_vec())
+}
+
+// returns None for fardels carried before contents keys were added
+pub fn get_fardel_key<S: ReadonlyStorage>(store: &S, fardel_id: u128) -> Option<Vec<u8>> {
+    let storage = ReadonlyPrefixedStorage::new(PREFIX_FARDEL_KEYS, store);
+    get_bin_data(&storage, &fardel_id.to_be_bytes()).ok()
+}
+
+//
+// Fardel Revisions
+//
+//   a carrier can revise the contents (and optionally the public message and tags) of a fardel.
+//   The fardel itself is rewritten with the latest revision, and every revision is also kept in
+//   an append-only history:
+//     b"fardel-revisions" | {global fardel id} | {appendstore index} -> StoredRevision
+//   The first entry is the fardel as it was originally carried.
+//
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct StoredRevision {
+    pub public_message: Vec<u8>,
+    pub tags: Vec<Vec<u8>>,
+    pub contents_data: Vec<u8>,
+    pub timestamp: u64,
+}
+
+pub fn revise_fardel<S: Storage>(
+    storage: &mut S,
+    fardel_id: u128,
+    contents_data: Vec<u8>,
+    public_message: Option<Vec<u8>>,
+    tags: Option<Vec<Vec<u8>>>,
+    timestamp: u64,
+) -> StdResult<()> {
+    let mapping_store = ReadonlyPrefixedStorage::new(PREFIX_ID_FARDEL_MAPPINGS, storage);
+    let mapping: GlobalIdFardelMapping = get_bin_data(&mapping_store, &fardel_id.to_be_bytes())?;
+
+    let mut fardel = get_stored_fardel_at(storage, &mapping.owner, mapping.index)?;
+
+    let mut revision_store = PrefixedStorage::multilevel(
+        &[PREFIX_FARDEL_REVISIONS, &fardel_id.to_be_bytes()],
+        storage,
+    );
+    let mut revision_store =
+        AppendStoreMut::<StoredRevision, _>::attach_or_create(&mut revision_store)?;
+    if revision_store.len() == 0 {
+        revision_store.push(&StoredRevision {
+            public_message: fardel.public_message.clone(),
+            tags: fardel.tags.clone(),
+            contents_data: fardel.contents_data.clone(),
+            timestamp: fardel.timestamp,
+        })?;
+    }
+
+    fardel.contents_data = contents_data;
+    if let Some(public_message) = public_message {
+        fardel.public_message = public_message;
+    }
+    if let Some(tags) = tags {
+        fardel.tags = tags;
+    }
+    revision_store.push(&StoredRevision {
+        public_message: fardel.public_message.clone(),
+        tags: fardel.tags.clone(),
+        contents_data: fardel.contents_data.clone(),
+        timestamp,
+    })?;
+
+    set_stored_fardel_at(storage, &mapping.owner, mapping.index, fardel)
+}
+
+// returns the number of stored revisions, 0 if the fardel has never been revised
+pub fn get_number_of_revisions<S: ReadonlyStorage>(storage: &S, fardel_id: u128) -> u32 {
+    let store = ReadonlyPrefixedStorage::multilevel(
+        &[PREFIX_FARDEL_REVISIONS, &fardel_id.to_be_bytes()],
+        storage,
+    );
+    match AppendStore::<StoredRevision, _>::attach(&store) {
+        Some(Ok(store)) => store.len(),
+        _ => 0,
+    }
+}
+
+// returns the timestamp of the latest revision if the fardel has been revised
+pub fn get_last_revised<S: ReadonlyStorage>(storage: &S, fardel_id: u128) -> Option<u64> {
+    let store = ReadonlyPrefixedStorage::multilevel(
+        &[PREFIX_FARDEL_REVISIONS, &fardel_id.to_be_bytes()],
+        storage,
+    );
+    match AppendStore::<StoredRevision, _>::attach(&store) {
+        Some(Ok(store)) if store.len() > 1 => {
+            store.get_at(store.len() - 1).ok().map(|r| r.timestamp)
+        }
+        _ => None,
+    }
+}
+
+// returns a page of revisions, newest first
+pub fn get_revisions<S: ReadonlyStorage>(
+    storage: &S,
+    fardel_id: u128,
+    page: u32,
+    page_size: u32,
+) -> StdResult<Vec<StoredRevision>> {
+    let store = ReadonlyPrefixedStorage::multilevel(
+        &[PREFIX_FARDEL_REVISIONS, &fardel_id.to_be_bytes()],
+        storage,
+    );
+    let store = match AppendStore::<StoredRevision, _>::attach(&store) {
+        Some(store) => store?,
+        None => return Ok(vec![]),
+    };
+    store
+        .iter()
+        .rev()
+        .skip((page * page_size) as _)
+        .take(page_size as _)
+        .collect()
+}
+
+// appends a revision as it was stored, used for import
+pub fn append_revision<S: Storage>(
+    storage: &mut S,
+    fardel_id: u128,
+    revision: &StoredRevision,
+) -> StdResult<()> {
+    let mut store = PrefixedStorage::multilevel(
+        &[PREFIX_FARDEL_REVISIONS, &fardel_id.to_be_bytes()],
+        storage,
+    );
+    let mut store = AppendStoreMut::<StoredRevision, _>::attach_or_create(&mut store)?;
+    store.push(revision)
+}
+
+// gets revisions in appendstore order, used for export
+pub fn get_revisions_range<S: ReadonlyStorage>(
+    storage: &S,
+    fardel_id: u128,
+    start: u32,
+    count: u32,
+) -> StdResult<Vec<StoredRevision>> {
+    let store = ReadonlyPrefixedStorage::multilevel(
+        &[PREFIX_FARDEL_REVISIONS, &fardel_id.to_be_bytes()],
+        storage,
+    );
+    let store = match AppendStore::<StoredRevision, _>::attach(&store) {
+        Some(store) => store?,
+        None => return Ok(vec![]),
+    };
+    store.iter().skip(start as _).take(count as _).collect()
+}
+
+//
+// Fardel Parts
+//
+//   a fardel can have additional parts that are priced and unpacked separately. Each extra part
+//   is stored as a fardel of its own, so it gets its own unpack state, pending approvals and
+//   transactions, and it is mapped back to the fardel it belongs to:
+//     b"fardel-parts" | {global fardel id} -> Vec<global id of each extra part>
+//     b"part-of" | {global id of part} -> global fardel id
+//     b"part-count" | {owner canonical addr} -> number of extra parts the owner has carried
+//   The fardel itself is part 0, extra parts are numbered from 1 in the order they were carried.
+//
+
+pub fn add_fardel_part<S: Storage>(
+    store: &mut S,
+    owner: &CanonicalAddr,
+    fardel_id: u128,
+    part_id: u128,
+) -> StdResult<()> {
+    let mut parts = get_fardel_parts(store, fardel_id);
+    parts.push(part_id);
+    let mut storage = PrefixedStorage::new(PREFIX_FARDEL_PARTS, store);
+    set_bin_data(&mut storage, &fardel_id.to_be_bytes(), &parts)?;
+    let mut storage = PrefixedStorage::new(PREFIX_PART_OF, store);
+    set_bin_data(&mut storage, &part_id.to_be_bytes(), &fardel_id)?;
+    let part_count = get_number_of_parts(store, owner) + 1;
+    let mut storage = PrefixedStorage::new(PREFIX_PART_COUNT, store);
+    set_bin_data(&mut storage, owner.as_slice(), &part_count)
+}
+
+// returns the number of extra parts the owner has carried, they are stored with the owner's
+//   fardels so get_number_of_fardels includes them
+pub fn get_number_of_parts<S: ReadonlyStorage>(store: &S, owner: &CanonicalAddr) -> u32 {
+    let storage = ReadonlyPrefixedStorage::new(PREFIX_PART_COUNT, store);
+    get_bin_data(&storage, owner.as_slice()).unwrap_or_else(|_| 0_u32)
+}
+
+// returns the global ids of a fardel's extra parts, in order
+pub fn get_fardel_parts<S: ReadonlyStorage>(store: &S, fardel_id: u128) -> Vec<u128> {
+    let storage = ReadonlyPrefixedStorage::new(PREFIX_FARDEL_PARTS, store);
+    get_bin_data(&storage, &fardel_id.to_be_bytes()).unwrap_or_else(|_| vec![])
+}
+
+// returns the global id of the fardel a part belongs to, None if it is not an extra part
+pub fn get_part_of<S: ReadonlyStorage>(store: &S, part_id: u128) -> Option<u128> {
+    let storage = ReadonlyPrefixedStorage::new(PREFIX_PART_OF, store);
+    get_bin_data(&storage, &part_id.to_be_bytes()).ok()
+}
+
+//
+// Restocks
+//
+//   public history of a carrier raising a fardel's countable cap or reopening it:
+//     b"restocks" | {global fardel id} | {appendstore index} -> StoredRestock
+//
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct StoredRestock {
+    // number added to the countable cap
+    pub added: u16,
+    // countable cap and seal_time after the restock
+    pub countable: u16,
+    pub seal_time: u64,
+    pub timestamp: u64,
+}
+
+pub fn append_restock<S: Storage>(
+    storage: &mut S,
+    fardel_id: u128,
+    restock: &StoredRestock,
+) -> StdResult<()> {
+    let mut store =
+        PrefixedStorage::multilevel(&[PREFIX_RESTOCKS, &fardel_id.to_be_bytes()], storage);
+    let mut store = AppendStoreMut::<StoredRestock, _>::attach_or_create(&mut store)?;
+    store.push(restock)
+}
+
+pub fn get_number_of_restocks<S: ReadonlyStorage>(storage: &S, fardel_id: u128) -> u32 {
+    let store =
+        ReadonlyPrefixedStorage::multilevel(&[PREFIX_RESTOCKS, &fardel_id.to_be_bytes()], storage);
+    match AppendStore::<StoredRestock, _>::attach(&store) {
+        Some(Ok(store)) => store.len(),
+        _ => 0,
+    }
+}
+
+// returns a page of restocks, newest first
+pub fn get_restocks<S: ReadonlyStorage>(
+    storage: &S,
+    fardel_id: u128,
+    page: u32,
+    page_size: u32,
+) -> StdResult<Vec<StoredRestock>> {
+    let store =
+        ReadonlyPrefixedStorage::multilevel(&[PREFIX_RESTOCKS, &fardel_id.to_be_bytes()], storage);
+    let store = match AppendStore::<StoredRestock, _>::attach(&store) {
+        Some(store) => store?,
+        None => return Ok(vec![]),
+    };
+    store
+        .iter()
+        .rev()
+        .skip((page * page_size) as _)
+        .take(page_size as _)
+        .collect()
+}
+
+// gets restocks in appendstore order, used for export
+pub fn get_restocks_range<S: ReadonlyStorage>(
+    storage: &S,
+    fardel_id: u128,
+    start: u32,
+    count: u32,
+) -> StdResult<Vec<StoredRestock>> {
+    let store =
+        ReadonlyPrefixedStorage::multilevel(&[PREFIX_RESTOCKS, &fardel_id.to_be_bytes()], storage);
+    let store = match AppendStore::<StoredRestock, _>::attach(&store) {
+        Some(store) => store?,
+        None => return Ok(vec![]),
+    };
+    store.iter().skip(start as _).take(count as _).collect()
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::msg::EncryptedContents;
+    use crate::unpack_state::{get_encrypted_unpack, store_encrypted_unpack};
+    use cosmwasm_std::testing::MockStorage;
+    use cosmwasm_std::Binary;
+
+    // appends a fardel in the layout used before versioning, as a contract that has not been
+    //   migrated would have stored it
+    pub fn store_v1_fardel<S: Storage>(
+        storage: &mut S,
+        owner: &CanonicalAddr,
+        hash_id: u128,
+    ) -> StoredFardelV1 {
+        let global_id = get_total_fardel_count(storage);
+        let fardel = StoredFardelV1 {
+            global_id,
+            hash_id,
+            public_message: b"public message".to_vec(),
+            tags: vec![b"tag".to_vec(), b"other".to_vec()],
+            contents_data: b"contents".to_vec(),
+            cost: 1000000,
+            countable: 10,
+            approval_req: true,
+            seal_time: 1600000000,
+            timestamp: 1500000000,
+        };
+        let index = {
+            let mut store =
+                PrefixedStorage::multilevel(&[PREFIX_FARDELS, owner.as_slice()], storage);
+            let mut store =
+                AppendStoreMut::<StoredFardelV1, _>::attach_or_create(&mut store).unwrap();
+            store.push(&fardel).unwrap();
+            store.len() - 1
+        };
+        map_global_id_to_fardel(storage, global_id, owner, index).unwrap();
+        map_hash_id_to_global_id(storage, hash_id, global_id).unwrap();
+        set_bin_data(storage, KEY_FARDEL_COUNT, &(global_id + 1)).unwrap();
+        fardel
+    }
+
+    pub fn assert_upgraded_fardel(fardel: &StoredFardel, v1: &StoredFardelV1) {
+        assert_eq!(fardel.global_id, v1.global_id);
+        assert_eq!(fardel.hash_id, v1.hash_id);
+        assert_eq!(fardel.public_message, v1.public_message);
+        assert_eq!(fardel.tags, v1.tags);
+        assert_eq!(fardel.contents_data, v1.contents_data);
+        assert_eq!(fardel.cost, v1.cost);
+        assert_eq!(fardel.countable, v1.countable);
+        assert_eq!(fardel.approval_req, v1.approval_req);
+        assert_eq!(fardel.seal_time, v1.seal_time);
+        assert_eq!(fardel.timestamp, v1.timestamp);
+    }
+
+    #[test]
+    fn v1_fardel_is_read_as_v2() {
+        let mut storage = MockStorage::new();
+        let owner = CanonicalAddr(Binary(b"owner".to_vec()));
+        let v1 = store_v1_fardel(&mut storage, &owner, 42);
+
+        // no fardel has been tagged by Migrate, so it is read in the v1 layout
+        assert_upgraded_fardel(&get_stored_fardel_at(&storage, &owner, 0).unwrap(), &v1);
+
+        let fardel = get_fardel_by_global_id(&storage, 0).unwrap().unwrap();
+        assert_eq!(fardel.global_id, Uint128(v1.global_id));
+        assert_eq!(fardel.hash_id, Uint128(42));
+        assert_eq!(fardel.public_message, "public message");
+        assert_eq!(fardel.tags, vec!["tag".to_string(), "other".to_string()]);
+        assert_eq!(fardel.contents_data, "contents");
+        assert_eq!(fardel.cost.amount, Uint128(v1.cost));
+        assert_eq!(fardel.countable, v1.countable);
+        assert_eq!(fardel.approval_req, v1.approval_req);
+        assert_eq!(fardel.seal_time, v1.seal_time);
+        assert_eq!(fardel.timestamp, v1.timestamp);
+    }
+
+    #[test]
+    fn upgrade_stored_fardel_rewrites_v1_as_v2() {
+        let mut storage = MockStorage::new();
+        let owner = CanonicalAddr(Binary(b"owner".to_vec()));
+        let first = store_v1_fardel(&mut storage, &owner, 1);
+        let second = store_v1_fardel(&mut storage, &owner, 2);
+
+        upgrade_stored_fardel(&mut storage, 0).unwrap();
+        assert_eq!(get_tagged_fardel_count(&storage, &owner), 1);
+
+        let store =
+            ReadonlyPrefixedStorage::multilevel(&[PREFIX_FARDELS, owner.as_slice()], &storage);
+        let current = AppendStore::<VersionedFardel, _>::attach(&store)
+            .unwrap()
+            .unwrap();
+        match current.get_at(0).unwrap() {
+            VersionedFardel::V2(fardel) => assert_upgraded_fardel(&fardel, &first),
+            _ => panic!("Upgraded fardel is not tagged as v2"),
+        }
+        // the next fardel is untouched and still read in the v1 layout
+        assert_upgraded_fardel(&get_stored_fardel_at(&storage, &owner, 1).unwrap(), &second);
+    }
+
+    #[test]
+    fn get_fardels_pages_over_fardels_without_parts() {
+        let mut storage = MockStorage::new();
+        let owner = CanonicalAddr(Binary(b"owner".to_vec()));
+        for hash_id in 0..4 {
+            store_fardel(
+                &mut storage,
+                hash_id,
+                &owner,
+                b"public message".to_vec(),
+                vec![],
+                b"contents".to_vec(),
+                0,
+                0,
+                false,
+                0,
+                1500000000,
+            )
+            .unwrap();
+        }
+        // fardels 1 and 2 are extra parts of fardel 0
+        add_fardel_part(&mut storage, &owner, 0, 1).unwrap();
+        add_fardel_part(&mut storage, &owner, 0, 2).unwrap();
+
+        let first_page = get_fardels(&storage, &owner, 0, 1).unwrap();
+        assert_eq!(first_page.len(), 1);
+        assert_eq!(first_page[0].global_id, Uint128(3));
+        let second_page = get_fardels(&storage, &owner, 1, 1).unwrap();
+        assert_eq!(second_page.len(), 1);
+        assert_eq!(second_page[0].global_id, Uint128(0));
+        assert!(get_fardels(&storage, &owner, 2, 1).unwrap().is_empty());
+    }
+
+    #[test]
+    fn revision_leaves_encrypted_unpacks_behind() {
+        let mut storage = MockStorage::new();
+        let owner = CanonicalAddr(Binary(b"owner".to_vec()));
+        let unpacker = CanonicalAddr(Binary(b"unpacker".to_vec()));
+        store_fardel(
+            &mut storage,
+            7,
+            &owner,
+            b"public message".to_vec(),
+            vec![],
+            b"contents".to_vec(),
+            0,
+            0,
+            false,
+            0,
+            1500000000,
+        )
+        .unwrap();
+        let encrypted_contents = EncryptedContents {
+            ephemeral_pubkey: Binary(b"ephemeral".to_vec()),
+            encrypted_key: Binary(b"key".to_vec()),
+            encrypted_contents: Binary(b"contents".to_vec()),
+        };
+        store_encrypted_unpack(&mut storage, &unpacker, 0, &encrypted_contents).unwrap();
+        assert_eq!(
+            get_encrypted_unpack(&storage, &unpacker, 0),
+            Some(encrypted_contents.clone())
+        );
+
+        revise_fardel(&mut storage, 0, b"revised".to_vec(), None, None, 1600000000).unwrap();
+        assert_eq!(get_encrypted_unpack(&storage, &unpacker, 0), None);
+
+        // contents encrypted after the revision are found again
+        store_encrypted_unpack(&mut storage, &unpacker, 0, &encrypted_contents).unwrap();
+        assert_eq!(
+            get_encrypted_unpack(&storage, &unpacker, 0),
+            Some(encrypted_contents)
+        );
+    }
+}
@@ -1,7 +1,10 @@
 pub mod contract;
 mod exec;
 mod fardel_state;
+mod message_state;
 pub mod msg;
+mod notification_state;
+mod permit;
 mod query;
 mod social_state;
 pub mod state;
@@ -0,0 +1,212 @@
+use crate::social_state::is_blocked_by;
+use crate::state::{
+    get_bin_data, set_bin_data, KEY_MESSAGE_FEE, PREFIX_INBOX, PREFIX_MESSAGE_COUNT, PREFIX_OUTBOX,
+};
+use cosmwasm_std::{CanonicalAddr, ReadonlyStorage, StdError, StdResult, Storage};
+use cosmwasm_storage::{PrefixedStorage, ReadonlyPrefixedStorage};
+use secret_toolkit::storage::{AppendStore, AppendStoreMut};
+use serde::{Deserialize, Serialize};
+
+//
+// Direct messages
+//
+//   b"inbox" | {recipient canonical addr} | {appendstore index} -> StoredMessage
+//   b"outbox" | {sender canonical addr} | {appendstore index} -> StoredMessage
+//
+//   b"message-count" | {b"inbox" or b"outbox"} | {owner canonical addr} -> count not deleted
+//
+//   each message is written to both the recipient's inbox and the sender's outbox, with `other`
+//   set to the sender or recipient respectively. Deleting a message only marks that copy as
+//   deleted, the appendstore index is the message id.
+//
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct StoredMessage {
+    pub other: CanonicalAddr,
+    pub text: Vec<u8>,
+    pub timestamp: u64,
+    pub deleted: bool,
+}
+
+pub fn send_message<S: Storage>(
+    storage: &mut S,
+    from: &CanonicalAddr,
+    to: &CanonicalAddr,
+    text: Vec<u8>,
+    timestamp: u64,
+) -> StdResult<()> {
+    push_message(
+        storage,
+        to,
+        false,
+        &StoredMessage {
+            other: from.clone(),
+            text: text.clone(),
+            timestamp,
+            deleted: false,
+        },
+    )?;
+    push_message(
+        storage,
+        from,
+        true,
+        &StoredMessage {
+            other: to.clone(),
+            text,
+            timestamp,
+            deleted: false,
+        },
+    )
+}
+
+// appends a message to one of the owner's boxes, also used to import each copy as it was
+//   stored
+pub fn push_message<S: Storage>(
+    storage: &mut S,
+    owner: &CanonicalAddr,
+    sent: bool,
+    message: &StoredMessage,
+) -> StdResult<()> {
+    let count = get_number_of_messages(storage, owner, sent);
+
+    let prefix = if sent { PREFIX_OUTBOX } else { PREFIX_INBOX };
+    let mut store = PrefixedStorage::multilevel(&[prefix, owner.as_slice()], storage);
+    let mut store = AppendStoreMut::<StoredMessage, _>::attach_or_create(&mut store)?;
+    store.push(message)?;
+
+    if message.deleted {
+        return Ok(());
+    }
+    set_number_of_messages(storage, owner, sent, count + 1)
+}
+
+// marks a message in the owner's inbox (or outbox, if sent) as deleted
+pub fn delete_message<S: Storage>(
+    storage: &mut S,
+    owner: &CanonicalAddr,
+    message_id: u32,
+    sent: bool,
+) -> StdResult<()> {
+    let count = get_number_of_messages(storage, owner, sent);
+
+    let prefix = if sent { PREFIX_OUTBOX } else { PREFIX_INBOX };
+    let mut store = PrefixedStorage::multilevel(&[prefix, owner.as_slice()], storage);
+    let mut store = AppendStoreMut::<StoredMessage, _>::attach_or_create(&mut store)?;
+    if message_id >= store.len() {
+        return Err(StdError::generic_err("No message with that id."));
+    }
+    let mut message = store.get_at(message_id)?;
+    // deleting twice does not count twice
+    if message.deleted {
+        return Ok(());
+    }
+    message.deleted = true;
+    store.set_at(message_id, &message)?;
+
+    set_number_of_messages(storage, owner, sent, count.saturating_sub(1))
+}
+
+// gets messages with their ids starting from the latest, potentially skipping
+//   `page * page_size` messages. Deleted messages, and received messages from accounts the
+//   owner has since blocked, are left out before paging.
+pub fn get_messages<S: ReadonlyStorage>(
+    storage: &S,
+    owner: &CanonicalAddr,
+    sent: bool,
+    page: u32,
+    page_size: u32,
+) -> StdResult<Vec<(u32, StoredMessage)>> {
+    let prefix = if sent { PREFIX_OUTBOX } else { PREFIX_INBOX };
+    let store = ReadonlyPrefixedStorage::multilevel(&[prefix, owner.as_slice()], storage);
+    let store = if let Some(result) = AppendStore::<StoredMessage, _>::attach(&store) {
+        result?
+    } else {
+        return Ok(vec![]);
+    };
+
+    let mut messages: Vec<(u32, StoredMessage)> = vec![];
+    let mut skip = page * page_size;
+    for (idx, message) in store.iter().enumerate().rev() {
+        if messages.len() as u32 >= page_size {
+            break;
+        }
+        let message = message?;
+        if message.deleted || (!sent && is_blocked_by(storage, owner, &message.other)) {
+            continue;
+        }
+        if skip > 0 {
+            skip -= 1;
+            continue;
+        }
+        messages.push((idx as u32, message));
+    }
+    Ok(messages)
+}
+
+// returns number of messages not deleted -- for pagination
+//   received messages from blocked accounts are still counted, the block can be undone
+pub fn get_number_of_messages<S: ReadonlyStorage>(
+    storage: &S,
+    owner: &CanonicalAddr,
+    sent: bool,
+) -> u32 {
+    let prefix = if sent { PREFIX_OUTBOX } else { PREFIX_INBOX };
+    let count_store = ReadonlyPrefixedStorage::multilevel(&[PREFIX_MESSAGE_COUNT, prefix], storage);
+    get_bin_data(&count_store, owner.as_slice()).unwrap_or_else(|_| 0_u32)
+}
+
+// gets messages, including deleted ones, in appendstore order, used for export
+pub fn get_messages_range<S: ReadonlyStorage>(
+    storage: &S,
+    owner: &CanonicalAddr,
+    sent: bool,
+    start: u32,
+    count: u32,
+) -> StdResult<Vec<StoredMessage>> {
+    let prefix = if sent { PREFIX_OUTBOX } else { PREFIX_INBOX };
+    let store = ReadonlyPrefixedStorage::multilevel(&[prefix, owner.as_slice()], storage);
+    let store = if let Some(result) = AppendStore::<StoredMessage, _>::attach(&store) {
+        result?
+    } else {
+        return Ok(vec![]);
+    };
+    store.iter().skip(start as _).take(count as _).collect()
+}
+
+// returns number of messages including deleted ones -- for paging the export
+pub fn get_message_box_len<S: ReadonlyStorage>(
+    storage: &S,
+    owner: &CanonicalAddr,
+    sent: bool,
+) -> u32 {
+    let prefix = if sent { PREFIX_OUTBOX } else { PREFIX_INBOX };
+    let store = ReadonlyPrefixedStorage::multilevel(&[prefix, owner.as_slice()], storage);
+    match AppendStore::<StoredMessage, _>::attach(&store) {
+        Some(Ok(store)) => store.len(),
+        _ => 0_u32,
+    }
+}
+
+fn set_number_of_messages<S: Storage>(
+    storage: &mut S,
+    owner: &CanonicalAddr,
+    sent: bool,
+    count: u32,
+) -> StdResult<()> {
+    let prefix = if sent { PREFIX_OUTBOX } else { PREFIX_INBOX };
+    let mut count_store = PrefixedStorage::multilevel(&[PREFIX_MESSAGE_COUNT, prefix], storage);
+    set_bin_data(&mut count_store, owner.as_slice(), &count)
+}
+
+//
+// Message fee
+//   uscrt that must be sent with each message, paid to the admin. 0 (the default) means free.
+//
+
+pub fn set_message_fee<S: Storage>(storage: &mut S, fee: u128) -> StdResult<()> {
+    set_bin_data(storage, KEY_MESSAGE_FEE, &fee)
+}
+
+pub fn get_message_fee<S: ReadonlyStorage>(storage: &S) -> u128 {
+    get_bin_data(storage, KEY_MESSAGE_FEE).unwrap_or_else(|_| 0_u128)
+}
@@ -1,5 +1,7 @@
+use crate::permit::{Permission, Permit};
 use crate::state::StoredFee;
 use crate::tx_state::{PurchaseTx, SaleTx};
+use crate::user_state::DelegatePermission;
 use crate::viewing_key::ViewingKey;
 use cosmwasm_std::{Binary, HumanAddr, StdResult, Uint128};
 use schemars::JsonSchema;
@@ -51,10 +53,20 @@ pub enum HandleMsg {
         max_description_len: Option<i32>,
         padding: Option<String>,
     },
+    // Proposes a new admin address, which must be accepted by that address with AcceptAdmin
+    //   before the proposal expires. A new proposal replaces any existing one.
     ChangeAdmin {
         admin: HumanAddr,
         padding: Option<String>,
     },
+    // Accepts a pending admin proposal, must be sent by the proposed address
+    AcceptAdmin {
+        padding: Option<String>,
+    },
+    // Cancels a pending admin proposal, admin only
+    CancelAdminChange {
+        padding: Option<String>,
+    },
     // Disables the ability for non-admin users to execute handle functions, essentially making it read-only
     FreezeContract {
         padding: Option<String>,
@@ -63,9 +75,101 @@ pub enum HandleMsg {
     UnfreezeContract {
         padding: Option<String>,
     },
+    // Like FreezeContract, but users (banned ones included) can still cancel pending unpacks
+    //   to reclaim escrowed funds
+    EnterEmergencyMode {
+        padding: Option<String>,
+    },
+    // Leaves emergency mode
+    ExitEmergencyMode {
+        padding: Option<String>,
+    },
+    // Cancels up to limit (default 10) pending unpacks awaiting approval by the owner and
+    //   returns the escrowed funds to the unpackers. Only allowed in emergency mode,
+    //   call repeatedly to page through all of the owner's pending unpacks.
+    RefundAllPending {
+        owner: HumanAddr,
+        limit: Option<i32>,
+        padding: Option<String>,
+    },
+    // Upgrades stored fardels, accounts, follow lists, the handle map, blocked lists and bans to
+    //   the current storage layout, processing up to limit (default 50) records per call. Call
+    //   repeatedly until done is returned. Handles that collide ignoring case, or that are
+    //   reserved and held by an account other than the admin, are listed by GetHandleCollisions.
+    //   The contract must be frozen first.
+    Migrate {
+        limit: Option<i32>,
+        padding: Option<String>,
+    },
+    // Starts an export to a new contract instance. Generates a random export key and sends it
+    //   directly to the target contract (which must already have this contract set as its
+    //   import source), so private data in the Export* queries can only be decrypted there.
+    //   The contract must be frozen and fully migrated first.
+    //   Escrowed funds can't be moved to the new contract, so first every account is checked
+    //   for pending unpacks, up to limit (default 50) accounts per call. Call repeatedly until
+    //   done is returned, the key is only sent then. An account with pending unpacks fails the
+    //   call, refund them with RefundAllPending (in emergency mode) and call again.
+    StartExport {
+        target: HumanAddr,
+        target_code_hash: String,
+        limit: Option<i32>,
+        padding: Option<String>,
+    },
+    // Sets the contract that is allowed to send the import key, admin only
+    SetImportSource {
+        source: HumanAddr,
+        padding: Option<String>,
+    },
+    // Receives the import key, must be sent by the import source contract. This contract must
+    //   be frozen and have no accounts or fardels yet. Unfreezing it ends the import.
+    ReceiveImportKey {
+        key: Binary,
+        padding: Option<String>,
+    },
+    // Replays exported records into this contract, admin only. Settings should be imported
+    //   first (so reserved handles are known), then accounts, then fardels in global id order,
+    //   then comments, revisions, restocks and account lists.
+    //   Pending unpacks are not exported, StartExport does not start while any are left.
+    //   Revoked query permits are
+    //   not exported either, a permit only names the contract it was signed for so none can be
+    //   used here. Constants, pause flags and cursors belong to the new instance.
+    ImportBatch {
+        records: Vec<ImportRecord>,
+        padding: Option<String>,
+    },
+    // Pauses or unpauses groups of non-admin handle functions, flags that are not sent are unchanged
+    //   carry: carrying new fardels and changing carried ones (revising, scheduling, audience,
+    //     seal time and restocking)
+    //   unpack: unpacking and approving pending unpacks (canceling pending unpacks is always allowed)
+    //   social: following (including removing followers and answering follow requests),
+    //     muting, rating, commenting, and messaging
+    SetPauseFlags {
+        carry: Option<bool>,
+        unpack: Option<bool>,
+        social: Option<bool>,
+        padding: Option<String>,
+    },
+    // Sets the uscrt fee that must be sent with each SendMessage, paid to the admin (0 for free)
+    SetMessageFee {
+        fee: Uint128,
+        padding: Option<String>,
+    },
+    // Reserves handles so only the admin can register them, compared ignoring case
+    ReserveHandles {
+        handles: Vec<String>,
+        padding: Option<String>,
+    },
+    UnreserveHandles {
+        handles: Vec<String>,
+        padding: Option<String>,
+    },
+    // Bans an account, optionally until the expiry timestamp (otherwise permanent)
+    //   with a reason that the banned user can see
     Ban {
         handle: Option<String>,
         address: Option<HumanAddr>,
+        expiry: Option<i32>,
+        reason: Option<String>,
         padding: Option<String>,
     },
     Unban {
@@ -107,20 +211,54 @@ pub enum HandleMsg {
         view_settings: String,
         padding: Option<String>,
     },
+    // notification_opt_outs replaces the types of notification the account will not receive
     SetPrivateSettings {
         private_settings: String,
+        notification_opt_outs: Option<Vec<NotificationType>>,
         padding: Option<String>,
     },
     SetProfileImg {
         img: String,
         padding: Option<String>,
     },
+    // Without a name these set the default viewing key, which can run every query.
+    //   With a name they set a named key limited to scopes (default: owner, i.e. everything),
+    //   replacing any existing key with that name.
     GenerateViewingKey {
         entropy: String,
+        name: Option<String>,
+        scopes: Option<Vec<Permission>>,
         padding: Option<String>,
     },
     SetViewingKey {
         key: String,
+        name: Option<String>,
+        scopes: Option<Vec<Permission>>,
+        padding: Option<String>,
+    },
+    // Revokes a named viewing key
+    RevokeViewingKey {
+        name: String,
+        padding: Option<String>,
+    },
+    // Lets the delegate call CarryFardel, SealFardel, HideFardel, UnhideFardel,
+    //   ApprovePendingUnpacks, and DeleteComment on the sender's behalf (using on_behalf_of),
+    //   limited to the given permissions and optionally until the expiry timestamp.
+    //   Replaces any existing delegation to the same address.
+    AddDelegate {
+        delegate: HumanAddr,
+        permissions: Vec<DelegatePermission>,
+        expiry: Option<i32>,
+        padding: Option<String>,
+    },
+    // Revokes a delegation
+    RemoveDelegate {
+        delegate: HumanAddr,
+        padding: Option<String>,
+    },
+    // Revokes all query permits signed by the sender with the given name
+    RevokePermit {
+        permit_name: String,
         padding: Option<String>,
     },
     Deactivate {
@@ -129,16 +267,40 @@ pub enum HandleMsg {
     Reactivate {
         padding: Option<String>,
     },
+    // Private accounts only show their fardels to followers, who alone can unpack them, and
+    //   Follow sends them a request that they have to accept with RespondFollowRequest
+    SetAccountPrivacy {
+        private: bool,
+        padding: Option<String>,
+    },
 
     // Other accounts
+    // Blocking also ends any follow relation between the two accounts. With purge_comments the
+    //   blocked account's comments and ratings on the sender's fardels are removed as well,
+    //   checking up to limit (default 50) comments and fardels per call. Send Block again until
+    //   done is returned.
     Block {
         handle: String,
+        purge_comments: Option<bool>,
+        limit: Option<i32>,
         padding: Option<String>,
     },
     Unblock {
         handle: String,
         padding: Option<String>,
     },
+    // Mutes carriers and tags, so their fardels are left out of the sender's authenticated
+    //   GetFardels and their comments out of GetComments. Muted accounts are not notified.
+    Mute {
+        handles: Option<Vec<String>>,
+        tags: Option<Vec<String>>,
+        padding: Option<String>,
+    },
+    Unmute {
+        handles: Option<Vec<String>>,
+        tags: Option<Vec<String>>,
+        padding: Option<String>,
+    },
     Follow {
         handle: String,
         padding: Option<String>,
@@ -147,6 +309,34 @@ pub enum HandleMsg {
         handle: String,
         padding: Option<String>,
     },
+    // Stops the given handle from following the sender
+    RemoveFollower {
+        handle: String,
+        padding: Option<String>,
+    },
+    // Accepts or denies a pending follow request from the given handle
+    RespondFollowRequest {
+        handle: String,
+        accept: bool,
+        padding: Option<String>,
+    },
+    // Sends a private message to the handle, the message fee (see GetContractStatus) must be
+    //   sent with it. Accounts cannot message accounts that have blocked them.
+    SendMessage {
+        to_handle: String,
+        text: String,
+        padding: Option<String>,
+    },
+    // Deletes a message from the sender's inbox, or from their outbox if sent is true
+    DeleteMessage {
+        message_id: i32,
+        sent: Option<bool>,
+        padding: Option<String>,
+    },
+    // Marks all of the sender's notifications as read
+    MarkNotificationsRead {
+        padding: Option<String>,
+    },
 
     // My Fardels
     CarryFardel {
@@ -174,6 +364,21 @@ pub enum HandleMsg {
 
         /// seal_time sets an automatic timestamp for when the fardel will seal
         seal_time: Option<i32>,
+
+        /// publish_time schedules the fardel to go live later, until then only the
+        ///   carrier can see it and it cannot be unpacked
+        publish_time: Option<i32>,
+
+        /// on_behalf_of carries the fardel for an account that has made the sender a delegate
+        on_behalf_of: Option<HumanAddr>,
+
+        /// parts are extra sections after contents_data (which is part 0), each with its own
+        ///   cost and approval_req, that are unpacked separately
+        parts: Option<Vec<FardelPart>>,
+
+        /// audience limits who can see and unpack the fardel
+        ///   None: public
+        audience: Option<Audience>,
         padding: Option<String>,
     },
     /// Seals a fardel so no one can unpack it anymore
@@ -182,6 +387,7 @@ pub enum HandleMsg {
     ///   available again, then they need to carry a new fardel.
     SealFardel {
         fardel_id: Uint128,
+        on_behalf_of: Option<HumanAddr>,
         padding: Option<String>,
     },
     /// Hides a fardel so that it will not be returned by GetFardels or GetFardelsById
@@ -189,17 +395,65 @@ pub enum HandleMsg {
     ///   unpacked it.
     HideFardel {
         fardel_id: Uint128,
+        on_behalf_of: Option<HumanAddr>,
         padding: Option<String>,
     },
     /// unhides a previously hidden fardel
     UnhideFardel {
         fardel_id: Uint128,
+        on_behalf_of: Option<HumanAddr>,
         padding: Option<String>,
     },
     /// approves the unpacking of a set number of pending fardels,
-    ///   and processes transactions.
+    ///   and processes transactions. Payments always go to the owner.
     ApprovePendingUnpacks {
         number: Option<i32>,
+        on_behalf_of: Option<HumanAddr>,
+        padding: Option<String>,
+    },
+    /// publishes a new revision of a fardel's contents, everyone who has unpacked it
+    ///   sees the latest revision. public_message and tags are only changed if sent.
+    ///   part selects which part of a multi-part fardel to revise, None or 0 is the main
+    ///   contents. Contents encrypted to a pubkey before the revision are no longer returned.
+    ReviseFardel {
+        fardel_id: Uint128,
+        contents_data: String,
+        public_message: Option<String>,
+        tags: Option<Vec<String>>,
+        part: Option<i32>,
+        padding: Option<String>,
+    },
+    /// reschedules when a fardel goes live, None publishes it immediately. Only a fardel
+    ///   that has not gone live yet can be rescheduled.
+    SchedulePublish {
+        fardel_id: Uint128,
+        publish_time: Option<i32>,
+        padding: Option<String>,
+    },
+    /// changes who can see and unpack a fardel, anyone who already unpacked it keeps access
+    SetAudience {
+        fardel_id: Uint128,
+        audience: Audience,
+        padding: Option<String>,
+    },
+    /// moves a fardel's seal_time later, only before it has expired
+    ExtendSealTime {
+        fardel_id: Uint128,
+        seal_time: i32,
+        padding: Option<String>,
+    },
+    /// raises the countable cap of a fardel by additional_count and/or sets a new seal_time
+    ///   (0 for none), reopening it if it was sealed or sold out. Restocks are public.
+    Restock {
+        fardel_id: Uint128,
+        additional_count: Option<i32>,
+        new_seal_time: Option<i32>,
+        padding: Option<String>,
+    },
+    /// seals fardels that are past their seal_time, checking up to limit (default 50)
+    ///   fardels per call from where the last call stopped. Anyone can call it.
+    SweepExpired {
+        limit: Option<i32>,
         padding: Option<String>,
     },
 
@@ -207,13 +461,20 @@ pub enum HandleMsg {
 
     // If the fardel requires approval it will be pending,
     //   otherwise it will unpack and process transaction immediately.
+    // If a compressed secp256k1 pubkey is sent, contents are returned encrypted to it
+    //   instead of in plaintext. For a pending unpack the pubkey is kept until the owner
+    //   approves, then the encrypted contents are returned as encrypted_contents by GetUnpacked.
+    // part selects which part of a multi-part fardel to unpack, None or 0 is the main contents.
     UnpackFardel {
         fardel_id: Uint128,
+        pubkey: Option<Binary>,
+        part: Option<i32>,
         padding: Option<String>,
     },
     // Cancels a pending unpacking and returns scrt to sender
     CancelPending {
         fardel_id: Uint128,
+        part: Option<i32>,
         padding: Option<String>,
     },
     // Rates a fardel, rating values are defined as follows:
@@ -238,10 +499,11 @@ pub enum HandleMsg {
         rating: Option<bool>,
         padding: Option<String>,
     },
-    // deletes a comment
+    // deletes a comment, either your own or any comment on a fardel you carry
     DeleteComment {
         fardel_id: Uint128,
         comment_id: i32,
+        on_behalf_of: Option<HumanAddr>,
         padding: Option<String>,
     },
 }
@@ -257,12 +519,69 @@ pub enum HandleAnswer {
         status: ResponseStatus,
         msg: String,
     },
+    AcceptAdmin {
+        status: ResponseStatus,
+        msg: Option<String>,
+    },
+    CancelAdminChange {
+        status: ResponseStatus,
+        msg: Option<String>,
+    },
     FreezeContract {
         status: ResponseStatus,
     },
     UnfreezeContract {
         status: ResponseStatus,
     },
+    EnterEmergencyMode {
+        status: ResponseStatus,
+    },
+    ExitEmergencyMode {
+        status: ResponseStatus,
+    },
+    RefundAllPending {
+        status: ResponseStatus,
+        msg: Option<String>,
+        // number of pending unpacks for the owner that still need to be processed
+        remaining: i32,
+    },
+    Migrate {
+        status: ResponseStatus,
+        msg: Option<String>,
+        done: bool,
+    },
+    SetPauseFlags {
+        status: ResponseStatus,
+    },
+    SetMessageFee {
+        status: ResponseStatus,
+    },
+    ReserveHandles {
+        status: ResponseStatus,
+        msg: Option<String>,
+    },
+    UnreserveHandles {
+        status: ResponseStatus,
+        msg: Option<String>,
+    },
+    StartExport {
+        status: ResponseStatus,
+        msg: Option<String>,
+        // false while accounts are still being checked for pending unpacks
+        done: bool,
+    },
+    SetImportSource {
+        status: ResponseStatus,
+    },
+    ReceiveImportKey {
+        status: ResponseStatus,
+    },
+    ImportBatch {
+        status: ResponseStatus,
+        msg: Option<String>,
+        // number of records imported
+        imported: i32,
+    },
     Ban {
         status: ResponseStatus,
         msg: Option<String>,
@@ -313,6 +632,21 @@ pub enum HandleAnswer {
         status: ResponseStatus,
         msg: Option<String>,
     },
+    RevokeViewingKey {
+        status: ResponseStatus,
+        msg: Option<String>,
+    },
+    AddDelegate {
+        status: ResponseStatus,
+        msg: Option<String>,
+    },
+    RemoveDelegate {
+        status: ResponseStatus,
+        msg: Option<String>,
+    },
+    RevokePermit {
+        status: ResponseStatus,
+    },
     Deactivate {
         status: ResponseStatus,
         msg: Option<String>,
@@ -321,22 +655,55 @@ pub enum HandleAnswer {
         status: ResponseStatus,
         msg: Option<String>,
     },
+    SetAccountPrivacy {
+        status: ResponseStatus,
+    },
 
     // Other accounts
     Block {
         status: ResponseStatus,
         msg: Option<String>,
+        done: bool,
     },
     Unblock {
         status: ResponseStatus,
         msg: Option<String>,
     },
+    Mute {
+        status: ResponseStatus,
+        msg: Option<String>,
+    },
+    Unmute {
+        status: ResponseStatus,
+        msg: Option<String>,
+    },
     Follow {
         status: ResponseStatus,
+        // true when the followed account is private and the follow is a pending request
+        pending: bool,
     },
     Unfollow {
         status: ResponseStatus,
     },
+    RemoveFollower {
+        status: ResponseStatus,
+        msg: Option<String>,
+    },
+    RespondFollowRequest {
+        status: ResponseStatus,
+        msg: Option<String>,
+    },
+    SendMessage {
+        status: ResponseStatus,
+        msg: Option<String>,
+    },
+    DeleteMessage {
+        status: ResponseStatus,
+        msg: Option<String>,
+    },
+    MarkNotificationsRead {
+        status: ResponseStatus,
+    },
 
     // My Fardels
     CarryFardel {
@@ -360,12 +727,39 @@ pub enum HandleAnswer {
         status: ResponseStatus,
         msg: Option<String>,
     },
+    ReviseFardel {
+        status: ResponseStatus,
+        msg: Option<String>,
+    },
+    SchedulePublish {
+        status: ResponseStatus,
+        msg: Option<String>,
+    },
+    SetAudience {
+        status: ResponseStatus,
+        msg: Option<String>,
+    },
+    ExtendSealTime {
+        status: ResponseStatus,
+        msg: Option<String>,
+    },
+    Restock {
+        status: ResponseStatus,
+        msg: Option<String>,
+    },
+    SweepExpired {
+        status: ResponseStatus,
+        msg: Option<String>,
+        // number of fardels sealed by this call
+        sealed: i32,
+    },
 
     // Other Fardels
     UnpackFardel {
         status: ResponseStatus,
         msg: Option<String>,
         contents_data: Option<String>,
+        encrypted_contents: Option<EncryptedContents>,
     },
     CancelPending {
         status: ResponseStatus,
@@ -398,6 +792,8 @@ pub enum QueryMsg {
 
     // User queries
     // Get the public profile for a given handle (description, profile img)
+    //   a handle released by a rename still finds its previous owner for a cooldown period,
+    //   the response has the current handle
     GetProfile {
         handle: String,
     },
@@ -405,7 +801,7 @@ pub enum QueryMsg {
     GetProfileByIndex {
         idx: i32,
     },
-    // Check if the given handle is available
+    // Check if the given handle is available: valid, not reserved, and not taken ignoring case
     IsHandleAvailable {
         handle: String,
     },
@@ -425,6 +821,27 @@ pub enum QueryMsg {
         page: Option<i32>,
         page_size: Option<i32>,
     },
+    // Get paginated restock history (newest first) for the given fardel
+    GetRestocks {
+        fardel_id: Uint128,
+        page: Option<i32>,
+        page_size: Option<i32>,
+    },
+    // Get the currently pending admin proposal, if there is one
+    GetPendingAdmin {},
+    // Get whether the contract is frozen and which groups of functions are paused
+    GetContractStatus {},
+
+    //
+    // Queries authenticated with a query permit instead of a viewing key
+    //
+
+    // Runs any query requiring authentication, the query's address must be the permit
+    //   signer and its key is ignored. The permit must grant the query's permission.
+    WithPermit {
+        permit: Permit,
+        query: Box<QueryMsg>,
+    },
 
     //
     // Queries requiring authentication (viewing key)
@@ -448,7 +865,18 @@ pub enum QueryMsg {
         address: HumanAddr,
         key: String,
     },
+    // Get the logged in user's named viewing keys (names and scopes only)
+    GetViewingKeys {
+        address: HumanAddr,
+        key: String,
+    },
+    // Get the logged in user's active delegates
+    GetDelegates {
+        address: HumanAddr,
+        key: String,
+    },
     // Get logged in user's list of handles they are currently following
+    //   newest first, but an unfollow moves the last follow into the removed one's place
     GetFollowing {
         address: HumanAddr,
         key: String,
@@ -462,12 +890,71 @@ pub enum QueryMsg {
         handle: String,
     },
     // Get logged in user's list of followers
+    //   newest first, but a removed follower's place is taken by the last follower
     GetFollowers {
         address: HumanAddr,
         key: String,
         page: Option<i32>,
         page_size: Option<i32>,
     },
+    // Get logged in user's pending follow requests
+    GetFollowRequests {
+        address: HumanAddr,
+        key: String,
+        page: Option<i32>,
+        page_size: Option<i32>,
+    },
+    // Get handles that the logged in user follows and that follow them back
+    GetMutuals {
+        address: HumanAddr,
+        key: String,
+        page: Option<i32>,
+        page_size: Option<i32>,
+    },
+    // Get whether a given handle follows the logged in user
+    FollowsMe {
+        address: HumanAddr,
+        key: String,
+        handle: String,
+    },
+    // Get logged in user's list of blocked handles
+    //   newest first, but an unblock moves the last block into the removed one's place
+    GetBlocked {
+        address: HumanAddr,
+        key: String,
+        page: Option<i32>,
+        page_size: Option<i32>,
+    },
+    // Get logged in user's muted handles and tags
+    GetMutes {
+        address: HumanAddr,
+        key: String,
+    },
+    // Get logged in user's received messages, newest first
+    GetInbox {
+        address: HumanAddr,
+        key: String,
+        page: Option<i32>,
+        page_size: Option<i32>,
+    },
+    // Get logged in user's sent messages, newest first
+    GetOutbox {
+        address: HumanAddr,
+        key: String,
+        page: Option<i32>,
+        page_size: Option<i32>,
+    },
+    // Get logged in user's notifications, newest first, starting below the before id (or from
+    //   the latest). Pass the lowest notification_id received to get the next page.
+    //   With since only notifications with a greater id are returned, pass the highest
+    //   notification_id received to poll for new ones.
+    GetNotifications {
+        address: HumanAddr,
+        key: String,
+        since: Option<i32>,
+        before: Option<i32>,
+        page_size: Option<i32>,
+    },
     // Get a fardel by hash id, as a logged in user (with unpacked private data)
     GetFardelByIdAuth {
         address: HumanAddr,
@@ -488,13 +975,22 @@ pub enum QueryMsg {
         key: String,
         fardel_id: Uint128,
     },
-    // Get paginated list of fardels that logged in user has unpacked
+    // Get paginated list of fardels that logged in user has unpacked, leaving out muted
+    //   carriers and tags
     GetUnpacked {
         address: HumanAddr,
         key: String,
         page: Option<i32>,
         page_size: Option<i32>,
     },
+    // Get paginated revision history (newest first) of a fardel the logged in user has unpacked
+    GetFardelRevisions {
+        address: HumanAddr,
+        key: String,
+        fardel_id: Uint128,
+        page: Option<i32>,
+        page_size: Option<i32>,
+    },
     // Get information about pending unpacks needing approval by the currently logged in user
     GetPendingApprovals {
         address: HumanAddr,
@@ -502,6 +998,8 @@ pub enum QueryMsg {
         number: Option<i32>,
     },
     // Get paginated list of comments for the given fardel, as a logged in user
+    //   muted commenters are left out, and no comments are listed if the carrier or a tag of
+    //   the fardel is muted
     GetCommentsAuth {
         address: HumanAddr,
         key: String,
@@ -509,6 +1007,11 @@ pub enum QueryMsg {
         page: Option<i32>,
         page_size: Option<i32>,
     },
+    // Get the ban reason and expiry for the logged in user, available while banned
+    GetBanStatus {
+        address: HumanAddr,
+        key: String,
+    },
     // Gets whether the logged in user has rated (upvoted or downvoted) the given fardel
     // returns the rating (true: upvote, false: downvote, None: no vote)
     GetRating {
@@ -536,9 +1039,136 @@ pub enum QueryMsg {
         start: Option<i32>,
         count: Option<i32>,
     },
+    // Admin-only list of currently active bans
+    GetBans {
+        // must match admin
+        address: HumanAddr,
+        key: String,
+        start: Option<i32>,
+        count: Option<i32>,
+    },
+    // Admin-only list of reserved handles
+    GetReservedHandles {
+        // must match admin
+        address: HumanAddr,
+        key: String,
+    },
+    // Admin-only list of accounts whose handle matched another account's ignoring case, or
+    //   was reserved, when handles were normalized by Migrate or on import
+    GetHandleCollisions {
+        // must match admin
+        address: HumanAddr,
+        key: String,
+        start: Option<i32>,
+        count: Option<i32>,
+    },
+    // Admin-only export of fardels by global id, requires StartExport
+    ExportFardels {
+        // must match admin
+        address: HumanAddr,
+        key: String,
+        start: Option<Uint128>,
+        count: Option<Uint128>,
+    },
+    // Admin-only export of all comments (including deleted) on a fardel
+    ExportComments {
+        // must match admin
+        address: HumanAddr,
+        key: String,
+        fardel_id: Uint128,
+        start: Option<i32>,
+        count: Option<i32>,
+    },
+    // Admin-only export of a fardel's revisions, oldest first (contents encrypted)
+    ExportRevisions {
+        // must match admin
+        address: HumanAddr,
+        key: String,
+        fardel_id: Uint128,
+        start: Option<i32>,
+        count: Option<i32>,
+    },
+    // Admin-only export of a fardel's restocks, oldest first
+    ExportRestocks {
+        // must match admin
+        address: HumanAddr,
+        key: String,
+        fardel_id: Uint128,
+        start: Option<i32>,
+        count: Option<i32>,
+    },
+    // Admin-only export of contract-wide settings, requires StartExport
+    ExportSettings {
+        // must match admin
+        address: HumanAddr,
+        key: String,
+    },
+    // Admin-only export of registered accounts, requires StartExport
+    ExportAccounts {
+        // must match admin
+        address: HumanAddr,
+        key: String,
+        start: Option<i32>,
+        count: Option<i32>,
+    },
+    // Admin-only export of one of an account's lists
+    ExportAccountList {
+        // must match admin
+        address: HumanAddr,
+        key: String,
+        owner: HumanAddr,
+        list: ExportList,
+        start: Option<i32>,
+        count: Option<i32>,
+    },
 }
 
 impl QueryMsg {
+    // the permission a query permit needs to run this query, None if it does not need authentication
+    pub fn permission(&self) -> Option<Permission> {
+        match self {
+            Self::GetSaleTransactions { .. } | Self::GetPurchaseTransactions { .. } => {
+                Some(Permission::History)
+            }
+            Self::GetUnpacked { .. } | Self::GetFardelRevisions { .. } => {
+                Some(Permission::Unpacked)
+            }
+            Self::GetPendingApprovals { .. } => Some(Permission::Pending),
+            Self::GetInbox { .. } | Self::GetOutbox { .. } => Some(Permission::Messages),
+            Self::GetNotifications { .. } => Some(Permission::Notifications),
+            Self::GetHandle { .. }
+            | Self::GetFollowing { .. }
+            | Self::IsFollowing { .. }
+            | Self::GetFollowers { .. }
+            | Self::GetFollowRequests { .. }
+            | Self::GetMutuals { .. }
+            | Self::FollowsMe { .. }
+            | Self::GetBlocked { .. }
+            | Self::GetMutes { .. }
+            | Self::GetFardelByIdAuth { .. }
+            | Self::GetFardelsAuth { .. }
+            | Self::IsPendingUnpack { .. }
+            | Self::GetCommentsAuth { .. }
+            | Self::GetRating { .. }
+            | Self::GetBanStatus { .. } => Some(Permission::Social),
+            Self::GetViewingKeys { .. } | Self::GetDelegates { .. } => Some(Permission::Owner),
+            // Admin functions
+            Self::GetFardelsBatch { .. }
+            | Self::GetRegisteredAddresses { .. }
+            | Self::GetBans { .. }
+            | Self::GetReservedHandles { .. }
+            | Self::GetHandleCollisions { .. }
+            | Self::ExportFardels { .. }
+            | Self::ExportComments { .. }
+            | Self::ExportRevisions { .. }
+            | Self::ExportRestocks { .. }
+            | Self::ExportSettings { .. }
+            | Self::ExportAccounts { .. }
+            | Self::ExportAccountList { .. } => Some(Permission::Owner),
+            _ => None,
+        }
+    }
+
     pub fn get_validation_params(&self) -> (Vec<&HumanAddr>, ViewingKey) {
         match self {
             Self::GetSaleTransactions { address, key, .. } => {
@@ -548,9 +1178,21 @@ impl QueryMsg {
                 (vec![address], ViewingKey(key.clone()))
             }
             Self::GetHandle { address, key } => (vec![address], ViewingKey(key.clone())),
+            Self::GetViewingKeys { address, key } => (vec![address], ViewingKey(key.clone())),
+            Self::GetDelegates { address, key } => (vec![address], ViewingKey(key.clone())),
             Self::GetFollowing { address, key, .. } => (vec![address], ViewingKey(key.clone())),
             Self::IsFollowing { address, key, .. } => (vec![address], ViewingKey(key.clone())),
             Self::GetFollowers { address, key, .. } => (vec![address], ViewingKey(key.clone())),
+            Self::GetFollowRequests { address, key, .. } => {
+                (vec![address], ViewingKey(key.clone()))
+            }
+            Self::GetMutuals { address, key, .. } => (vec![address], ViewingKey(key.clone())),
+            Self::FollowsMe { address, key, .. } => (vec![address], ViewingKey(key.clone())),
+            Self::GetBlocked { address, key, .. } => (vec![address], ViewingKey(key.clone())),
+            Self::GetMutes { address, key } => (vec![address], ViewingKey(key.clone())),
+            Self::GetInbox { address, key, .. } => (vec![address], ViewingKey(key.clone())),
+            Self::GetOutbox { address, key, .. } => (vec![address], ViewingKey(key.clone())),
+            Self::GetNotifications { address, key, .. } => (vec![address], ViewingKey(key.clone())),
             Self::GetFardelByIdAuth { address, key, .. } => {
                 (vec![address], ViewingKey(key.clone()))
             }
@@ -562,11 +1204,29 @@ impl QueryMsg {
             }
             Self::GetCommentsAuth { address, key, .. } => (vec![address], ViewingKey(key.clone())),
             Self::GetRating { address, key, .. } => (vec![address], ViewingKey(key.clone())),
+            Self::GetFardelRevisions { address, key, .. } => {
+                (vec![address], ViewingKey(key.clone()))
+            }
+            Self::GetBanStatus { address, key } => (vec![address], ViewingKey(key.clone())),
             // Admin functions
             Self::GetFardelsBatch { address, key, .. } => (vec![address], ViewingKey(key.clone())),
             Self::GetRegisteredAddresses { address, key, .. } => {
                 (vec![address], ViewingKey(key.clone()))
             }
+            Self::GetBans { address, key, .. } => (vec![address], ViewingKey(key.clone())),
+            Self::GetReservedHandles { address, key } => (vec![address], ViewingKey(key.clone())),
+            Self::GetHandleCollisions { address, key, .. } => {
+                (vec![address], ViewingKey(key.clone()))
+            }
+            Self::ExportFardels { address, key, .. } => (vec![address], ViewingKey(key.clone())),
+            Self::ExportComments { address, key, .. } => (vec![address], ViewingKey(key.clone())),
+            Self::ExportRevisions { address, key, .. } => (vec![address], ViewingKey(key.clone())),
+            Self::ExportRestocks { address, key, .. } => (vec![address], ViewingKey(key.clone())),
+            Self::ExportSettings { address, key } => (vec![address], ViewingKey(key.clone())),
+            Self::ExportAccounts { address, key, .. } => (vec![address], ViewingKey(key.clone())),
+            Self::ExportAccountList { address, key, .. } => {
+                (vec![address], ViewingKey(key.clone()))
+            }
             _ => panic!("This query type does not require authentication"),
         }
     }
@@ -605,12 +1265,83 @@ pub struct FardelResponse {
     pub contents_data: Option<String>,
     // user's current rating of this fardel if there is one
     pub rating: Option<bool>,
+    // timestamp of the latest revision if the carrier has revised the fardel
+    pub revised: Option<i32>,
+    // extra parts of a multi-part fardel, with the viewer's unpack status for each
+    pub parts: Vec<FardelPartResponse>,
+    // only set while the fardel is scheduled to be published in the future
+    pub publish_time: Option<i32>,
+    // contents encrypted to the pubkey sent with a pending unpack, once it is approved
+    pub encrypted_contents: Option<EncryptedContents>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct FardelPart {
+    pub contents_data: String,
+    pub cost: Uint128,
+    pub approval_req: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Audience {
+    Public,
+    // only accounts following the carrier
+    Followers,
+    // only the listed handles
+    AllowList { handles: Vec<String> },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct FardelPartResponse {
+    pub part: i32,
+    pub cost: Uint128,
+    pub approval_req: bool,
+    pub unpacked: bool,
+    pub pending_unpack: bool,
+    // only if unpacked
+    pub contents_data: Option<String>,
+    // contents encrypted to the pubkey sent with a pending unpack, once it is approved
+    pub encrypted_contents: Option<EncryptedContents>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RevisionResponse {
+    // revision 0 is the fardel as originally carried
+    pub revision: i32,
+    pub public_message: String,
+    pub tags: Vec<String>,
+    pub contents_data: String,
+    pub timestamp: i32,
+}
+
+// contents_data encrypted to a buyer's pubkey, see utils::encrypt_contents_for_pubkey
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct EncryptedContents {
+    pub ephemeral_pubkey: Binary,
+    // the fardel's contents key, encrypted with sha256 of the ecdh shared secret
+    //   both fields are XChaCha20-Poly1305 (ciphertext then tag) with key sha256(key) and
+    //   nonce sha256(ephemeral_pubkey)[..24]
+    pub encrypted_key: Binary,
+    // contents_data encrypted with the fardel's contents key
+    pub encrypted_contents: Binary,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RestockResponse {
+    pub added: i32,
+    // countable cap and seal_time after the restock
+    pub countable: Option<i32>,
+    pub seal_time: Option<i32>,
+    pub timestamp: i32,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct PendingApprovalResponse {
     pub handle: String,
     pub fardel_id: Uint128,
+    // set when the pending unpack is for an extra part of a multi-part fardel
+    pub part: Option<i32>,
     pub canceled: bool,
 }
 
@@ -634,6 +1365,53 @@ pub struct FardelBatchResponse {
     pub remaining: Option<i32>,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MessageResponse {
+    pub message_id: i32,
+    // sender for inbox messages, recipient for outbox messages
+    pub handle: String,
+    pub text: String,
+    pub timestamp: i32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationType {
+    // someone followed the account
+    Follow,
+    // someone asked to follow the private account
+    FollowRequest,
+    // someone commented on one of the account's fardels
+    Comment,
+    // someone rated one of the account's fardels
+    Rating,
+    // someone unpacked one of the account's fardels that requires approval
+    PendingUnpack,
+    // the carrier approved the account's pending unpack
+    UnpackApproved,
+    // someone canceled their pending unpack of one of the account's fardels
+    UnpackCanceled,
+    // the private account accepted the account's follow request
+    FollowAccepted,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct NotificationResponse {
+    pub notification_id: i32,
+    pub kind: NotificationType,
+    // handle of the account that caused the notification
+    pub handle: Option<String>,
+    pub fardel_id: Option<Uint128>,
+    pub timestamp: i32,
+    pub read: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct FollowRequestResponse {
+    pub handle: String,
+    pub timestamp: i32,
+}
+
 #[derive(Serialize, Deserialize, JsonSchema, Debug)]
 pub struct RegisteredAccountsResponse {
     pub address: HumanAddr,
@@ -645,6 +1423,301 @@ pub struct RegisteredAccountsResponse {
     pub img: Option<String>,
 }
 
+#[derive(Serialize, Deserialize, JsonSchema, Debug)]
+pub struct ViewingKeyResponse {
+    pub name: String,
+    pub scopes: Vec<Permission>,
+    pub timestamp: i32,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug)]
+pub struct DelegateResponse {
+    pub delegate: HumanAddr,
+    pub permissions: Vec<DelegatePermission>,
+    // None means the delegation does not expire
+    pub expiry: Option<i32>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug)]
+pub struct BanResponse {
+    pub address: HumanAddr,
+    pub handle: Option<String>,
+    pub reason: String,
+    // None means the ban is permanent
+    pub expiry: Option<i32>,
+    pub timestamp: i32,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug)]
+pub struct HandleCollisionResponse {
+    pub address: HumanAddr,
+    // the account's handle, which it cannot be looked up by
+    pub handle: String,
+    // the account that holds the handle, the admin for a reserved handle
+    pub holder: HumanAddr,
+    // None if the account has since set a new handle
+    pub current_handle: Option<String>,
+}
+
+//
+// Export / import records
+//   encrypted fields can only be decrypted by the contract that received the export key
+//
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct FardelExport {
+    pub global_id: Uint128,
+    pub hash_id: Uint128,
+    pub owner: HumanAddr,
+    pub public_message: String,
+    pub tags: Vec<String>,
+    // encrypted
+    pub contents_data: Binary,
+    // encrypted, None for fardels carried before contents keys were added
+    pub contents_key: Option<Binary>,
+    pub cost: Uint128,
+    pub countable: i32,
+    pub approval_req: bool,
+    pub seal_time: i32,
+    pub timestamp: i32,
+    pub img: String,
+    pub sealed: bool,
+    pub hidden: bool,
+    pub removed: bool,
+    pub unpack_count: i32,
+    pub upvotes: i32,
+    pub downvotes: i32,
+    // for paging ExportComments, ExportRevisions and ExportRestocks
+    pub number_of_comments: i32,
+    pub number_of_revisions: i32,
+    pub number_of_restocks: i32,
+    // global id of the fardel this is an extra part of
+    pub part_of: Option<Uint128>,
+    // 0 if not scheduled
+    pub publish_time: i32,
+    pub audience: AudienceExport,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AudienceExport {
+    Public,
+    Followers,
+    AllowList { addresses: Vec<HumanAddr> },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CommentExport {
+    pub commenter: HumanAddr,
+    pub text: String,
+    pub deleted: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RevisionExport {
+    pub public_message: String,
+    pub tags: Vec<String>,
+    // encrypted
+    pub contents_data: Binary,
+    pub timestamp: i32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RestockExport {
+    pub added: i32,
+    pub countable: i32,
+    pub seal_time: i32,
+    pub timestamp: i32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SettingsExport {
+    pub reserved_handles: Vec<String>,
+    pub message_fee: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BanExport {
+    pub banned: bool,
+    pub expiry: i32,
+    pub reason: String,
+    pub timestamp: i32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AccountExport {
+    pub address: HumanAddr,
+    pub handle: String,
+    pub description: String,
+    pub view_settings: String,
+    // encrypted
+    pub private_settings: Binary,
+    pub img: String,
+    // encrypted hash of the viewing key
+    pub viewing_key: Option<Binary>,
+    pub deactivated: bool,
+    pub private: bool,
+    pub ban: Option<BanExport>,
+    // every slot, including revoked keys
+    pub named_viewing_keys: Vec<NamedViewingKeyExport>,
+    // encrypted json of MutesExport, mutes are private to the account
+    pub mutes: Binary,
+    pub notification_opt_outs: Vec<NotificationType>,
+    pub notifications_read: i32,
+    // list lengths, for paging ExportAccountList
+    pub number_following: i32,
+    pub number_unpacked: i32,
+    pub number_sales: i32,
+    pub number_purchases: i32,
+    pub number_blocked: i32,
+    pub number_follow_requests: i32,
+    pub number_inbox: i32,
+    pub number_outbox: i32,
+    pub number_notifications: i32,
+    pub number_previous_handles: i32,
+    pub number_delegates: i32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct NamedViewingKeyExport {
+    pub name: String,
+    // encrypted hash of the key
+    pub hashed_key: Binary,
+    pub scopes: Vec<Permission>,
+    pub timestamp: i32,
+    pub revoked: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MutesExport {
+    pub accounts: Vec<HumanAddr>,
+    pub tags: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportList {
+    Following,
+    Unpacked,
+    Sales,
+    Purchases,
+    Blocked,
+    FollowRequests,
+    Inbox,
+    Outbox,
+    Notifications,
+    PreviousHandles,
+    Delegates,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+// the other account in a social or message entry is the canonical address, encrypted
+pub enum AccountListEntry {
+    Following {
+        // encrypted
+        who: Binary,
+        active: bool,
+    },
+    Unpacked {
+        // global id
+        fardel_id: Uint128,
+        rating: Option<bool>,
+        // contents delivered to the buyer's public key, if they sent one
+        encrypted_contents: Option<EncryptedContents>,
+    },
+    Sale {
+        // global id
+        fardel_id: Uint128,
+        unpacker: HumanAddr,
+        amount: Uint128,
+        fee: Uint128,
+        timestamp: i32,
+    },
+    Purchase {
+        // global id
+        fardel_id: Uint128,
+        owner: HumanAddr,
+        amount: Uint128,
+        fee: Uint128,
+        timestamp: i32,
+    },
+    Blocked {
+        // encrypted
+        who: Binary,
+    },
+    FollowRequest {
+        // encrypted
+        requester: Binary,
+        // 0 if no longer pending
+        timestamp: i32,
+    },
+    Received {
+        // encrypted
+        from: Binary,
+        // encrypted
+        text: Binary,
+        timestamp: i32,
+        deleted: bool,
+    },
+    Sent {
+        // encrypted
+        to: Binary,
+        // encrypted
+        text: Binary,
+        timestamp: i32,
+        deleted: bool,
+    },
+    Notification {
+        kind: NotificationType,
+        // encrypted
+        actor: Binary,
+        // global id
+        fardel_id: Option<Uint128>,
+        timestamp: i32,
+    },
+    PreviousHandle {
+        handle: String,
+        // when it was released
+        timestamp: i32,
+    },
+    Delegate {
+        who: HumanAddr,
+        permissions: Vec<DelegatePermission>,
+        expiry: i32,
+        revoked: bool,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportRecord {
+    Settings(SettingsExport),
+    Account(AccountExport),
+    Fardel(FardelExport),
+    Comments {
+        // global id
+        fardel_id: Uint128,
+        comments: Vec<CommentExport>,
+    },
+    Revisions {
+        // global id
+        fardel_id: Uint128,
+        revisions: Vec<RevisionExport>,
+    },
+    Restocks {
+        // global id
+        fardel_id: Uint128,
+        restocks: Vec<RestockExport>,
+    },
+    // entries from one ExportAccountList page, start is the start it was queried with
+    AccountList {
+        owner: HumanAddr,
+        start: i32,
+        entries: Vec<AccountListEntry>,
+    },
+}
+
 #[derive(Serialize, Deserialize, JsonSchema, Debug)]
 #[serde(rename_all = "snake_case")]
 pub enum QueryAnswer {
@@ -655,6 +1728,8 @@ pub enum QueryAnswer {
         view_settings: Option<String>,
         img: Option<String>,
         follower_count: i32,
+        // most recent first
+        previous_handles: Vec<String>,
     },
     GetProfileByIndex {
         status: ResponseStatus,
@@ -677,9 +1752,27 @@ pub enum QueryAnswer {
     GetComments {
         comments: Vec<CommentResponse>,
     },
+    GetRestocks {
+        restocks: Vec<RestockResponse>,
+        total_count: i32,
+    },
     GetRating {
         rating: Option<bool>,
     },
+    GetPendingAdmin {
+        address: Option<HumanAddr>,
+        deadline: Option<i32>,
+    },
+    GetContractStatus {
+        frozen: bool,
+        emergency: bool,
+        carry_paused: bool,
+        unpack_paused: bool,
+        social_paused: bool,
+        schema_version: i32,
+        // uscrt required with each SendMessage
+        message_fee: Uint128,
+    },
 
     GetSaleTransactions {
         txs: Vec<SaleTx>,
@@ -693,6 +1786,7 @@ pub enum QueryAnswer {
         status: ResponseStatus,
         handle: Option<String>,
         private_settings: Option<String>,
+        notification_opt_outs: Vec<NotificationType>,
     },
     GetFollowing {
         following: Vec<String>,
@@ -707,6 +1801,44 @@ pub enum QueryAnswer {
         // for pagination
         total_count: i32,
     },
+    GetFollowRequests {
+        requests: Vec<FollowRequestResponse>,
+        // for pagination
+        total_count: i32,
+    },
+    GetMutuals {
+        mutuals: Vec<String>,
+        // for pagination, a lower bound: counted up to one past the requested page, so it is
+        //   only exact when it is not more than (page + 1) * page_size
+        total_count: i32,
+    },
+    FollowsMe {
+        response: bool,
+    },
+    GetBlocked {
+        blocked: Vec<String>,
+        // for pagination, number of accounts currently blocked
+        total_count: i32,
+    },
+    GetMutes {
+        handles: Vec<String>,
+        tags: Vec<String>,
+    },
+    GetInbox {
+        messages: Vec<MessageResponse>,
+        // for pagination, messages not deleted, including ones from blocked accounts that
+        //   are not listed
+        total_count: i32,
+    },
+    GetOutbox {
+        messages: Vec<MessageResponse>,
+        // for pagination, messages not deleted
+        total_count: i32,
+    },
+    GetNotifications {
+        notifications: Vec<NotificationResponse>,
+        unread_count: i32,
+    },
     IsPendingUnpack {
         response: bool,
     },
@@ -714,6 +1846,10 @@ pub enum QueryAnswer {
         fardels: Vec<FardelResponse>,
         total_count: i32,
     },
+    GetFardelRevisions {
+        revisions: Vec<RevisionResponse>,
+        total_count: i32,
+    },
     GetPendingApprovals {
         pending: Vec<PendingApprovalResponse>,
     },
@@ -724,6 +1860,52 @@ pub enum QueryAnswer {
         accounts: Vec<RegisteredAccountsResponse>,
         total_registered: i32,
     },
+    GetViewingKeys {
+        keys: Vec<ViewingKeyResponse>,
+    },
+    GetDelegates {
+        delegates: Vec<DelegateResponse>,
+    },
+    GetBanStatus {
+        banned: bool,
+        reason: Option<String>,
+        expiry: Option<i32>,
+    },
+    GetBans {
+        bans: Vec<BanResponse>,
+        // for pagination, includes lapsed and lifted bans
+        total_count: i32,
+    },
+    GetReservedHandles {
+        handles: Vec<String>,
+    },
+    GetHandleCollisions {
+        collisions: Vec<HandleCollisionResponse>,
+        total_count: i32,
+    },
+    ExportFardels {
+        fardels: Vec<FardelExport>,
+        total_count: Uint128,
+    },
+    ExportComments {
+        comments: Vec<CommentExport>,
+    },
+    ExportRevisions {
+        revisions: Vec<RevisionExport>,
+    },
+    ExportRestocks {
+        restocks: Vec<RestockExport>,
+    },
+    ExportSettings {
+        settings: SettingsExport,
+    },
+    ExportAccounts {
+        accounts: Vec<AccountExport>,
+        total_count: i32,
+    },
+    ExportAccountList {
+        entries: Vec<AccountListEntry>,
+    },
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
@@ -0,0 +1,173 @@
+use crate::msg::NotificationType;
+use crate::state::{
+    get_bin_data, set_bin_data, PREFIX_NOTIFICATIONS, PREFIX_NOTIFICATIONS_READ,
+    PREFIX_NOTIFICATION_OPT_OUTS,
+};
+use cosmwasm_std::{CanonicalAddr, ReadonlyStorage, StdResult, Storage};
+use cosmwasm_storage::{PrefixedStorage, ReadonlyPrefixedStorage};
+use secret_toolkit::storage::{AppendStore, AppendStoreMut};
+use serde::{Deserialize, Serialize};
+
+//
+// Notifications
+//
+//   b"notifications" | {recipient canonical addr} | {appendstore index} -> StoredNotification
+//   b"notifications-read" | {recipient canonical addr} -> u32 (number of notifications read)
+//   b"notification-opt-outs" | {recipient canonical addr} -> Vec<NotificationType>
+//
+//   the appendstore index is the notification id. Everything below the read count has been
+//   marked read by the recipient.
+//
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct StoredNotification {
+    pub kind: NotificationType,
+    // the account that caused the notification
+    pub actor: CanonicalAddr,
+    // global id of the fardel involved, if any
+    pub fardel_id: Option<u128>,
+    pub timestamp: u64,
+}
+
+// appends a notification for the recipient, unless they opted out of that type or
+//   caused it themselves
+pub fn notify<S: Storage>(
+    storage: &mut S,
+    recipient: &CanonicalAddr,
+    kind: NotificationType,
+    actor: &CanonicalAddr,
+    fardel_id: Option<u128>,
+    timestamp: u64,
+) -> StdResult<()> {
+    if recipient == actor || get_notification_opt_outs(storage, recipient).contains(&kind) {
+        return Ok(());
+    }
+    push_notification(
+        storage,
+        recipient,
+        &StoredNotification {
+            kind,
+            actor: actor.clone(),
+            fardel_id,
+            timestamp,
+        },
+    )
+}
+
+// appends a notification without checking opt-outs, used to import notifications as they
+//   were stored
+pub fn push_notification<S: Storage>(
+    storage: &mut S,
+    recipient: &CanonicalAddr,
+    notification: &StoredNotification,
+) -> StdResult<()> {
+    let mut storage =
+        PrefixedStorage::multilevel(&[PREFIX_NOTIFICATIONS, recipient.as_slice()], storage);
+    let mut storage = AppendStoreMut::<StoredNotification, _>::attach_or_create(&mut storage)?;
+    storage.push(notification)
+}
+
+// gets up to `count` notifications with their ids, newest first, starting with the one just
+//   below `before` (or the latest) and stopping at `since`, whose id is not included
+pub fn get_notifications<S: ReadonlyStorage>(
+    storage: &S,
+    owner: &CanonicalAddr,
+    since: Option<u32>,
+    before: Option<u32>,
+    count: u32,
+) -> StdResult<Vec<(u32, StoredNotification)>> {
+    let storage =
+        ReadonlyPrefixedStorage::multilevel(&[PREFIX_NOTIFICATIONS, owner.as_slice()], storage);
+    let storage = if let Some(result) = AppendStore::<StoredNotification, _>::attach(&storage) {
+        result?
+    } else {
+        return Ok(vec![]);
+    };
+
+    let len = storage.len();
+    let skip = before.map_or(0, |before| len.saturating_sub(before));
+    let mut notifications: Vec<(u32, StoredNotification)> = vec![];
+    for (idx, notification) in storage
+        .iter()
+        .enumerate()
+        .rev()
+        .skip(skip as _)
+        .take_while(|(idx, _)| since.map_or(true, |since| *idx as u32 > since))
+        .take(count as _)
+    {
+        notifications.push((idx as u32, notification?));
+    }
+    Ok(notifications)
+}
+
+// gets notifications in appendstore (id) order, used for export
+pub fn get_notifications_range<S: ReadonlyStorage>(
+    storage: &S,
+    owner: &CanonicalAddr,
+    start: u32,
+    count: u32,
+) -> StdResult<Vec<StoredNotification>> {
+    let storage =
+        ReadonlyPrefixedStorage::multilevel(&[PREFIX_NOTIFICATIONS, owner.as_slice()], storage);
+    let storage = if let Some(result) = AppendStore::<StoredNotification, _>::attach(&storage) {
+        result?
+    } else {
+        return Ok(vec![]);
+    };
+    storage.iter().skip(start as _).take(count as _).collect()
+}
+
+pub fn get_number_of_notifications<S: ReadonlyStorage>(storage: &S, owner: &CanonicalAddr) -> u32 {
+    let storage =
+        ReadonlyPrefixedStorage::multilevel(&[PREFIX_NOTIFICATIONS, owner.as_slice()], storage);
+    if let Some(result) = AppendStore::<StoredNotification, _>::attach(&storage) {
+        result.map(|storage| storage.len()).unwrap_or(0_u32)
+    } else {
+        0_u32
+    }
+}
+
+// marks every notification the owner has so far as read
+pub fn mark_notifications_read<S: Storage>(
+    storage: &mut S,
+    owner: &CanonicalAddr,
+) -> StdResult<()> {
+    let read = get_number_of_notifications(storage, owner);
+    set_notifications_read(storage, owner, read)
+}
+
+pub fn set_notifications_read<S: Storage>(
+    storage: &mut S,
+    owner: &CanonicalAddr,
+    read: u32,
+) -> StdResult<()> {
+    let mut storage = PrefixedStorage::new(PREFIX_NOTIFICATIONS_READ, storage);
+    set_bin_data(&mut storage, owner.as_slice(), &read)
+}
+
+pub fn get_notifications_read<S: ReadonlyStorage>(storage: &S, owner: &CanonicalAddr) -> u32 {
+    let storage = ReadonlyPrefixedStorage::new(PREFIX_NOTIFICATIONS_READ, storage);
+    get_bin_data(&storage, owner.as_slice()).unwrap_or_else(|_| 0_u32)
+}
+
+//
+// Notification opt-outs
+//   types of notification the account does not want to receive
+//
+
+pub fn set_notification_opt_outs<S: Storage>(
+    storage: &mut S,
+    owner: &CanonicalAddr,
+    opt_outs: &[NotificationType],
+) -> StdResult<()> {
+    let mut storage = PrefixedStorage::new(PREFIX_NOTIFICATION_OPT_OUTS, storage);
+    set_bin_data(&mut storage, owner.as_slice(), &opt_outs.to_vec())
+}
+
+pub fn get_notification_opt_outs<S: ReadonlyStorage>(
+    storage: &S,
+    owner: &CanonicalAddr,
+) -> Vec<NotificationType> {
+    let storage = ReadonlyPrefixedStorage::new(PREFIX_NOTIFICATION_OPT_OUTS, storage);
+    get_bin_data(&storage, owner.as_slice()).unwrap_or_else(|_| vec![])
+}
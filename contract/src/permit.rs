@@ -0,0 +1,266 @@
+use crate::contract::DENOM;
+use crate::user_state::is_permit_revoked;
+use cosmwasm_std::{
+    to_binary, Binary, CanonicalAddr, HumanAddr, ReadonlyStorage, StdError, StdResult, Uint128,
+};
+use k256::ecdsa::signature::{Signature as _, Verifier};
+use k256::ecdsa::{Signature, VerifyingKey};
+use ripemd160::{Digest, Ripemd160};
+use schemars::JsonSchema;
+use secret_toolkit::crypto::sha_256;
+use serde::{Deserialize, Serialize};
+
+//
+// SNIP-24 query permits
+//   a permit is an offline signed document that lets a wallet authenticate queries without
+//   first sending a SetViewingKey transaction
+//
+
+pub const PERMIT_PUB_KEY_TYPE: &str = "tendermint/PubKeySecp256k1";
+
+// groups of authenticated queries a permit or named viewing key can grant access to
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Permission {
+    // all authenticated queries
+    Owner,
+    // profile, following, fardels, comments, and ratings
+    Social,
+    // unpacked fardels
+    Unpacked,
+    // pending unpack approvals
+    Pending,
+    // sale and purchase transactions
+    History,
+    // direct messages
+    Messages,
+    // account event notifications
+    Notifications,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct Permit {
+    pub params: PermitParams,
+    pub signature: PermitSignature,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct PermitParams {
+    pub allowed_tokens: Vec<HumanAddr>,
+    pub permit_name: String,
+    pub chain_id: String,
+    pub permissions: Vec<Permission>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct PermitSignature {
+    pub pub_key: PubKey,
+    pub signature: Binary,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct PubKey {
+    pub r#type: String,
+    pub value: Binary,
+}
+
+impl Permit {
+    pub fn check_permission(&self, permission: &Permission) -> bool {
+        has_permission(&self.params.permissions, permission)
+    }
+}
+
+// true if the granted permissions (of a permit or named viewing key) include the permission
+pub fn has_permission(granted: &[Permission], permission: &Permission) -> bool {
+    granted.contains(&Permission::Owner) || granted.contains(permission)
+}
+
+// The document the wallet actually signs. Fields are declared in alphabetical order so the
+//   serialized json matches the sorted amino json produced by the wallet.
+#[derive(Serialize)]
+struct SignedPermit {
+    account_number: Uint128,
+    chain_id: String,
+    fee: SignedFee,
+    memo: String,
+    msgs: Vec<SignedPermitMsg>,
+    sequence: Uint128,
+}
+
+#[derive(Serialize)]
+struct SignedFee {
+    amount: Vec<SignedCoin>,
+    gas: Uint128,
+}
+
+#[derive(Serialize)]
+struct SignedCoin {
+    amount: Uint128,
+    denom: String,
+}
+
+#[derive(Serialize)]
+struct SignedPermitMsg {
+    r#type: String,
+    value: SignedPermitContent,
+}
+
+#[derive(Serialize)]
+struct SignedPermitContent {
+    allowed_tokens: Vec<HumanAddr>,
+    permissions: Vec<Permission>,
+    permit_name: String,
+}
+
+impl SignedPermit {
+    fn from_params(params: &PermitParams) -> Self {
+        Self {
+            account_number: Uint128(0),
+            chain_id: params.chain_id.clone(),
+            fee: SignedFee {
+                amount: vec![SignedCoin {
+                    amount: Uint128(0),
+                    denom: DENOM.to_string(),
+                }],
+                gas: Uint128(1),
+            },
+            memo: String::new(),
+            msgs: vec![SignedPermitMsg {
+                r#type: String::from("query_permit"),
+                value: SignedPermitContent {
+                    allowed_tokens: params.allowed_tokens.clone(),
+                    permissions: params.permissions.clone(),
+                    permit_name: params.permit_name.clone(),
+                },
+            }],
+            sequence: Uint128(0),
+        }
+    }
+}
+
+// verifies the permit signature and returns the canonical address of the signer
+pub fn validate_permit<S: ReadonlyStorage>(
+    storage: &S,
+    permit: &Permit,
+    contract_address: &HumanAddr,
+) -> StdResult<CanonicalAddr> {
+    if !permit.params.allowed_tokens.contains(contract_address) {
+        return Err(StdError::generic_err(
+            "Permit is not valid for this contract.",
+        ));
+    }
+
+    let pub_key = &permit.signature.pub_key;
+    if pub_key.r#type != PERMIT_PUB_KEY_TYPE {
+        return Err(StdError::generic_err("Unsupported permit public key type."));
+    }
+    let verifying_key = VerifyingKey::from_sec1_bytes(pub_key.value.as_slice())
+        .map_err(|_| StdError::generic_err("Invalid permit public key."))?;
+    let signature = Signature::from_bytes(permit.signature.signature.as_slice())
+        .map_err(|_| StdError::generic_err("Invalid permit signature."))?;
+
+    let signed_bytes = to_binary(&SignedPermit::from_params(&permit.params))?;
+    verifying_key
+        .verify(signed_bytes.as_slice(), &signature)
+        .map_err(|_| StdError::generic_err("Failed to verify permit signature."))?;
+
+    // cosmos addresses are ripemd160(sha256(compressed public key))
+    let signer = CanonicalAddr(Binary(
+        Ripemd160::digest(&sha_256(pub_key.value.as_slice())).to_vec(),
+    ));
+
+    if is_permit_revoked(storage, &signer, &permit.params.permit_name) {
+        return Err(StdError::generic_err(format!(
+            "Permit {} has been revoked.",
+            permit.params.permit_name
+        )));
+    }
+
+    Ok(signer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::user_state::revoke_permit;
+    use cosmwasm_std::testing::MockStorage;
+
+    const CONTRACT: &str = "secret1pzg3g5rksm53lxufyfej38c5usqj5ufzgyhy7l";
+    // signer of the permit below, secret15l2nfh30p395v5cy0kdma2rdnxzs9ntrxvufj3
+    const SIGNER: &str = "a7d534de2f0c4b4653047d9bbea86d998502cd63";
+    // the amino sign doc a wallet signs for the permit below (Keplr's signAmino sorts the keys
+    //   and drops whitespace), signed with a fixed secp256k1 key
+    const SIGN_DOC: &str = r#"{"account_number":"0","chain_id":"secret-4","fee":{"amount":[{"amount":"0","denom":"uscrt"}],"gas":"1"},"memo":"","msgs":[{"type":"query_permit","value":{"allowed_tokens":["secret1pzg3g5rksm53lxufyfej38c5usqj5ufzgyhy7l"],"permissions":["social"],"permit_name":"fardels"}}],"sequence":"0"}"#;
+
+    fn signed_permit() -> Permit {
+        Permit {
+            params: PermitParams {
+                allowed_tokens: vec![HumanAddr(CONTRACT.to_string())],
+                permit_name: String::from("fardels"),
+                chain_id: String::from("secret-4"),
+                permissions: vec![Permission::Social],
+            },
+            signature: PermitSignature {
+                pub_key: PubKey {
+                    r#type: PERMIT_PUB_KEY_TYPE.to_string(),
+                    value: Binary::from_base64("AzxVRih0pr9a+Jl/9ARYHSBTiOlBuX1q4TkvhdMUipZ2")
+                        .unwrap(),
+                },
+                signature: Binary::from_base64(
+                    "OQWAJkryNGEhTPtSHohEU9eLvvIYQX0wwaJQC2v3NcEkZ8o/H2piX5TssNZaObeM8uwdNDdGT9aRj1g+gdnBBw==",
+                )
+                .unwrap(),
+            },
+        }
+    }
+
+    fn error_msg(result: StdResult<CanonicalAddr>) -> String {
+        match result {
+            Err(StdError::GenericErr { msg, .. }) => msg,
+            _ => panic!("Expected the permit to be rejected"),
+        }
+    }
+
+    #[test]
+    fn signed_permit_matches_the_wallet_sign_doc() {
+        let params = signed_permit().params;
+        let signed_bytes = to_binary(&SignedPermit::from_params(&params)).unwrap();
+        assert_eq!(signed_bytes.as_slice(), SIGN_DOC.as_bytes());
+    }
+
+    #[test]
+    fn validate_permit_returns_the_signer() {
+        let storage = MockStorage::new();
+        let contract = HumanAddr(CONTRACT.to_string());
+        let signer = validate_permit(&storage, &signed_permit(), &contract).unwrap();
+        assert_eq!(signer, CanonicalAddr(Binary(hex::decode(SIGNER).unwrap())));
+    }
+
+    #[test]
+    fn validate_permit_rejects_other_contracts() {
+        let storage = MockStorage::new();
+        let other = HumanAddr("secret1other".to_string());
+        let msg = error_msg(validate_permit(&storage, &signed_permit(), &other));
+        assert_eq!(msg, "Permit is not valid for this contract.");
+    }
+
+    #[test]
+    fn validate_permit_rejects_revoked_names() {
+        let mut storage = MockStorage::new();
+        let signer = CanonicalAddr(Binary(hex::decode(SIGNER).unwrap()));
+        revoke_permit(&mut storage, &signer, "fardels").unwrap();
+        let contract = HumanAddr(CONTRACT.to_string());
+        let msg = error_msg(validate_permit(&storage, &signed_permit(), &contract));
+        assert_eq!(msg, "Permit fardels has been revoked.");
+    }
+
+    #[test]
+    fn validate_permit_rejects_tampered_permissions() {
+        let storage = MockStorage::new();
+        let mut permit = signed_permit();
+        permit.params.permissions = vec![Permission::Owner];
+        let contract = HumanAddr(CONTRACT.to_string());
+        let msg = error_msg(validate_permit(&storage, &permit, &contract));
+        assert_eq!(msg, "Failed to verify permit signature.");
+    }
+}
@@ -1,30 +1,61 @@
+use crate::contract::MAX_PROFILE_PREVIOUS_HANDLES;
 use crate::fardel_state::{
-    get_fardel_by_global_id, get_fardel_by_hash, get_fardel_img, get_fardel_owner, get_fardels,
-    get_global_id_by_hash, get_number_of_fardels, get_sealed_status, get_total_fardel_count,
-    is_fardel_hidden, Fardel, get_fardel_unpack_count, is_fardel_removed,
+    audience_allows, get_audience, get_fardel_by_global_id, get_fardel_by_hash, get_fardel_img,
+    get_fardel_key, get_fardel_owner, get_fardel_parts, get_fardel_unpack_count, get_fardels,
+    get_global_id_by_hash, get_last_revised, get_number_of_fardels, get_number_of_parts,
+    get_number_of_restocks, get_number_of_revisions, get_part_of, get_publish_time, get_restocks,
+    get_restocks_range, get_revisions, get_revisions_range, get_total_fardel_count,
+    is_fardel_hidden, is_fardel_removed, is_sealed, Fardel, StoredAudience,
+};
+use crate::message_state::{
+    get_message_box_len, get_message_fee, get_messages, get_messages_range, get_number_of_messages,
 };
 use crate::msg::{
-    CommentResponse, FardelBatchResponse, FardelResponse, PendingApprovalResponse, QueryAnswer,
-    RegisteredAccountsResponse, ResponseStatus, ResponseStatus::Failure, ResponseStatus::Success,
+    AccountExport, AccountListEntry, AudienceExport, BanExport, BanResponse, CommentExport,
+    CommentResponse, DelegateResponse, EncryptedContents, ExportList, FardelBatchResponse,
+    FardelExport, FardelPartResponse, FardelResponse, FollowRequestResponse,
+    HandleCollisionResponse, MessageResponse, MutesExport, NamedViewingKeyExport,
+    NotificationResponse, PendingApprovalResponse, QueryAnswer, RegisteredAccountsResponse,
+    ResponseStatus, ResponseStatus::Failure, ResponseStatus::Success, RestockExport,
+    RestockResponse, RevisionExport, RevisionResponse, SettingsExport, ViewingKeyResponse,
+};
+use crate::notification_state::{
+    get_notification_opt_outs, get_notifications, get_notifications_range, get_notifications_read,
+    get_number_of_notifications,
 };
 use crate::social_state::{
-    get_comments, get_downvotes, get_follower_count, get_followers, get_following,
-    get_number_of_comments, get_number_of_followers, get_number_of_following, get_rating,
-    get_upvotes, is_following,
+    get_blocked_list, get_blocked_list_len, get_comments, get_comments_range, get_downvotes,
+    get_follow_request_range, get_follow_requests, get_follower_count, get_followers,
+    get_following, get_following_range, get_mutes, get_mutuals, get_number_of_comments,
+    get_number_of_follow_requests, get_number_of_followers, get_number_of_following, get_rating,
+    get_upvotes, is_blocked_by, is_following, StoredMutes,
+};
+use crate::state::{
+    get_block_time, get_export_key, get_pause_flags, get_pending_admin, get_schema_version,
+    is_emergency, is_frozen, ReadonlyConfig,
+};
+use crate::tx_state::{
+    get_number_of_purchases, get_number_of_sales, get_purchase_txs, get_sale_txs,
+    get_stored_purchase_txs, get_stored_sale_txs, PurchaseTx, SaleTx,
 };
-use crate::state::ReadonlyConfig;
-use crate::tx_state::{get_purchase_txs, get_sale_txs, get_number_of_sales, get_number_of_purchases, PurchaseTx, SaleTx};
 use crate::unpack_state::{
-    get_number_of_unpacked_by_unpacker, get_pending_approvals_from_start,
-    get_pending_unpacked_status_by_fardel_id, get_unpacked_by_unpacker,
-    get_unpacked_status_by_fardel_id, UnpackedFardel,
+    get_encrypted_unpack, get_number_of_pending_from_start, get_number_of_unpacked_by_unpacker,
+    get_pending_approvals_from_start, get_pending_unpacked_status_by_fardel_id,
+    get_unpacked_by_unpacker, get_unpacked_range, get_unpacked_status_by_fardel_id, UnpackedFardel,
 };
 use crate::user_state::{
-    get_account, get_account_for_handle, get_account_img, get_registered_addresses, is_banned,
-    is_deactivated, Account, get_total_number_registered_accounts, get_registered_address,
+    get_account, get_account_ban, get_account_for_handle, get_account_img, get_ban_list,
+    get_ban_list_len, get_delegate_list, get_delegation, get_handle_collisions,
+    get_handle_collisions_len, get_handle_history, get_handle_history_len,
+    get_handle_history_range, get_named_viewing_keys, get_registered_address,
+    get_registered_addresses, get_reserved_handles, get_total_number_registered_accounts,
+    is_banned, is_deactivated, is_private_account, is_reserved_handle, read_viewing_key, Account,
 };
+use crate::utils::{encrypt_export_data, export_entry_label};
+use crate::validation::valid_handle_chars;
 use cosmwasm_std::{
-    to_binary, Api, Extern, HumanAddr, Querier, QueryResult, StdError, Storage, Uint128,
+    to_binary, Api, Binary, CanonicalAddr, Extern, HumanAddr, Querier, QueryResult,
+    ReadonlyStorage, StdError, StdResult, Storage, Uint128,
 };
 
 pub fn query_get_profile<S: Storage, A: Api, Q: Querier>(
@@ -42,13 +73,19 @@ pub fn query_get_profile<S: Storage, A: Api, Q: Querier>(
     let img = get_account_img(&deps.storage, &address).unwrap_or_else(|_| vec![]);
     let img_str = String::from_utf8(img).unwrap();
     let follower_count = get_follower_count(&deps.storage, &address) as i32;
+    let previous_handles: Vec<String> =
+        get_handle_history(&deps.storage, &address, MAX_PROFILE_PREVIOUS_HANDLES)?
+            .into_iter()
+            .map(|released| String::from_utf8(released.handle).ok().unwrap_or_default())
+            .collect();
     let answer = QueryAnswer::GetProfile {
         status,
-        handle: Some(handle),
+        handle: Some(account.handle),
         description: Some(account.description),
         view_settings: Some(account.view_settings),
         img: Some(img_str),
         follower_count,
+        previous_handles,
     };
     to_binary(&answer)
 }
@@ -101,10 +138,12 @@ pub fn query_is_handle_available<S: Storage, A: Api, Q: Querier>(
     deps: &Extern<S, A, Q>,
     handle: String,
 ) -> QueryResult {
-    let response = match get_account_for_handle(&deps.storage, &handle) {
-        Ok(_) => true,
-        Err(_) => false,
-    };
+    let constants = ReadonlyConfig::from_storage(&deps.storage).constants()?;
+    let handle = handle.trim().to_owned();
+    let response = handle.as_bytes().len() <= constants.max_handle_len.into()
+        && valid_handle_chars(&handle)
+        && !is_reserved_handle(&deps.storage, &handle)
+        && get_account_for_handle(&deps.storage, &handle).is_err();
     let answer = QueryAnswer::IsHandleAvailable { response };
     to_binary(&answer)
 }
@@ -127,7 +166,8 @@ pub fn query_get_fardel_by_id<S: Storage, A: Api, Q: Querier>(
     let global_id = fardel.global_id.u128();
 
     let removed = is_fardel_removed(&deps.storage, global_id);
-    if removed {
+    // extra parts are only accessible through the fardel they belong to
+    if removed || get_part_of(&deps.storage, global_id).is_some() {
         return Err(StdError::generic_err("Fardel not found."));
     }
 
@@ -137,6 +177,7 @@ pub fn query_get_fardel_by_id<S: Storage, A: Api, Q: Querier>(
 
     // unpacked parts
     let mut contents_data: Option<String> = None;
+    let mut encrypted_contents: Option<EncryptedContents> = None;
     let mut unpacked = false;
     let mut pending_unpack = false;
 
@@ -146,6 +187,28 @@ pub fn query_get_fardel_by_id<S: Storage, A: Api, Q: Querier>(
     let hidden = is_fardel_hidden(&deps.storage, global_id);
     let mut rating: Option<bool> = None;
 
+    // scheduled fardels are only visible to their carrier until they are published
+    let viewer = match address {
+        Some(address) => Some(deps.api.canonical_address(address)?),
+        None => None,
+    };
+    let publish_time = get_scheduled_publish_time(&deps.storage, global_id);
+    if publish_time.is_some() && viewer.as_ref() != Some(&owner) {
+        return Err(StdError::generic_err("Fardel not found."));
+    }
+    // fardels of private accounts the viewer does not follow, outside the viewer's audience,
+    //   or whose carrier blocked the viewer, are only visible to those who already unpacked
+    let followers_only = is_private_account(&deps.storage, &owner)
+        && match viewer.as_ref() {
+            Some(viewer) => viewer != &owner && !is_following(&deps.storage, viewer, &owner),
+            None => true,
+        };
+    let restricted = followers_only
+        || !audience_allows(&deps.storage, global_id, &owner, viewer.as_ref())
+        || viewer
+            .as_ref()
+            .map_or(false, |viewer| is_blocked_by(&deps.storage, &owner, viewer));
+
     if address.is_some() {
         let unpacker_address = address.clone().unwrap();
         let unpacker = &deps.api.canonical_address(&unpacker_address)?;
@@ -153,10 +216,11 @@ pub fn query_get_fardel_by_id<S: Storage, A: Api, Q: Querier>(
         
         if unpacked_status.unpacked {
             contents_data = Some(fardel.contents_data);
+            encrypted_contents = get_encrypted_unpack(&deps.storage, unpacker, global_id);
             unpacked = true;
         } else if get_pending_unpacked_status_by_fardel_id(&deps.storage, unpacker, global_id).value {
             pending_unpack = true;
-        } else if banned || deactivated || hidden {
+        } else if banned || deactivated || hidden || restricted {
             return Err(StdError::generic_err("Fardel not found."));
         }
 
@@ -164,7 +228,7 @@ pub fn query_get_fardel_by_id<S: Storage, A: Api, Q: Querier>(
             Ok(r) => rating = Some(r),
             Err(_) => {}
         };
-    } else if banned || deactivated || hidden {
+    } else if banned || deactivated || hidden || restricted {
         return Err(StdError::generic_err("Fardel not found."));
     }
 
@@ -173,8 +237,11 @@ pub fn query_get_fardel_by_id<S: Storage, A: Api, Q: Querier>(
     if fardel.seal_time > 0 {
         seal_time = Some(fardel.seal_time as i32);
     }
-    let sealed = get_sealed_status(&deps.storage, global_id);
+    let time = get_block_time(&deps.storage);
+    let sealed = is_sealed(&deps.storage, global_id, fardel.seal_time, time);
     let img = get_fardel_img(&deps.storage, global_id);
+    let revised = get_last_revised(&deps.storage, global_id).map(|t| t as i32);
+    let parts = get_parts_response(deps, viewer.as_ref(), global_id);
     let mut remaining: Option<i32> = None;
     if fardel.countable > 0 {
         let unpack_count = get_fardel_unpack_count(&deps.storage, global_id).unwrap_or_else(|_| 0_u64) as u16;
@@ -202,6 +269,10 @@ pub fn query_get_fardel_by_id<S: Storage, A: Api, Q: Querier>(
         contents_data,
         rating,
         img,
+        revised,
+        parts,
+        publish_time,
+        encrypted_contents,
     };
     let answer = QueryAnswer::GetFardelById {
         fardel: fardel_response,
@@ -209,6 +280,54 @@ pub fn query_get_fardel_by_id<S: Storage, A: Api, Q: Querier>(
     to_binary(&answer)
 }
 
+// gets the publish time of a fardel that is scheduled but not yet published
+fn get_scheduled_publish_time<S: ReadonlyStorage>(storage: &S, fardel_id: u128) -> Option<i32> {
+    let publish_time = get_publish_time(storage, fardel_id);
+    if publish_time > get_block_time(storage) {
+        return Some(publish_time as i32);
+    }
+    None
+}
+
+// gets the extra parts of a multi-part fardel, with the viewer's unpack status for each
+fn get_parts_response<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    viewer: Option<&CanonicalAddr>,
+    fardel_id: u128,
+) -> Vec<FardelPartResponse> {
+    get_fardel_parts(&deps.storage, fardel_id)
+        .into_iter()
+        .enumerate()
+        .filter_map(|(idx, part_id)| {
+            let part = get_fardel_by_global_id(&deps.storage, part_id).ok()??;
+            let mut unpacked = false;
+            let mut pending_unpack = false;
+            let mut contents_data: Option<String> = None;
+            let mut encrypted_contents: Option<EncryptedContents> = None;
+            if let Some(viewer) = viewer {
+                if get_unpacked_status_by_fardel_id(&deps.storage, viewer, part_id).unpacked {
+                    unpacked = true;
+                    contents_data = Some(part.contents_data);
+                    encrypted_contents = get_encrypted_unpack(&deps.storage, viewer, part_id);
+                } else {
+                    pending_unpack =
+                        get_pending_unpacked_status_by_fardel_id(&deps.storage, viewer, part_id)
+                            .value;
+                }
+            }
+            Some(FardelPartResponse {
+                part: idx as i32 + 1,
+                cost: part.cost.amount,
+                approval_req: part.approval_req,
+                unpacked,
+                pending_unpack,
+                contents_data,
+                encrypted_contents,
+            })
+        })
+        .collect()
+}
+
 pub fn query_get_fardels<S: Storage, A: Api, Q: Querier>(
     deps: &Extern<S, A, Q>,
     address: &Option<HumanAddr>,
@@ -225,6 +344,26 @@ pub fn query_get_fardels<S: Storage, A: Api, Q: Querier>(
     let fardels: Vec<Fardel> =
         get_fardels(&deps.storage, &account, page, page_size).unwrap_or_else(|_| vec![]);
 
+    let viewer = match address {
+        Some(address) => Some(deps.api.canonical_address(address)?),
+        None => None,
+    };
+    // private accounts only list their fardels for the account itself and accepted followers
+    let followers_only = is_private_account(&deps.storage, &account)
+        && match viewer.as_ref() {
+            Some(viewer) => viewer != &account && !is_following(&deps.storage, viewer, &account),
+            None => true,
+        };
+    // and no one lists fardels for accounts they are blocked by
+    let blocked = viewer.as_ref().map_or(false, |viewer| {
+        is_blocked_by(&deps.storage, &account, viewer)
+    });
+    let mutes = viewer
+        .as_ref()
+        .map(|viewer| get_mutes(&deps.storage, viewer))
+        .unwrap_or_default();
+
+    let time = get_block_time(&deps.storage);
     let mut fardels_response: Vec<FardelResponse> = vec![];
     if fardels.len() > 0 {
         fardels_response = fardels
@@ -235,8 +374,13 @@ pub fn query_get_fardels<S: Storage, A: Api, Q: Querier>(
                 let mut pending_unpack = false;
 
                 let removed = is_fardel_removed(&deps.storage, global_id);
+                // scheduled fardels are only listed for their carrier
+                let scheduled = get_scheduled_publish_time(&deps.storage, global_id).is_some()
+                    && viewer.as_ref() != Some(&account);
 
                 let hidden = is_fardel_hidden(&deps.storage, global_id);
+                let restricted = blocked
+                    || !audience_allows(&deps.storage, global_id, &account, viewer.as_ref());
                 if address.is_some() {
                     let unpacker_address = address.clone().unwrap();
                     let unpacker = deps.api.canonical_address(&unpacker_address).unwrap();
@@ -248,7 +392,14 @@ pub fn query_get_fardels<S: Storage, A: Api, Q: Querier>(
                         pending_unpack = true;
                     }
                 }
-                !removed && (!(banned || deactivated || hidden) || unpacked || pending_unpack)
+                // muted carriers and tags are left out even if unpacked
+                let muted = mutes.hides(&account, &fardel.tags);
+                !removed
+                    && !scheduled
+                    && !muted
+                    && (!(banned || deactivated || hidden || followers_only || restricted)
+                        || unpacked
+                        || pending_unpack)
             })
             .map(|fardel| {
                 let global_id = fardel.global_id.u128();
@@ -259,6 +410,7 @@ pub fn query_get_fardels<S: Storage, A: Api, Q: Querier>(
 
                 // unpacked parts
                 let mut contents_data: Option<String> = None;
+                let mut encrypted_contents: Option<EncryptedContents> = None;
                 let mut unpacked = false;
                 let mut pending_unpack = false;
                 let mut rating: Option<bool> = None;
@@ -270,6 +422,8 @@ pub fn query_get_fardels<S: Storage, A: Api, Q: Querier>(
                         .unpacked
                     {
                         contents_data = Some(fardel.contents_data.clone());
+                        encrypted_contents =
+                            get_encrypted_unpack(&deps.storage, &unpacker, global_id);
                         unpacked = true;
                     } else if get_pending_unpacked_status_by_fardel_id(&deps.storage, &unpacker, global_id).value {
                         pending_unpack = true;
@@ -286,8 +440,11 @@ pub fn query_get_fardels<S: Storage, A: Api, Q: Querier>(
                 if fardel.seal_time > 0 {
                     seal_time = Some(fardel.seal_time as i32);
                 }
-                let sealed = get_sealed_status(&deps.storage, global_id);
+                let sealed = is_sealed(&deps.storage, global_id, fardel.seal_time, time);
                 let img = get_fardel_img(&deps.storage, global_id);
+                let revised = get_last_revised(&deps.storage, global_id).map(|t| t as i32);
+                let parts = get_parts_response(deps, viewer.as_ref(), global_id);
+                let publish_time = get_scheduled_publish_time(&deps.storage, global_id);
 
                 let mut remaining: Option<i32> = None;
                 if fardel.countable > 0 {
@@ -316,11 +473,17 @@ pub fn query_get_fardels<S: Storage, A: Api, Q: Querier>(
                     contents_data,
                     rating,
                     img,
+                    revised,
+                    parts,
+                    publish_time,
+                    encrypted_contents,
                 }
             })
             .collect();
     }
-    let total_count = get_number_of_fardels(&deps.storage, &account) as i32;
+    // extra parts are listed with the fardel they belong to
+    let total_count = (get_number_of_fardels(&deps.storage, &account)
+        - get_number_of_parts(&deps.storage, &account)) as i32;
     let answer = QueryAnswer::GetFardels {
         fardels: fardels_response,
         total_count,
@@ -346,7 +509,8 @@ pub fn query_get_comments<S: Storage, A: Api, Q: Querier>(
     let global_id = fardel.global_id.u128();
 
     let removed = is_fardel_removed(&deps.storage, global_id);
-    if removed {
+    // extra parts are only accessible through the fardel they belong to
+    if removed || get_part_of(&deps.storage, global_id).is_some() {
         return Err(StdError::generic_err("Fardel not found."));
     }
 
@@ -368,9 +532,17 @@ pub fn query_get_comments<S: Storage, A: Api, Q: Querier>(
     let page = page.unwrap_or_else(|| 0_i32) as u32;
     let page_size = page_size.unwrap_or_else(|| 10_i32) as u32;
 
-    // get last page_size comments
+    let mutes = match address {
+        Some(address) => get_mutes(&deps.storage, &deps.api.canonical_address(address)?),
+        None => StoredMutes::default(),
+    };
+
+    // get last page_size comments, leaving out muted commenters, or all of them if the
+    //   fardel's carrier or one of its tags is muted
+    let muted = mutes.hides(&owner, &fardel.tags);
     let comments: Vec<CommentResponse> = get_comments(&deps.storage, global_id, page, page_size)?
         .iter()
+        .filter(|c| !muted && !mutes.hides(&c.commenter, &[]))
         .map(|c| {
             let commenter_account = get_account(&deps.storage, &c.commenter).unwrap();
             let mut response_fardel_id: Option<Uint128> = None;
@@ -396,6 +568,79 @@ pub fn query_get_comments<S: Storage, A: Api, Q: Querier>(
     to_binary(&answer)
 }
 
+pub fn query_get_restocks<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    fardel_id: Uint128,
+    page: Option<i32>,
+    page_size: Option<i32>,
+) -> QueryResult {
+    let global_id = get_global_id_by_hash(&deps.storage, fardel_id.u128())?;
+    if is_fardel_removed(&deps.storage, global_id)
+        || get_part_of(&deps.storage, global_id).is_some()
+    {
+        return Err(StdError::generic_err("Fardel not found."));
+    }
+
+    let page = page.unwrap_or_else(|| 0_i32) as u32;
+    let page_size = page_size.unwrap_or_else(|| 10_i32) as u32;
+    let restocks: Vec<RestockResponse> = get_restocks(&deps.storage, global_id, page, page_size)?
+        .into_iter()
+        .map(|restock| {
+            let mut countable: Option<i32> = None;
+            if restock.countable > 0 {
+                countable = Some(restock.countable as i32);
+            }
+            let mut seal_time: Option<i32> = None;
+            if restock.seal_time > 0 {
+                seal_time = Some(restock.seal_time as i32);
+            }
+            RestockResponse {
+                added: restock.added as i32,
+                countable,
+                seal_time,
+                timestamp: restock.timestamp as i32,
+            }
+        })
+        .collect();
+
+    let answer = QueryAnswer::GetRestocks {
+        restocks,
+        total_count: get_number_of_restocks(&deps.storage, global_id) as i32,
+    };
+    to_binary(&answer)
+}
+
+// get the pending admin proposal, deadline is a block time after which
+//   the proposal can no longer be accepted
+pub fn query_get_pending_admin<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+) -> QueryResult {
+    let mut address: Option<HumanAddr> = None;
+    let mut deadline: Option<i32> = None;
+    if let Ok(pending_admin) = get_pending_admin(&deps.storage) {
+        address = Some(deps.api.human_address(&pending_admin.address)?);
+        deadline = Some(pending_admin.deadline as i32);
+    }
+    let answer = QueryAnswer::GetPendingAdmin { address, deadline };
+    to_binary(&answer)
+}
+
+pub fn query_get_contract_status<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+) -> QueryResult {
+    let flags = get_pause_flags(&deps.storage);
+    let answer = QueryAnswer::GetContractStatus {
+        frozen: is_frozen(&deps.storage),
+        emergency: is_emergency(&deps.storage),
+        carry_paused: flags.carry,
+        unpack_paused: flags.unpack,
+        social_paused: flags.social,
+        schema_version: get_schema_version(&deps.storage) as i32,
+        message_fee: Uint128(get_message_fee(&deps.storage)),
+    };
+    to_binary(&answer)
+}
+
 // Authenticated queries
 
 pub fn query_get_sale_transactions<S: Storage, A: Api, Q: Querier>(
@@ -482,10 +727,60 @@ pub fn query_get_handle<S: Storage, A: Api, Q: Querier>(
         status,
         handle,
         private_settings,
+        notification_opt_outs: get_notification_opt_outs(&deps.storage, &address),
     };
     to_binary(&answer)
 }
 
+pub fn query_get_viewing_keys<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    account: &HumanAddr,
+) -> QueryResult {
+    let address = deps.api.canonical_address(account)?;
+
+    let keys: Vec<ViewingKeyResponse> = get_named_viewing_keys(&deps.storage, &address)?
+        .into_iter()
+        .filter(|named_key| !named_key.revoked)
+        .map(|named_key| ViewingKeyResponse {
+            name: String::from_utf8(named_key.name).ok().unwrap_or_default(),
+            scopes: named_key.scopes,
+            timestamp: named_key.timestamp as i32,
+        })
+        .collect();
+
+    let answer = QueryAnswer::GetViewingKeys { keys };
+    to_binary(&answer)
+}
+
+pub fn query_get_delegates<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    account: &HumanAddr,
+) -> QueryResult {
+    let address = deps.api.canonical_address(account)?;
+    let time = get_block_time(&deps.storage);
+
+    let mut delegates: Vec<DelegateResponse> = vec![];
+    for delegate in get_delegate_list(&deps.storage, &address)? {
+        if let Some(delegation) = get_delegation(&deps.storage, &address, &delegate) {
+            // ignore revoked or expired delegations
+            if delegation.is_active(time) {
+                let mut expiry: Option<i32> = None;
+                if delegation.expiry > 0 {
+                    expiry = Some(delegation.expiry as i32);
+                }
+                delegates.push(DelegateResponse {
+                    delegate: deps.api.human_address(&delegate)?,
+                    permissions: delegation.permissions,
+                    expiry,
+                });
+            }
+        }
+    }
+
+    let answer = QueryAnswer::GetDelegates { delegates };
+    to_binary(&answer)
+}
+
 pub fn query_get_following<S: Storage, A: Api, Q: Querier>(
     deps: &Extern<S, A, Q>,
     account: &HumanAddr,
@@ -534,6 +829,214 @@ pub fn query_is_following<S: Storage, A: Api, Q: Querier>(
     to_binary(&response)
 }
 
+pub fn query_follows_me<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    account: &HumanAddr,
+    handle: String,
+) -> QueryResult {
+    let address = deps.api.canonical_address(account)?;
+
+    if is_banned(&deps.storage, &address) {
+        return Err(StdError::generic_err("Account has been banned."));
+    } else if is_deactivated(&deps.storage, &address) {
+        return Err(StdError::generic_err("Account has been deactivated."));
+    }
+
+    let follower_addr = get_account_for_handle(&deps.storage, &handle)?;
+    let response = QueryAnswer::FollowsMe {
+        response: is_following(&deps.storage, &follower_addr, &address),
+    };
+    to_binary(&response)
+}
+
+pub fn query_get_mutuals<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    account: &HumanAddr,
+    page: Option<i32>,
+    page_size: Option<i32>,
+) -> QueryResult {
+    let address = deps.api.canonical_address(account)?;
+
+    if is_banned(&deps.storage, &address) {
+        return Err(StdError::generic_err("Account has been banned."));
+    } else if is_deactivated(&deps.storage, &address) {
+        return Err(StdError::generic_err("Account has been deactivated."));
+    }
+
+    let page = page.unwrap_or_else(|| 0_i32) as u32;
+    let page_size = page_size.unwrap_or_else(|| 10_i32) as u32;
+
+    // mutuals are found by checking each followed account, so stop one past the page
+    let mutuals = get_mutuals(&deps.storage, &address, (page + 1) * page_size + 1)?;
+    let total_count = mutuals.len() as i32;
+    let mutuals: StdResult<Vec<String>> = mutuals
+        .iter()
+        .skip((page * page_size) as _)
+        .take(page_size as _)
+        .map(|mutual| {
+            Ok(get_account(&deps.storage, mutual)?
+                .into_humanized(&deps.api)?
+                .handle)
+        })
+        .collect();
+    let response = QueryAnswer::GetMutuals {
+        mutuals: mutuals?,
+        total_count,
+    };
+    to_binary(&response)
+}
+
+pub fn query_get_blocked<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    account: &HumanAddr,
+    page: Option<i32>,
+    page_size: Option<i32>,
+) -> QueryResult {
+    let address = deps.api.canonical_address(account)?;
+
+    let page = page.unwrap_or_else(|| 0_i32) as u32;
+    let page_size = page_size.unwrap_or_else(|| 10_i32) as u32;
+
+    // the blocked list only holds current blocks, page from the end for the newest first
+    let total = get_blocked_list_len(&deps.storage, &address);
+    let end = total.saturating_sub(page * page_size);
+    let start = end.saturating_sub(page_size);
+    let mut blocked: Vec<String> = vec![];
+    for blocked_addr in get_blocked_list(&deps.storage, &address, start, end - start)?
+        .iter()
+        .rev()
+    {
+        let account = get_account(&deps.storage, blocked_addr)?.into_humanized(&deps.api)?;
+        blocked.push(account.handle);
+    }
+    let response = QueryAnswer::GetBlocked {
+        blocked,
+        total_count: total as i32,
+    };
+    to_binary(&response)
+}
+
+pub fn query_get_mutes<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    account: &HumanAddr,
+) -> QueryResult {
+    let address = deps.api.canonical_address(account)?;
+    let mutes = get_mutes(&deps.storage, &address);
+
+    let handles: StdResult<Vec<String>> = mutes
+        .accounts
+        .iter()
+        .map(|muted| {
+            Ok(get_account(&deps.storage, muted)?
+                .into_humanized(&deps.api)?
+                .handle)
+        })
+        .collect();
+    let response = QueryAnswer::GetMutes {
+        handles: handles?,
+        tags: mutes.tags,
+    };
+    to_binary(&response)
+}
+
+pub fn query_get_messages<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    account: &HumanAddr,
+    sent: bool,
+    page: Option<i32>,
+    page_size: Option<i32>,
+) -> QueryResult {
+    let address = deps.api.canonical_address(account)?;
+
+    if is_deactivated(&deps.storage, &address) {
+        return Err(StdError::generic_err("Account has been deactivated."));
+    }
+
+    let page = page.unwrap_or_else(|| 0_i32) as u32;
+    let page_size = page_size.unwrap_or_else(|| 10_i32) as u32;
+
+    let mut messages: Vec<MessageResponse> = vec![];
+    for (message_id, message) in get_messages(&deps.storage, &address, sent, page, page_size)? {
+        let handle = get_account(&deps.storage, &message.other)?
+            .into_humanized(&deps.api)?
+            .handle;
+        messages.push(MessageResponse {
+            message_id: message_id as i32,
+            handle,
+            text: String::from_utf8(message.text).unwrap_or_default(),
+            timestamp: message.timestamp as i32,
+        });
+    }
+    let total_count = get_number_of_messages(&deps.storage, &address, sent) as i32;
+
+    let response = if sent {
+        QueryAnswer::GetOutbox {
+            messages,
+            total_count,
+        }
+    } else {
+        QueryAnswer::GetInbox {
+            messages,
+            total_count,
+        }
+    };
+    to_binary(&response)
+}
+
+pub fn query_get_notifications<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    account: &HumanAddr,
+    since: Option<i32>,
+    before: Option<i32>,
+    page_size: Option<i32>,
+) -> QueryResult {
+    let address = deps.api.canonical_address(account)?;
+
+    if is_deactivated(&deps.storage, &address) {
+        return Err(StdError::generic_err("Account has been deactivated."));
+    }
+
+    // a negative since returns everything, like not sending it
+    let since = since.filter(|since| *since >= 0).map(|since| since as u32);
+    let before = before.map(|before| before.max(0) as u32);
+    let page_size = page_size.unwrap_or_else(|| 10_i32) as u32;
+    let read = get_notifications_read(&deps.storage, &address);
+
+    let notifications: StdResult<Vec<NotificationResponse>> =
+        get_notifications(&deps.storage, &address, since, before, page_size)?
+            .into_iter()
+            .map(|(notification_id, notification)| {
+                let handle = get_account(&deps.storage, &notification.actor)
+                    .ok()
+                    .map(|account| account.into_humanized(&deps.api))
+                    .transpose()?
+                    .map(|account| account.handle);
+                // fardels are identified by their hash id outside the contract
+                let fardel_id = match notification.fardel_id {
+                    Some(global_id) => {
+                        get_fardel_by_global_id(&deps.storage, global_id)?.map(|f| f.hash_id)
+                    }
+                    None => None,
+                };
+                Ok(NotificationResponse {
+                    notification_id: notification_id as i32,
+                    kind: notification.kind,
+                    handle,
+                    fardel_id,
+                    timestamp: notification.timestamp as i32,
+                    read: notification_id < read,
+                })
+            })
+            .collect();
+    let unread_count = get_number_of_notifications(&deps.storage, &address) - read;
+
+    let response = QueryAnswer::GetNotifications {
+        notifications: notifications?,
+        unread_count: unread_count as i32,
+    };
+    to_binary(&response)
+}
+
 pub fn query_get_followers<S: Storage, A: Api, Q: Querier>(
     deps: &Extern<S, A, Q>,
     account: &HumanAddr,
@@ -561,6 +1064,40 @@ pub fn query_get_followers<S: Storage, A: Api, Q: Querier>(
     to_binary(&response)
 }
 
+pub fn query_get_follow_requests<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    account: &HumanAddr,
+    page: Option<i32>,
+    page_size: Option<i32>,
+) -> QueryResult {
+    let address = deps.api.canonical_address(account)?;
+
+    let page = page.unwrap_or_else(|| 0_i32) as u32;
+    let page_size = page_size.unwrap_or_else(|| 10_i32) as u32;
+
+    let requests: Vec<FollowRequestResponse> =
+        get_follow_requests(&deps.storage, &address, page, page_size)?
+            .into_iter()
+            .filter_map(|(requester, timestamp)| {
+                let handle = get_account(&deps.storage, &requester)
+                    .ok()?
+                    .into_humanized(&deps.api)
+                    .ok()?
+                    .handle;
+                Some(FollowRequestResponse {
+                    handle,
+                    timestamp: timestamp as i32,
+                })
+            })
+            .collect();
+    let total_count = get_number_of_follow_requests(&deps.storage, &address) as i32;
+    let response = QueryAnswer::GetFollowRequests {
+        requests,
+        total_count,
+    };
+    to_binary(&response)
+}
+
 pub fn query_is_pending_unpack<S: Storage, A: Api, Q: Querier>(
     deps: &Extern<S, A, Q>,
     account: &HumanAddr,
@@ -605,11 +1142,20 @@ pub fn query_get_unpacked<S: Storage, A: Api, Q: Querier>(
         get_unpacked_by_unpacker(&deps.storage, &address, page, page_size)
             .unwrap_or_else(|_| vec![]);
     let mut fardels: Vec<FardelResponse> = vec![];
+    let time = get_block_time(&deps.storage);
+    let mutes = get_mutes(&deps.storage, &address);
     for unpacked in unpacked_fardels {
         let unpack_id = unpacked.fardel_id;
         let fardel = get_fardel_by_global_id(&deps.storage, unpack_id)?;
         let fardel_owner = get_fardel_owner(&deps.storage, unpack_id)?;
-        if fardel.is_some() && fardel_owner != address {
+        // extra parts are listed with the fardel they belong to, and muted carriers and tags
+        //   are left out like in GetFardels
+        if fardel
+            .as_ref()
+            .map_or(false, |fardel| !mutes.hides(&fardel_owner, &fardel.tags))
+            && fardel_owner != address
+            && get_part_of(&deps.storage, unpack_id).is_none()
+        {
             let fardel = fardel.unwrap();
             let upvotes: i32 = get_upvotes(&deps.storage, unpack_id) as i32;
             let downvotes: i32 = get_downvotes(&deps.storage, unpack_id) as i32;
@@ -621,8 +1167,11 @@ pub fn query_get_unpacked<S: Storage, A: Api, Q: Querier>(
             if fardel.seal_time > 0 {
                 seal_time = Some(fardel.seal_time as i32);
             }
-            let sealed = get_sealed_status(&deps.storage, unpack_id);
+            let sealed = is_sealed(&deps.storage, unpack_id, fardel.seal_time, time);
             let img = get_fardel_img(&deps.storage, unpack_id);
+            let revised = get_last_revised(&deps.storage, unpack_id).map(|t| t as i32);
+            let parts = get_parts_response(deps, Some(&address), unpack_id);
+            let publish_time = get_scheduled_publish_time(&deps.storage, unpack_id);
 
             let mut remaining: Option<i32> = None;
             if fardel.countable > 0 {
@@ -657,6 +1206,10 @@ pub fn query_get_unpacked<S: Storage, A: Api, Q: Querier>(
                 contents_data: Some(fardel.contents_data),
                 rating,
                 img,
+                revised,
+                parts,
+                publish_time,
+                encrypted_contents: get_encrypted_unpack(&deps.storage, &address, unpack_id),
             });
         }
     }
@@ -670,6 +1223,48 @@ pub fn query_get_unpacked<S: Storage, A: Api, Q: Querier>(
     to_binary(&response)
 }
 
+// get revision history of a fardel the user has unpacked
+pub fn query_get_fardel_revisions<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    account: &HumanAddr,
+    fardel_id: Uint128,
+    page: Option<i32>,
+    page_size: Option<i32>,
+) -> QueryResult {
+    let address = deps.api.canonical_address(account)?;
+    let global_id = get_global_id_by_hash(&deps.storage, fardel_id.u128())?;
+    // the owner is automatically unpacked when carrying
+    if !get_unpacked_status_by_fardel_id(&deps.storage, &address, global_id).unpacked {
+        return Err(StdError::generic_err("You have not unpacked this fardel."));
+    }
+
+    let page = page.unwrap_or_else(|| 0_i32) as u32;
+    let page_size = page_size.unwrap_or_else(|| 10_i32) as u32;
+    let total_count = get_number_of_revisions(&deps.storage, global_id);
+    let revisions: Vec<RevisionResponse> =
+        get_revisions(&deps.storage, global_id, page, page_size)?
+            .into_iter()
+            .enumerate()
+            .map(|(i, revision)| RevisionResponse {
+                revision: (total_count - 1 - page * page_size - i as u32) as i32,
+                public_message: String::from_utf8(revision.public_message).unwrap_or_default(),
+                tags: revision
+                    .tags
+                    .into_iter()
+                    .map(|tag| String::from_utf8(tag).unwrap_or_default())
+                    .collect(),
+                contents_data: String::from_utf8(revision.contents_data).unwrap_or_default(),
+                timestamp: revision.timestamp as i32,
+            })
+            .collect();
+
+    let answer = QueryAnswer::GetFardelRevisions {
+        revisions,
+        total_count: total_count as i32,
+    };
+    to_binary(&answer)
+}
+
 // get user's current rating for a fardel
 pub fn query_get_rating<S: Storage, A: Api, Q: Querier>(
     deps: &Extern<S, A, Q>,
@@ -694,6 +1289,34 @@ pub fn query_get_rating<S: Storage, A: Api, Q: Querier>(
     to_binary(&response)
 }
 
+// get the logged in user's own ban status, reason and expiry
+pub fn query_get_ban_status<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    account: &HumanAddr,
+) -> QueryResult {
+    let address = deps.api.canonical_address(account)?;
+
+    let mut banned = false;
+    let mut reason: Option<String> = None;
+    let mut expiry: Option<i32> = None;
+    if let Ok(ban) = get_account_ban(&deps.storage, &address) {
+        if ban.is_active(get_block_time(&deps.storage)) {
+            banned = true;
+            reason = Some(String::from_utf8(ban.reason).ok().unwrap_or_default());
+            if ban.expiry > 0 {
+                expiry = Some(ban.expiry as i32);
+            }
+        }
+    }
+
+    let response = QueryAnswer::GetBanStatus {
+        banned,
+        reason,
+        expiry,
+    };
+    to_binary(&response)
+}
+
 // get pending approvals of unpacks for the given fardel owner
 pub fn query_get_pending_approvals<S: Storage, A: Api, Q: Querier>(
     deps: &Extern<S, A, Q>,
@@ -718,9 +1341,22 @@ pub fn query_get_pending_approvals<S: Storage, A: Api, Q: Querier>(
             let fardel = fardel.unwrap();
             let account = get_account(&deps.storage, &pu.unpacker)?;
             let handle = account.into_humanized(&deps.api)?.handle;
+            // show pending unpacks of extra parts against the fardel they belong to
+            let mut fardel_id = fardel.hash_id;
+            let mut part: Option<i32> = None;
+            if let Some(parent_id) = get_part_of(&deps.storage, pu.fardel_id) {
+                if let Some(parent) = get_fardel_by_global_id(&deps.storage, parent_id)? {
+                    fardel_id = parent.hash_id;
+                    part = get_fardel_parts(&deps.storage, parent_id)
+                        .iter()
+                        .position(|part_id| *part_id == pu.fardel_id)
+                        .map(|idx| idx as i32 + 1);
+                }
+            }
             pending.push(PendingApprovalResponse {
-                fardel_id: fardel.hash_id,
+                fardel_id,
                 handle,
+                part,
                 canceled: pu.canceled,
             });
         }
@@ -740,7 +1376,8 @@ pub fn query_get_fardels_batch<S: Storage, A: Api, Q: Querier>(
     let constants = config.constants()?;
 
     // permission check - admin only
-    if deps.api.canonical_address(address)? != constants.admin {
+    let admin = deps.api.canonical_address(address)?;
+    if admin != constants.admin {
         return Err(StdError::unauthorized());
     }
 
@@ -754,14 +1391,26 @@ pub fn query_get_fardels_batch<S: Storage, A: Api, Q: Querier>(
         end = total;
     }
 
+    let time = get_block_time(&deps.storage);
     for idx in start..end {
         let owner = get_fardel_owner(&deps.storage, idx)?;
         let banned = is_banned(&deps.storage, &owner);
         let deactivated = is_deactivated(&deps.storage, &owner);
         let removed = is_fardel_removed(&deps.storage, idx);
         let hidden = is_fardel_hidden(&deps.storage, idx);
-        // ignore if fardel is hidden or user is banned or deactivated
-        if !(banned || deactivated || removed || hidden) {
+        let part = get_part_of(&deps.storage, idx).is_some();
+        let scheduled = get_scheduled_publish_time(&deps.storage, idx).is_some();
+        // the same private account, block and audience checks as the other fardel queries,
+        //   with the admin as the viewer
+        let followers_only = is_private_account(&deps.storage, &owner)
+            && admin != owner
+            && !is_following(&deps.storage, &admin, &owner);
+        let restricted = followers_only
+            || is_blocked_by(&deps.storage, &owner, &admin)
+            || !audience_allows(&deps.storage, idx, &owner, Some(&admin));
+        // ignore hidden, unpublished, restricted or extra part fardels,
+        //   and banned or deactivated users
+        if !(banned || deactivated || removed || hidden || part || scheduled || restricted) {
             let fardel: Option<Fardel> = get_fardel_by_global_id(&deps.storage, idx)?;
             if fardel.is_some() {
                 let fardel = fardel.unwrap();
@@ -774,7 +1423,7 @@ pub fn query_get_fardels_batch<S: Storage, A: Api, Q: Querier>(
                 if fardel.seal_time > 0 {
                     seal_time = Some(fardel.seal_time as i32);
                 }
-                let sealed = get_sealed_status(&deps.storage, idx);
+                let sealed = is_sealed(&deps.storage, idx, fardel.seal_time, time);
                 let img = get_fardel_img(&deps.storage, idx);
                 let account = get_account(&deps.storage, &owner)?.into_humanized(&deps.api)?;
 
@@ -855,3 +1504,655 @@ pub fn query_get_registered_addresses<S: Storage, A: Api, Q: Querier>(
     let answer = QueryAnswer::GetRegisteredAccounts { accounts, total_registered };
     to_binary(&answer)
 }
+
+// get the handles reserved for the admin -- for admin only!
+pub fn query_get_reserved_handles<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    address: &HumanAddr,
+) -> QueryResult {
+    let constants = ReadonlyConfig::from_storage(&deps.storage).constants()?;
+
+    // permission check - admin only
+    if deps.api.canonical_address(address)? != constants.admin {
+        return Err(StdError::unauthorized());
+    }
+
+    let answer = QueryAnswer::GetReservedHandles {
+        handles: get_reserved_handles(&deps.storage),
+    };
+    to_binary(&answer)
+}
+
+pub fn query_get_handle_collisions<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    address: &HumanAddr,
+    start: Option<i32>,
+    count: Option<i32>,
+) -> QueryResult {
+    let constants = ReadonlyConfig::from_storage(&deps.storage).constants()?;
+
+    // permission check - admin only
+    if deps.api.canonical_address(address)? != constants.admin {
+        return Err(StdError::unauthorized());
+    }
+
+    let start = start.unwrap_or_else(|| 0_i32) as u32;
+    let count = count.unwrap_or_else(|| 10_i32) as u32;
+
+    let mut collisions: Vec<HandleCollisionResponse> = vec![];
+    for collision in get_handle_collisions(&deps.storage, start, count)? {
+        let handle = String::from_utf8(collision.handle).ok().unwrap_or_default();
+        // resolved once the account has a handle it can be looked up by
+        let current_handle = match get_account(&deps.storage, &collision.account) {
+            Ok(account) => {
+                let account = account.into_humanized(&deps.api)?;
+                match get_account_for_handle(&deps.storage, &account.handle) {
+                    Ok(owner) if owner == collision.account => Some(account.handle),
+                    _ => None,
+                }
+            }
+            Err(_) => None,
+        };
+        collisions.push(HandleCollisionResponse {
+            address: deps.api.human_address(&collision.account)?,
+            handle,
+            holder: deps.api.human_address(&collision.holder)?,
+            current_handle,
+        });
+    }
+    let total_count = get_handle_collisions_len(&deps.storage)? as i32;
+    let answer = QueryAnswer::GetHandleCollisions {
+        collisions,
+        total_count,
+    };
+    to_binary(&answer)
+}
+
+// get currently active bans -- for admin only!
+pub fn query_get_bans<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    address: &HumanAddr,
+    start: Option<i32>,
+    count: Option<i32>,
+) -> QueryResult {
+    let config = ReadonlyConfig::from_storage(&deps.storage);
+    let constants = config.constants()?;
+
+    // permission check - admin only
+    if deps.api.canonical_address(address)? != constants.admin {
+        return Err(StdError::unauthorized());
+    }
+
+    let start = start.unwrap_or_else(|| 0_i32) as u32;
+    let count = count.unwrap_or_else(|| 10_i32) as u32;
+    let time = get_block_time(&deps.storage);
+
+    let mut bans: Vec<BanResponse> = vec![];
+    for banned_addr in get_ban_list(&deps.storage, start, count)? {
+        let ban = get_account_ban(&deps.storage, &banned_addr)?;
+        // ignore lifted or lapsed bans
+        if ban.is_active(time) {
+            let handle = match get_account(&deps.storage, &banned_addr) {
+                Ok(account) => Some(account.into_humanized(&deps.api)?.handle),
+                Err(_) => None,
+            };
+            let mut expiry: Option<i32> = None;
+            if ban.expiry > 0 {
+                expiry = Some(ban.expiry as i32);
+            }
+            bans.push(BanResponse {
+                address: deps.api.human_address(&banned_addr)?,
+                handle,
+                reason: String::from_utf8(ban.reason).ok().unwrap_or_default(),
+                expiry,
+                timestamp: ban.timestamp as i32,
+            });
+        }
+    }
+    let total_count = get_ban_list_len(&deps.storage)? as i32;
+    let answer = QueryAnswer::GetBans { bans, total_count };
+    to_binary(&answer)
+}
+
+pub fn query_export_fardels<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    address: &HumanAddr,
+    start: Option<Uint128>,
+    count: Option<Uint128>,
+) -> QueryResult {
+    let config = ReadonlyConfig::from_storage(&deps.storage);
+    let constants = config.constants()?;
+
+    // permission check - admin only
+    if deps.api.canonical_address(address)? != constants.admin {
+        return Err(StdError::unauthorized());
+    }
+    let export_key = get_export_key(&deps.storage)?;
+
+    let start = start.unwrap_or_else(|| Uint128(0)).u128();
+    let count = count.unwrap_or_else(|| Uint128(10)).u128();
+
+    let mut fardels: Vec<FardelExport> = vec![];
+    let total = get_total_fardel_count(&deps.storage);
+    let mut end = start + count;
+    if end > total {
+        end = total;
+    }
+
+    let time = get_block_time(&deps.storage);
+    for idx in start..end {
+        let fardel = match get_fardel_by_global_id(&deps.storage, idx)? {
+            Some(fardel) => fardel,
+            None => continue,
+        };
+        let owner = get_fardel_owner(&deps.storage, idx)?;
+        let label = [b"contents".as_ref(), &idx.to_be_bytes()].concat();
+        let key_label = [b"contents-key".as_ref(), &idx.to_be_bytes()].concat();
+        let contents_key = match get_fardel_key(&deps.storage, idx) {
+            Some(key) => Some(Binary(encrypt_export_data(&export_key, &key_label, &key)?)),
+            None => None,
+        };
+        fardels.push(FardelExport {
+            global_id: fardel.global_id,
+            hash_id: fardel.hash_id,
+            owner: deps.api.human_address(&owner)?,
+            public_message: fardel.public_message,
+            tags: fardel.tags,
+            contents_data: Binary(encrypt_export_data(
+                &export_key,
+                &label,
+                fardel.contents_data.as_bytes(),
+            )?),
+            contents_key,
+            cost: fardel.cost.amount,
+            countable: fardel.countable as i32,
+            approval_req: fardel.approval_req,
+            seal_time: fardel.seal_time as i32,
+            timestamp: fardel.timestamp as i32,
+            img: get_fardel_img(&deps.storage, idx),
+            sealed: is_sealed(&deps.storage, idx, fardel.seal_time, time),
+            hidden: is_fardel_hidden(&deps.storage, idx),
+            removed: is_fardel_removed(&deps.storage, idx),
+            unpack_count: get_fardel_unpack_count(&deps.storage, idx).unwrap_or(0_u64) as i32,
+            upvotes: get_upvotes(&deps.storage, idx) as i32,
+            downvotes: get_downvotes(&deps.storage, idx) as i32,
+            number_of_comments: get_number_of_comments(&deps.storage, idx) as i32,
+            number_of_revisions: get_number_of_revisions(&deps.storage, idx) as i32,
+            number_of_restocks: get_number_of_restocks(&deps.storage, idx) as i32,
+            part_of: get_part_of(&deps.storage, idx).map(Uint128),
+            publish_time: get_publish_time(&deps.storage, idx) as i32,
+            audience: match get_audience(&deps.storage, idx) {
+                StoredAudience::Public => AudienceExport::Public,
+                StoredAudience::Followers => AudienceExport::Followers,
+                StoredAudience::AllowList(allowed) => AudienceExport::AllowList {
+                    addresses: allowed
+                        .iter()
+                        .map(|address| deps.api.human_address(address))
+                        .collect::<StdResult<Vec<HumanAddr>>>()?,
+                },
+            },
+        });
+    }
+
+    let answer = QueryAnswer::ExportFardels {
+        fardels,
+        total_count: Uint128(total),
+    };
+    to_binary(&answer)
+}
+
+pub fn query_export_comments<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    address: &HumanAddr,
+    fardel_id: Uint128,
+    start: Option<i32>,
+    count: Option<i32>,
+) -> QueryResult {
+    let config = ReadonlyConfig::from_storage(&deps.storage);
+    let constants = config.constants()?;
+
+    // permission check - admin only
+    if deps.api.canonical_address(address)? != constants.admin {
+        return Err(StdError::unauthorized());
+    }
+    get_export_key(&deps.storage)?;
+
+    let start = start.unwrap_or_else(|| 0_i32) as u32;
+    let count = count.unwrap_or_else(|| 10_i32) as u32;
+
+    let mut comments: Vec<CommentExport> = vec![];
+    for (comment, deleted) in get_comments_range(&deps.storage, fardel_id.u128(), start, count)? {
+        comments.push(CommentExport {
+            commenter: deps.api.human_address(&comment.commenter)?,
+            text: String::from_utf8(comment.text).ok().unwrap_or_default(),
+            deleted,
+        });
+    }
+
+    let answer = QueryAnswer::ExportComments { comments };
+    to_binary(&answer)
+}
+
+pub fn query_export_revisions<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    address: &HumanAddr,
+    fardel_id: Uint128,
+    start: Option<i32>,
+    count: Option<i32>,
+) -> QueryResult {
+    let config = ReadonlyConfig::from_storage(&deps.storage);
+    let constants = config.constants()?;
+
+    // permission check - admin only
+    if deps.api.canonical_address(address)? != constants.admin {
+        return Err(StdError::unauthorized());
+    }
+    let export_key = get_export_key(&deps.storage)?;
+
+    let fardel_id = fardel_id.u128();
+    let start = start.unwrap_or_else(|| 0_i32) as u32;
+    let count = count.unwrap_or_else(|| 10_i32) as u32;
+
+    let mut revisions: Vec<RevisionExport> = vec![];
+    for (offset, revision) in get_revisions_range(&deps.storage, fardel_id, start, count)?
+        .into_iter()
+        .enumerate()
+    {
+        let idx = start + offset as u32;
+        let label = [
+            b"revision".as_ref(),
+            &fardel_id.to_be_bytes(),
+            &idx.to_be_bytes(),
+        ]
+        .concat();
+        revisions.push(RevisionExport {
+            public_message: String::from_utf8(revision.public_message)
+                .ok()
+                .unwrap_or_default(),
+            tags: revision
+                .tags
+                .into_iter()
+                .map(|tag| String::from_utf8(tag).ok().unwrap_or_default())
+                .collect(),
+            contents_data: Binary(encrypt_export_data(
+                &export_key,
+                &label,
+                &revision.contents_data,
+            )?),
+            timestamp: revision.timestamp as i32,
+        });
+    }
+
+    let answer = QueryAnswer::ExportRevisions { revisions };
+    to_binary(&answer)
+}
+
+pub fn query_export_restocks<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    address: &HumanAddr,
+    fardel_id: Uint128,
+    start: Option<i32>,
+    count: Option<i32>,
+) -> QueryResult {
+    let config = ReadonlyConfig::from_storage(&deps.storage);
+    let constants = config.constants()?;
+
+    // permission check - admin only
+    if deps.api.canonical_address(address)? != constants.admin {
+        return Err(StdError::unauthorized());
+    }
+    get_export_key(&deps.storage)?;
+
+    let start = start.unwrap_or_else(|| 0_i32) as u32;
+    let count = count.unwrap_or_else(|| 10_i32) as u32;
+
+    let restocks: Vec<RestockExport> =
+        get_restocks_range(&deps.storage, fardel_id.u128(), start, count)?
+            .into_iter()
+            .map(|restock| RestockExport {
+                added: restock.added as i32,
+                countable: restock.countable as i32,
+                seal_time: restock.seal_time as i32,
+                timestamp: restock.timestamp as i32,
+            })
+            .collect();
+
+    let answer = QueryAnswer::ExportRestocks { restocks };
+    to_binary(&answer)
+}
+
+pub fn query_export_settings<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    address: &HumanAddr,
+) -> QueryResult {
+    let config = ReadonlyConfig::from_storage(&deps.storage);
+    let constants = config.constants()?;
+
+    // permission check - admin only
+    if deps.api.canonical_address(address)? != constants.admin {
+        return Err(StdError::unauthorized());
+    }
+    get_export_key(&deps.storage)?;
+
+    let answer = QueryAnswer::ExportSettings {
+        settings: SettingsExport {
+            reserved_handles: get_reserved_handles(&deps.storage),
+            message_fee: Uint128(get_message_fee(&deps.storage)),
+        },
+    };
+    to_binary(&answer)
+}
+
+pub fn query_export_accounts<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    address: &HumanAddr,
+    start: Option<i32>,
+    count: Option<i32>,
+) -> QueryResult {
+    let config = ReadonlyConfig::from_storage(&deps.storage);
+    let constants = config.constants()?;
+
+    // permission check - admin only
+    if deps.api.canonical_address(address)? != constants.admin {
+        return Err(StdError::unauthorized());
+    }
+    let export_key = get_export_key(&deps.storage)?;
+
+    let start = start.unwrap_or_else(|| 0_i32) as u32;
+    let count = count.unwrap_or_else(|| 10_i32) as u32;
+
+    let mut accounts: Vec<AccountExport> = vec![];
+    for owner in get_registered_addresses(&deps.storage, start, count)? {
+        // escrowed funds can't be moved to the new contract
+        if get_number_of_pending_from_start(&deps.storage, &owner)? > 0 {
+            return Err(StdError::generic_err(format!(
+                "Account {} has pending unpacks, refund them with RefundAllPending before exporting.",
+                deps.api.human_address(&owner)?
+            )));
+        }
+        let account = get_account(&deps.storage, &owner)?;
+        let private_label = [b"private-settings".as_ref(), owner.as_slice()].concat();
+        let key_label = [b"viewing-key".as_ref(), owner.as_slice()].concat();
+        let viewing_key = match read_viewing_key(&deps.storage, &owner) {
+            Some(hashed) => Some(Binary(encrypt_export_data(
+                &export_key,
+                &key_label,
+                &hashed,
+            )?)),
+            None => None,
+        };
+        let ban = match get_account_ban(&deps.storage, &owner) {
+            Ok(ban) => Some(BanExport {
+                banned: ban.banned,
+                expiry: ban.expiry as i32,
+                reason: String::from_utf8(ban.reason).ok().unwrap_or_default(),
+                timestamp: ban.timestamp as i32,
+            }),
+            Err(_) => None,
+        };
+        let mut named_viewing_keys: Vec<NamedViewingKeyExport> = vec![];
+        for (idx, named) in get_named_viewing_keys(&deps.storage, &owner)?
+            .into_iter()
+            .enumerate()
+        {
+            let named_label = [
+                b"named-viewing-key".as_ref(),
+                owner.as_slice(),
+                &(idx as u32).to_be_bytes(),
+            ]
+            .concat();
+            named_viewing_keys.push(NamedViewingKeyExport {
+                name: String::from_utf8(named.name).ok().unwrap_or_default(),
+                hashed_key: Binary(encrypt_export_data(
+                    &export_key,
+                    &named_label,
+                    &named.hashed_key,
+                )?),
+                scopes: named.scopes,
+                timestamp: named.timestamp as i32,
+                revoked: named.revoked,
+            });
+        }
+        let mutes = get_mutes(&deps.storage, &owner);
+        let mutes = MutesExport {
+            accounts: mutes
+                .accounts
+                .iter()
+                .map(|muted| deps.api.human_address(muted))
+                .collect::<StdResult<Vec<HumanAddr>>>()?,
+            tags: mutes.tags,
+        };
+        let mutes_label = [b"mutes".as_ref(), owner.as_slice()].concat();
+        let img = get_account_img(&deps.storage, &owner).unwrap_or_else(|_| vec![]);
+        accounts.push(AccountExport {
+            address: deps.api.human_address(&owner)?,
+            handle: String::from_utf8(account.handle).ok().unwrap_or_default(),
+            description: String::from_utf8(account.description)
+                .ok()
+                .unwrap_or_default(),
+            view_settings: String::from_utf8(account.view_settings)
+                .ok()
+                .unwrap_or_default(),
+            private_settings: Binary(encrypt_export_data(
+                &export_key,
+                &private_label,
+                &account.private_settings,
+            )?),
+            img: String::from_utf8(img).ok().unwrap_or_default(),
+            viewing_key,
+            deactivated: is_deactivated(&deps.storage, &owner),
+            private: is_private_account(&deps.storage, &owner),
+            ban,
+            named_viewing_keys,
+            mutes: Binary(encrypt_export_data(
+                &export_key,
+                &mutes_label,
+                to_binary(&mutes)?.as_slice(),
+            )?),
+            notification_opt_outs: get_notification_opt_outs(&deps.storage, &owner),
+            notifications_read: get_notifications_read(&deps.storage, &owner) as i32,
+            number_following: get_number_of_following(&deps.storage, &owner) as i32,
+            number_unpacked: get_number_of_unpacked_by_unpacker(&deps.storage, &owner) as i32,
+            number_sales: get_number_of_sales(&deps.storage, &owner)? as i32,
+            number_purchases: get_number_of_purchases(&deps.storage, &owner)? as i32,
+            number_blocked: get_blocked_list_len(&deps.storage, &owner) as i32,
+            number_follow_requests: get_number_of_follow_requests(&deps.storage, &owner) as i32,
+            number_inbox: get_message_box_len(&deps.storage, &owner, false) as i32,
+            number_outbox: get_message_box_len(&deps.storage, &owner, true) as i32,
+            number_notifications: get_number_of_notifications(&deps.storage, &owner) as i32,
+            number_previous_handles: get_handle_history_len(&deps.storage, &owner) as i32,
+            number_delegates: get_delegate_list(&deps.storage, &owner)?.len() as i32,
+        });
+    }
+
+    let total_count = get_total_number_registered_accounts(&deps.storage)? as i32;
+    let answer = QueryAnswer::ExportAccounts {
+        accounts,
+        total_count,
+    };
+    to_binary(&answer)
+}
+
+pub fn query_export_account_list<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    address: &HumanAddr,
+    owner: &HumanAddr,
+    list: ExportList,
+    start: Option<i32>,
+    count: Option<i32>,
+) -> QueryResult {
+    let config = ReadonlyConfig::from_storage(&deps.storage);
+    let constants = config.constants()?;
+
+    // permission check - admin only
+    if deps.api.canonical_address(address)? != constants.admin {
+        return Err(StdError::unauthorized());
+    }
+    let export_key = get_export_key(&deps.storage)?;
+
+    let owner = deps.api.canonical_address(owner)?;
+    let start = start.unwrap_or_else(|| 0_i32) as u32;
+    let count = count.unwrap_or_else(|| 10_i32) as u32;
+
+    let mut entries: Vec<AccountListEntry> = vec![];
+    match list {
+        ExportList::Following => {
+            let following = get_following_range(&deps.storage, &owner, start, count)?;
+            for (offset, following) in following.into_iter().enumerate() {
+                let label = export_entry_label(b"following", &owner, start + offset as u32);
+                entries.push(AccountListEntry::Following {
+                    who: Binary(encrypt_export_data(
+                        &export_key,
+                        &label,
+                        following.who.as_slice(),
+                    )?),
+                    active: following.active,
+                });
+            }
+        }
+        ExportList::Unpacked => {
+            for unpacked in get_unpacked_range(&deps.storage, &owner, start, count)? {
+                entries.push(AccountListEntry::Unpacked {
+                    fardel_id: Uint128(unpacked.fardel_id),
+                    rating: get_rating(&deps.storage, &owner, unpacked.fardel_id).ok(),
+                    encrypted_contents: get_encrypted_unpack(
+                        &deps.storage,
+                        &owner,
+                        unpacked.fardel_id,
+                    ),
+                });
+            }
+        }
+        ExportList::Sales => {
+            for tx in get_stored_sale_txs(&deps.storage, &owner, start, count)? {
+                entries.push(AccountListEntry::Sale {
+                    fardel_id: Uint128(tx.fardel_id),
+                    unpacker: deps.api.human_address(&tx.unpacker)?,
+                    amount: Uint128(tx.amount),
+                    fee: Uint128(tx.fee),
+                    timestamp: tx.timestamp as i32,
+                });
+            }
+        }
+        ExportList::Purchases => {
+            for tx in get_stored_purchase_txs(&deps.storage, &owner, start, count)? {
+                entries.push(AccountListEntry::Purchase {
+                    fardel_id: Uint128(tx.fardel_id),
+                    owner: deps.api.human_address(&tx.owner)?,
+                    amount: Uint128(tx.amount),
+                    fee: Uint128(tx.fee),
+                    timestamp: tx.timestamp as i32,
+                });
+            }
+        }
+        ExportList::Blocked => {
+            let blocked = get_blocked_list(&deps.storage, &owner, start, count)?;
+            for (offset, blocked) in blocked.into_iter().enumerate() {
+                let label = export_entry_label(b"blocked", &owner, start + offset as u32);
+                entries.push(AccountListEntry::Blocked {
+                    who: Binary(encrypt_export_data(
+                        &export_key,
+                        &label,
+                        blocked.as_slice(),
+                    )?),
+                });
+            }
+        }
+        ExportList::FollowRequests => {
+            let requests = get_follow_request_range(&deps.storage, &owner, start, count)?;
+            for (offset, (requester, timestamp)) in requests.into_iter().enumerate() {
+                let label = export_entry_label(b"follow-request", &owner, start + offset as u32);
+                entries.push(AccountListEntry::FollowRequest {
+                    requester: Binary(encrypt_export_data(
+                        &export_key,
+                        &label,
+                        requester.as_slice(),
+                    )?),
+                    timestamp: timestamp as i32,
+                });
+            }
+        }
+        ExportList::Inbox | ExportList::Outbox => {
+            let sent = list == ExportList::Outbox;
+            let messages = get_messages_range(&deps.storage, &owner, sent, start, count)?;
+            for (offset, message) in messages.into_iter().enumerate() {
+                let idx = start + offset as u32;
+                let (label, other_label) = if sent {
+                    (
+                        export_entry_label(b"outbox", &owner, idx),
+                        export_entry_label(b"outbox-to", &owner, idx),
+                    )
+                } else {
+                    (
+                        export_entry_label(b"inbox", &owner, idx),
+                        export_entry_label(b"inbox-from", &owner, idx),
+                    )
+                };
+                let other = Binary(encrypt_export_data(
+                    &export_key,
+                    &other_label,
+                    message.other.as_slice(),
+                )?);
+                let text = Binary(encrypt_export_data(&export_key, &label, &message.text)?);
+                let timestamp = message.timestamp as i32;
+                let deleted = message.deleted;
+                entries.push(if sent {
+                    AccountListEntry::Sent {
+                        to: other,
+                        text,
+                        timestamp,
+                        deleted,
+                    }
+                } else {
+                    AccountListEntry::Received {
+                        from: other,
+                        text,
+                        timestamp,
+                        deleted,
+                    }
+                });
+            }
+        }
+        ExportList::Notifications => {
+            let notifications = get_notifications_range(&deps.storage, &owner, start, count)?;
+            for (offset, notification) in notifications.into_iter().enumerate() {
+                let label =
+                    export_entry_label(b"notification-actor", &owner, start + offset as u32);
+                entries.push(AccountListEntry::Notification {
+                    kind: notification.kind,
+                    actor: Binary(encrypt_export_data(
+                        &export_key,
+                        &label,
+                        notification.actor.as_slice(),
+                    )?),
+                    fardel_id: notification.fardel_id.map(Uint128),
+                    timestamp: notification.timestamp as i32,
+                });
+            }
+        }
+        ExportList::PreviousHandles => {
+            for released in get_handle_history_range(&deps.storage, &owner, start, count)? {
+                entries.push(AccountListEntry::PreviousHandle {
+                    handle: String::from_utf8(released.handle).ok().unwrap_or_default(),
+                    timestamp: released.timestamp as i32,
+                });
+            }
+        }
+        ExportList::Delegates => {
+            let delegates = get_delegate_list(&deps.storage, &owner)?;
+            for delegate in delegates.iter().skip(start as _).take(count as _) {
+                if let Some(delegation) = get_delegation(&deps.storage, &owner, delegate) {
+                    entries.push(AccountListEntry::Delegate {
+                        who: deps.api.human_address(delegate)?,
+                        permissions: delegation.permissions,
+                        expiry: delegation.expiry as i32,
+                        revoked: delegation.revoked,
+                    });
+                }
+            }
+        }
+    }
+
+    let answer = QueryAnswer::ExportAccountList { entries };
+    to_binary(&answer)
+}
@@ -1,24 +1,34 @@
 use crate::fardel_state::get_fardel_owner;
 use crate::state::{
-    get_bin_data, set_bin_data, PREFIX_BLOCKED, PREFIX_COMMENTS, PREFIX_DELETED_COMMENTS,
-    PREFIX_DOWNVOTES, PREFIX_FOLLOWERS, PREFIX_FOLLOWER_COUNT, PREFIX_FOLLOWING, PREFIX_LINK,
-    PREFIX_RATED, PREFIX_UPVOTES, PREFIX_VEC,
+    get_bin_data, set_bin_data, BlockBackfill, FollowCompaction, PREFIX_BLOCKED,
+    PREFIX_BLOCKED_LINK, PREFIX_BLOCKED_LIST, PREFIX_BLOCK_PURGE, PREFIX_COMMENTS,
+    PREFIX_DELETED_COMMENTS, PREFIX_DOWNVOTES, PREFIX_FOLLOWERS, PREFIX_FOLLOWER_COUNT,
+    PREFIX_FOLLOWING, PREFIX_FOLLOW_REQUESTS, PREFIX_FOLLOW_REQUEST_LIST, PREFIX_LINK,
+    PREFIX_MUTES, PREFIX_RATED, PREFIX_UPVOTES, PREFIX_VEC,
+};
+use crate::user_state::{
+    get_account, get_account_for_handle, get_registered_address,
+    get_total_number_registered_accounts, Account,
 };
-use crate::user_state::{get_account, get_account_for_handle, Account};
 use cosmwasm_std::{Api, CanonicalAddr, ReadonlyStorage, StdError, StdResult, Storage};
 use cosmwasm_storage::{PrefixedStorage, ReadonlyPrefixedStorage};
 use secret_toolkit::storage::{AppendStore, AppendStoreMut};
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 
 //
 // Following / Follower
 //
 //   b"following" | {owner canonical addr} | b"link" | {followed canonical addr} -> v_index
-//   b"following" | {owner canonical addr} | b"vec" | {appendstore index} -> Following (active = true means following)
+//   b"following" | {owner canonical addr} | b"vec" | {appendstore index} -> Following
 //   b"followers" | {owner canonical addr} | b"link" | {follower canonical addr} -> v_index
-//   b"followers" | {owner canonical addr} | b"vec" | {appendstore index} -> Follower (active = true means follower)
+//   b"followers" | {owner canonical addr} | b"vec" | {appendstore index} -> Follower
 //   b"follower-count" | {canonical addr} -> count of (active) followers of this account
 //
+// removing a relation swap-removes it from the appendstore, so the lists stay compact.
+//   The last relation is moved into the removed one's slot, so after a removal the lists are
+//   no longer strictly in the order the relations were made.
+//
 // addresses are saved rather than handles, in case followed user changes handle
 //
 
@@ -40,7 +50,14 @@ pub fn store_following<S: Storage>(
     handle: String,
 ) -> StdResult<()> {
     let followed_addr = get_account_for_handle(storage, &handle)?;
+    store_following_address(storage, owner, &followed_addr)
+}
 
+pub fn store_following_address<S: Storage>(
+    storage: &mut S,
+    owner: &CanonicalAddr,
+    followed_addr: &CanonicalAddr,
+) -> StdResult<()> {
     save_following_relation(storage, &owner, &followed_addr)?;
     save_follower_relation(storage, &owner, &followed_addr)?;
     increment_follower_count(storage, &followed_addr);
@@ -178,42 +195,67 @@ pub fn remove_following<S: Storage>(
     handle: String,
 ) -> StdResult<()> {
     let followed_addr = get_account_for_handle(storage, &handle)?;
+    remove_following_address(storage, owner, &followed_addr)
+}
+
+// deactivates both sides of the relation, also used when an account removes one of its followers
+pub fn remove_following_address<S: Storage>(
+    storage: &mut S,
+    owner: &CanonicalAddr,
+    followed_addr: &CanonicalAddr,
+) -> StdResult<()> {
+    // only active relations count towards follower-count
+    let was_following = is_following(storage, owner, followed_addr);
 
     delete_following_relation(storage, &owner, &followed_addr)?;
     delete_follower_relation(storage, &owner, &followed_addr)?;
-    decrement_follower_count(storage, &followed_addr);
+    if was_following {
+        decrement_follower_count(storage, &followed_addr);
+    }
 
     Ok(())
 }
 
+// removed relations are swap-removed: the last entry is moved into their slot, so the
+//   appendstore only ever holds active relations
 fn delete_following_relation<S: Storage>(
     storage: &mut S,
     owner: &CanonicalAddr,
     followed_addr: &CanonicalAddr,
 ) -> StdResult<()> {
-    let mut vec_storage =
-        PrefixedStorage::multilevel(&[PREFIX_FOLLOWING, &owner.as_slice(), PREFIX_VEC], storage);
-    let vec_storage = AppendStoreMut::<Following, _>::attach_or_create(&mut vec_storage)?;
-    let vec_storage_len = vec_storage.len();
-
     let link_storage = ReadonlyPrefixedStorage::multilevel(
         &[PREFIX_FOLLOWING, &owner.as_slice(), PREFIX_LINK],
         storage,
     );
-    // update following relation, active = false
-
-    // get current idx and set to false or ignore if not following
-    let idx: u32 =
-        get_bin_data(&link_storage, followed_addr.as_slice()).unwrap_or_else(|_| vec_storage_len);
+    // ignore if not following
+    let idx: u32 = match get_bin_data(&link_storage, followed_addr.as_slice()) {
+        Ok(idx) => idx,
+        Err(_) => return Ok(()),
+    };
 
-    let mut vec_storage =
-        PrefixedStorage::multilevel(&[PREFIX_FOLLOWING, &owner.as_slice(), PREFIX_VEC], storage);
-    let mut vec_storage = AppendStoreMut::<Following, _>::attach_or_create(&mut vec_storage)?;
+    let moved = {
+        let mut vec_storage = PrefixedStorage::multilevel(
+            &[PREFIX_FOLLOWING, &owner.as_slice(), PREFIX_VEC],
+            storage,
+        );
+        let mut vec_storage = AppendStoreMut::<Following, _>::attach_or_create(&mut vec_storage)?;
+        if idx >= vec_storage.len() {
+            return Err(StdError::generic_err("Following index out of range."));
+        }
+        let last: Following = vec_storage.pop()?;
+        if idx < vec_storage.len() {
+            vec_storage.set_at(idx, &last)?;
+            Some(last.who)
+        } else {
+            None
+        }
+    };
 
-    if idx < vec_storage_len {
-        let mut following: Following = vec_storage.get_at(idx)?;
-        following.active = false;
-        vec_storage.set_at(idx, &following)?;
+    let mut link_storage =
+        PrefixedStorage::multilevel(&[PREFIX_FOLLOWING, &owner.as_slice(), PREFIX_LINK], storage);
+    link_storage.remove(followed_addr.as_slice());
+    if let Some(moved) = moved {
+        set_bin_data(&mut link_storage, moved.as_slice(), &idx)?;
     }
 
     Ok(())
@@ -224,32 +266,137 @@ fn delete_follower_relation<S: Storage>(
     owner: &CanonicalAddr,
     followed_addr: &CanonicalAddr,
 ) -> StdResult<()> {
-    let mut vec_storage = PrefixedStorage::multilevel(
-        &[PREFIX_FOLLOWERS, &followed_addr.as_slice(), PREFIX_VEC],
+    let link_storage = ReadonlyPrefixedStorage::multilevel(
+        &[PREFIX_FOLLOWERS, &followed_addr.as_slice(), PREFIX_LINK],
         storage,
     );
-    let vec_storage = AppendStoreMut::<Follower, _>::attach_or_create(&mut vec_storage)?;
-    let vec_storage_len = vec_storage.len();
+    // ignore if not a follower
+    let idx: u32 = match get_bin_data(&link_storage, owner.as_slice()) {
+        Ok(idx) => idx,
+        Err(_) => return Ok(()),
+    };
 
-    let link_storage = ReadonlyPrefixedStorage::multilevel(
+    let moved = {
+        let mut vec_storage = PrefixedStorage::multilevel(
+            &[PREFIX_FOLLOWERS, &followed_addr.as_slice(), PREFIX_VEC],
+            storage,
+        );
+        let mut vec_storage = AppendStoreMut::<Follower, _>::attach_or_create(&mut vec_storage)?;
+        if idx >= vec_storage.len() {
+            return Err(StdError::generic_err("Follower index out of range."));
+        }
+        let last: Follower = vec_storage.pop()?;
+        if idx < vec_storage.len() {
+            vec_storage.set_at(idx, &last)?;
+            Some(last.who)
+        } else {
+            None
+        }
+    };
+
+    let mut link_storage = PrefixedStorage::multilevel(
         &[PREFIX_FOLLOWERS, &followed_addr.as_slice(), PREFIX_LINK],
         storage,
     );
-    let idx: u32 =
-        get_bin_data(&link_storage, owner.as_slice()).unwrap_or_else(|_| vec_storage_len);
+    link_storage.remove(owner.as_slice());
+    if let Some(moved) = moved {
+        set_bin_data(&mut link_storage, moved.as_slice(), &idx)?;
+    }
 
-    let mut vec_storage = PrefixedStorage::multilevel(
-        &[PREFIX_FOLLOWERS, &followed_addr.as_slice(), PREFIX_VEC],
+    Ok(())
+}
+
+// drops the inactive entries left by the old soft-delete from both of the owner's lists and
+//   rebuilds their follower count, used by Migrate
+//   each entry read or popped costs one unit of budget, progress is kept in the cursor so a
+//   long list can take several calls. Returns true once both lists are done.
+pub fn compact_follow_lists<S: Storage>(
+    storage: &mut S,
+    owner: &CanonicalAddr,
+    progress: &mut FollowCompaction,
+    budget: &mut u32,
+) -> StdResult<bool> {
+    if !progress.followers {
+        let done = compact_follow_list(
+            storage,
+            PREFIX_FOLLOWING,
+            owner,
+            progress,
+            budget,
+            |entry: &Following| (entry.who.clone(), entry.active),
+        )?;
+        if !done {
+            return Ok(false);
+        }
+        *progress = FollowCompaction {
+            followers: true,
+            read: 0,
+            write: 0,
+        };
+    }
+
+    let done = compact_follow_list(
         storage,
-    );
-    let mut vec_storage = AppendStoreMut::<Follower, _>::attach_or_create(&mut vec_storage)?;
+        PREFIX_FOLLOWERS,
+        owner,
+        progress,
+        budget,
+        |entry: &Follower| (entry.who.clone(), entry.active),
+    )?;
+    if !done {
+        return Ok(false);
+    }
+    set_follower_count(storage, owner, progress.write)?;
+    *progress = FollowCompaction::default();
 
-    if idx < vec_storage_len {
-        let mut follower: Follower = vec_storage.get_at(idx)?;
-        follower.active = false;
-        vec_storage.set_at(idx, &follower)?;
+    Ok(true)
+}
+
+// active entries are shifted down to the write index, so they keep their order, inactive
+//   entries lose their link, then the leftover tail is popped
+fn compact_follow_list<S: Storage, T: Serialize + DeserializeOwned>(
+    storage: &mut S,
+    prefix: &[u8],
+    owner: &CanonicalAddr,
+    progress: &mut FollowCompaction,
+    budget: &mut u32,
+    who_and_active: fn(&T) -> (CanonicalAddr, bool),
+) -> StdResult<bool> {
+    while *budget > 0 {
+        let read = {
+            let mut vec_storage =
+                PrefixedStorage::multilevel(&[prefix, owner.as_slice(), PREFIX_VEC], storage);
+            let mut vec_storage = AppendStoreMut::<T, _>::attach_or_create(&mut vec_storage)?;
+            if progress.read < vec_storage.len() {
+                let entry: T = vec_storage.get_at(progress.read)?;
+                let (who, active) = who_and_active(&entry);
+                if active && progress.write < progress.read {
+                    vec_storage.set_at(progress.write, &entry)?;
+                }
+                Some((who, active))
+            } else if progress.write < vec_storage.len() {
+                vec_storage.pop()?;
+                None
+            } else {
+                return Ok(true);
+            }
+        };
+
+        if let Some((who, active)) = read {
+            let mut link_storage =
+                PrefixedStorage::multilevel(&[prefix, owner.as_slice(), PREFIX_LINK], storage);
+            if active {
+                set_bin_data(&mut link_storage, who.as_slice(), &progress.write)?;
+                progress.write += 1;
+            } else {
+                link_storage.remove(who.as_slice());
+            }
+            progress.read += 1;
+        }
+        *budget -= 1;
     }
-    Ok(())
+
+    Ok(false)
 }
 
 // returns a vec of handles
@@ -273,8 +420,8 @@ pub fn get_following<A: Api, S: Storage>(
         return Ok(vec![]);
     };
 
-    // Take `page_size` following starting from the latest following, potentially skipping `page * page_size`
-    // following from the start. Also filters non-active following.
+    // Take `page_size` following starting from the end of the list, potentially skipping
+    // `page * page_size` following. Also filters non-active following not yet migrated.
     let following_iter = store
         .iter()
         .rev()
@@ -294,7 +441,37 @@ pub fn get_following<A: Api, S: Storage>(
     Ok(result)
 }
 
-// returns number following including non-active -- for pagination
+// returns up to `limit` accounts that owner follows that also follow owner back, newest follow
+//   first
+pub fn get_mutuals<S: ReadonlyStorage>(
+    storage: &S,
+    owner: &CanonicalAddr,
+    limit: u32,
+) -> StdResult<Vec<CanonicalAddr>> {
+    let store = ReadonlyPrefixedStorage::multilevel(
+        &[PREFIX_FOLLOWING, owner.as_slice(), PREFIX_VEC],
+        storage,
+    );
+    let store = if let Some(result) = AppendStore::<Following, _>::attach(&store) {
+        result?
+    } else {
+        return Ok(vec![]);
+    };
+
+    let mut mutuals: Vec<CanonicalAddr> = vec![];
+    for following in store.iter().rev() {
+        if mutuals.len() as u32 >= limit {
+            break;
+        }
+        let following = following?;
+        if following.active && is_following(storage, &following.who, owner) {
+            mutuals.push(following.who);
+        }
+    }
+    Ok(mutuals)
+}
+
+// returns number following -- for pagination
 pub fn get_number_of_following<S: ReadonlyStorage>(storage: &S, owner: &CanonicalAddr) -> u32 {
     let store = ReadonlyPrefixedStorage::multilevel(
         &[PREFIX_FOLLOWING, owner.as_slice(), PREFIX_VEC],
@@ -310,6 +487,25 @@ pub fn get_number_of_following<S: ReadonlyStorage>(storage: &S, owner: &Canonica
     };
 }
 
+// gets raw following relations in appendstore order, used for export
+pub fn get_following_range<S: ReadonlyStorage>(
+    storage: &S,
+    owner: &CanonicalAddr,
+    start: u32,
+    count: u32,
+) -> StdResult<Vec<Following>> {
+    let store = ReadonlyPrefixedStorage::multilevel(
+        &[PREFIX_FOLLOWING, &owner.as_slice(), PREFIX_VEC],
+        storage,
+    );
+    let store = if let Some(result) = AppendStore::<Following, _>::attach(&store) {
+        result?
+    } else {
+        return Ok(vec![]);
+    };
+    store.iter().skip(start as _).take(count as _).collect()
+}
+
 pub fn get_followers<A: Api, S: Storage>(
     api: &A,
     storage: &S,
@@ -330,8 +526,8 @@ pub fn get_followers<A: Api, S: Storage>(
         return Ok(vec![]);
     };
 
-    // Take `page_size` following starting from the latest followers, potentially skipping `page * page_size`
-    // followers from the start. Also filters non-active followers.
+    // Take `page_size` followers starting from the end of the list, potentially skipping
+    // `page * page_size` followers. Also filters non-active followers not yet migrated.
     let follower_iter = store
         .iter()
         .rev()
@@ -393,7 +589,7 @@ pub fn decrement_follower_count<S: Storage>(
     }
 }
 
-// returns number followers including non-active -- for pagination
+// returns number of followers -- for pagination
 pub fn get_number_of_followers<S: ReadonlyStorage>(storage: &S, owner: &CanonicalAddr) -> u32 {
     let store = ReadonlyPrefixedStorage::multilevel(
         &[PREFIX_FOLLOWERS, owner.as_slice(), PREFIX_VEC],
@@ -409,12 +605,158 @@ pub fn get_number_of_followers<S: ReadonlyStorage>(storage: &S, owner: &Canonica
     };
 }
 
+//
+// Follow requests (to private accounts)
+//   b"follow-requests" | {followed canonical addr} | {requester canonical addr} -> timestamp of
+//     the pending request, 0 once it has been accepted, denied, or cancelled
+//   b"follow-request-list" | {followed canonical addr} | {appendstore index} -> requester canonical addr
+//
+
+pub fn store_follow_request<S: Storage>(
+    storage: &mut S,
+    followed_addr: &CanonicalAddr,
+    requester: &CanonicalAddr,
+    timestamp: u64,
+) -> StdResult<()> {
+    let mut store =
+        PrefixedStorage::multilevel(&[PREFIX_FOLLOW_REQUESTS, followed_addr.as_slice()], storage);
+    let existing: StdResult<u64> = get_bin_data(&store, &requester.as_slice());
+    set_bin_data(&mut store, &requester.as_slice(), &timestamp)?;
+    if existing.is_err() {
+        let mut list_store = PrefixedStorage::multilevel(
+            &[PREFIX_FOLLOW_REQUEST_LIST, followed_addr.as_slice()],
+            storage,
+        );
+        let mut list_store = AppendStoreMut::<CanonicalAddr, _>::attach_or_create(&mut list_store)?;
+        list_store.push(requester)?;
+    }
+    Ok(())
+}
+
+pub fn clear_follow_request<S: Storage>(
+    storage: &mut S,
+    followed_addr: &CanonicalAddr,
+    requester: &CanonicalAddr,
+) -> StdResult<()> {
+    if get_follow_request(storage, followed_addr, requester).is_none() {
+        return Ok(());
+    }
+    let mut store =
+        PrefixedStorage::multilevel(&[PREFIX_FOLLOW_REQUESTS, followed_addr.as_slice()], storage);
+    set_bin_data(&mut store, &requester.as_slice(), &0_u64)
+}
+
+// returns the timestamp of the requester's pending follow request, if there is one
+pub fn get_follow_request<S: ReadonlyStorage>(
+    storage: &S,
+    followed_addr: &CanonicalAddr,
+    requester: &CanonicalAddr,
+) -> Option<u64> {
+    let store = ReadonlyPrefixedStorage::multilevel(
+        &[PREFIX_FOLLOW_REQUESTS, followed_addr.as_slice()],
+        storage,
+    );
+    match get_bin_data(&store, &requester.as_slice()) {
+        Ok(0_u64) | Err(_) => None,
+        Ok(timestamp) => Some(timestamp),
+    }
+}
+
+// gets pending follow requests starting from the latest requester, potentially skipping
+//   `page * page_size` requesters from the start. Also filters requests no longer pending.
+pub fn get_follow_requests<S: ReadonlyStorage>(
+    storage: &S,
+    followed_addr: &CanonicalAddr,
+    page: u32,
+    page_size: u32,
+) -> StdResult<Vec<(CanonicalAddr, u64)>> {
+    let store = ReadonlyPrefixedStorage::multilevel(
+        &[PREFIX_FOLLOW_REQUEST_LIST, followed_addr.as_slice()],
+        storage,
+    );
+    let store = if let Some(result) = AppendStore::<CanonicalAddr, _>::attach(&store) {
+        result?
+    } else {
+        return Ok(vec![]);
+    };
+    let mut requests: Vec<(CanonicalAddr, u64)> = vec![];
+    for requester in store
+        .iter()
+        .rev()
+        .skip((page * page_size) as _)
+        .take(page_size as _)
+    {
+        let requester = requester?;
+        if let Some(timestamp) = get_follow_request(storage, followed_addr, &requester) {
+            requests.push((requester, timestamp));
+        }
+    }
+    Ok(requests)
+}
+
+// gets requesters with their raw request timestamps (0 if no longer pending) in appendstore
+//   order, used for export
+pub fn get_follow_request_range<S: ReadonlyStorage>(
+    storage: &S,
+    followed_addr: &CanonicalAddr,
+    start: u32,
+    count: u32,
+) -> StdResult<Vec<(CanonicalAddr, u64)>> {
+    let store = ReadonlyPrefixedStorage::multilevel(
+        &[PREFIX_FOLLOW_REQUEST_LIST, followed_addr.as_slice()],
+        storage,
+    );
+    let store = if let Some(result) = AppendStore::<CanonicalAddr, _>::attach(&store) {
+        result?
+    } else {
+        return Ok(vec![]);
+    };
+    let request_store = ReadonlyPrefixedStorage::multilevel(
+        &[PREFIX_FOLLOW_REQUESTS, followed_addr.as_slice()],
+        storage,
+    );
+    let mut requests: Vec<(CanonicalAddr, u64)> = vec![];
+    for requester in store.iter().skip(start as _).take(count as _) {
+        let requester = requester?;
+        let timestamp = get_bin_data(&request_store, &requester.as_slice()).unwrap_or(0_u64);
+        requests.push((requester, timestamp));
+    }
+    Ok(requests)
+}
+
+// returns number of requesters including those no longer pending -- for pagination
+pub fn get_number_of_follow_requests<S: ReadonlyStorage>(
+    storage: &S,
+    followed_addr: &CanonicalAddr,
+) -> u32 {
+    let store = ReadonlyPrefixedStorage::multilevel(
+        &[PREFIX_FOLLOW_REQUEST_LIST, followed_addr.as_slice()],
+        storage,
+    );
+    if let Some(result) = AppendStore::<CanonicalAddr, _>::attach(&store) {
+        result.map(|store| store.len()).unwrap_or(0_u32)
+    } else {
+        0_u32
+    }
+}
+
 //
 // Blocked accounts
 //
 // are stored using multilevel prefixed keys:
 //     b"blocked" | {blocker canonical addr} | {blocked canonical addr} -> bool
 //
+// with a list of the addresses the blocker currently blocks:
+//     b"blocked-list" | {blocker canonical addr} | {appendstore index} -> blocked canonical addr
+//     b"blocked-link" | {blocker canonical addr} | {blocked canonical addr} -> appendstore index
+//
+// unblocking swap-removes the address from the list, like removing a follow relation, so the
+//   list is in block order only until the first unblock.
+//
+// and the progress of a Block that purges the blocked account's comments and ratings, kept
+//   until every one of the blocker's fardels has been checked:
+//     b"block-purge" | {blocker canonical addr} | {blocked canonical addr} -> BlockPurge
+//
 
 pub fn store_account_block<S: Storage>(
     storage: &mut S,
@@ -424,7 +766,203 @@ pub fn store_account_block<S: Storage>(
 ) -> StdResult<()> {
     let mut store =
         PrefixedStorage::multilevel(&[PREFIX_BLOCKED, blocker_addr.as_slice()], storage);
-    set_bin_data(&mut store, &blocked_addr.as_slice(), &blocked)
+    set_bin_data(&mut store, &blocked_addr.as_slice(), &blocked)?;
+    if blocked {
+        add_to_blocked_list(storage, blocker_addr, blocked_addr)
+    } else {
+        remove_from_blocked_list(storage, blocker_addr, blocked_addr)
+    }
+}
+
+// index of the next of the blocker's fardels to purge, in the order they were carried, and the
+//   next comment to check on it
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct BlockPurge {
+    pub fardel: u32,
+    pub comment: u32,
+}
+
+pub fn get_block_purge<S: ReadonlyStorage>(
+    storage: &S,
+    blocker_addr: &CanonicalAddr,
+    blocked_addr: &CanonicalAddr,
+) -> BlockPurge {
+    let store = ReadonlyPrefixedStorage::multilevel(
+        &[PREFIX_BLOCK_PURGE, blocker_addr.as_slice()],
+        storage,
+    );
+    get_bin_data(&store, blocked_addr.as_slice()).unwrap_or_default()
+}
+
+// a default (finished or not started) purge is not stored
+pub fn set_block_purge<S: Storage>(
+    storage: &mut S,
+    blocker_addr: &CanonicalAddr,
+    blocked_addr: &CanonicalAddr,
+    progress: &BlockPurge,
+) -> StdResult<()> {
+    let mut store =
+        PrefixedStorage::multilevel(&[PREFIX_BLOCK_PURGE, blocker_addr.as_slice()], storage);
+    if *progress == BlockPurge::default() {
+        store.remove(blocked_addr.as_slice());
+        return Ok(());
+    }
+    set_bin_data(&mut store, blocked_addr.as_slice(), progress)
+}
+
+fn add_to_blocked_list<S: Storage>(
+    storage: &mut S,
+    blocker_addr: &CanonicalAddr,
+    blocked_addr: &CanonicalAddr,
+) -> StdResult<()> {
+    let link_store = ReadonlyPrefixedStorage::multilevel(
+        &[PREFIX_BLOCKED_LINK, blocker_addr.as_slice()],
+        storage,
+    );
+    // already listed
+    let existing: StdResult<u32> = get_bin_data(&link_store, blocked_addr.as_slice());
+    if existing.is_ok() {
+        return Ok(());
+    }
+
+    let idx = {
+        let mut list_store =
+            PrefixedStorage::multilevel(&[PREFIX_BLOCKED_LIST, blocker_addr.as_slice()], storage);
+        let mut list_store = AppendStoreMut::<CanonicalAddr, _>::attach_or_create(&mut list_store)?;
+        list_store.push(blocked_addr)?;
+        list_store.len() - 1
+    };
+
+    let mut link_store =
+        PrefixedStorage::multilevel(&[PREFIX_BLOCKED_LINK, blocker_addr.as_slice()], storage);
+    set_bin_data(&mut link_store, blocked_addr.as_slice(), &idx)
+}
+
+fn remove_from_blocked_list<S: Storage>(
+    storage: &mut S,
+    blocker_addr: &CanonicalAddr,
+    blocked_addr: &CanonicalAddr,
+) -> StdResult<()> {
+    let link_store = ReadonlyPrefixedStorage::multilevel(
+        &[PREFIX_BLOCKED_LINK, blocker_addr.as_slice()],
+        storage,
+    );
+    // ignore if not listed
+    let idx: u32 = match get_bin_data(&link_store, blocked_addr.as_slice()) {
+        Ok(idx) => idx,
+        Err(_) => return Ok(()),
+    };
+
+    let moved = {
+        let mut list_store =
+            PrefixedStorage::multilevel(&[PREFIX_BLOCKED_LIST, blocker_addr.as_slice()], storage);
+        let mut list_store = AppendStoreMut::<CanonicalAddr, _>::attach_or_create(&mut list_store)?;
+        if idx >= list_store.len() {
+            return Err(StdError::generic_err("Blocked index out of range."));
+        }
+        let last: CanonicalAddr = list_store.pop()?;
+        if idx < list_store.len() {
+            list_store.set_at(idx, &last)?;
+            Some(last)
+        } else {
+            None
+        }
+    };
+
+    let mut link_store =
+        PrefixedStorage::multilevel(&[PREFIX_BLOCKED_LINK, blocker_addr.as_slice()], storage);
+    link_store.remove(blocked_addr.as_slice());
+    if let Some(moved) = moved {
+        set_bin_data(&mut link_store, moved.as_slice(), &idx)?;
+    }
+
+    Ok(())
+}
+
+// rebuilds the blocker's list from the b"blocked" map, used by Migrate
+//   blocks can only be made by handle, so every blocked address is a registered account.
+//   Each entry popped and each account checked costs one unit of budget, progress is kept in
+//   the cursor. Returns true once the list is rebuilt.
+pub fn backfill_blocked_list<S: Storage>(
+    storage: &mut S,
+    blocker_addr: &CanonicalAddr,
+    progress: &mut BlockBackfill,
+    budget: &mut u32,
+) -> StdResult<bool> {
+    let total_accounts = get_total_number_registered_accounts(storage)?;
+    while *budget > 0 {
+        if !progress.cleared {
+            let popped = {
+                let mut list_store = PrefixedStorage::multilevel(
+                    &[PREFIX_BLOCKED_LIST, blocker_addr.as_slice()],
+                    storage,
+                );
+                let mut list_store =
+                    AppendStoreMut::<CanonicalAddr, _>::attach_or_create(&mut list_store)?;
+                if list_store.is_empty() {
+                    None
+                } else {
+                    Some(list_store.pop()?)
+                }
+            };
+            match popped {
+                Some(popped) => {
+                    let mut link_store = PrefixedStorage::multilevel(
+                        &[PREFIX_BLOCKED_LINK, blocker_addr.as_slice()],
+                        storage,
+                    );
+                    link_store.remove(popped.as_slice());
+                }
+                None => {
+                    progress.cleared = true;
+                    continue;
+                }
+            }
+        } else if progress.candidate < total_accounts {
+            let candidate = get_registered_address(storage, progress.candidate)?;
+            if is_blocked_by(storage, blocker_addr, &candidate) {
+                add_to_blocked_list(storage, blocker_addr, &candidate)?;
+            }
+            progress.candidate += 1;
+        } else {
+            *progress = BlockBackfill::default();
+            return Ok(true);
+        }
+        *budget -= 1;
+    }
+
+    Ok(false)
+}
+
+// gets addresses the blocker currently blocks
+pub fn get_blocked_list<S: ReadonlyStorage>(
+    storage: &S,
+    blocker_addr: &CanonicalAddr,
+    start: u32,
+    count: u32,
+) -> StdResult<Vec<CanonicalAddr>> {
+    let store = ReadonlyPrefixedStorage::multilevel(
+        &[PREFIX_BLOCKED_LIST, blocker_addr.as_slice()],
+        storage,
+    );
+    let store = if let Some(result) = AppendStore::<CanonicalAddr, _>::attach(&store) {
+        result?
+    } else {
+        return Ok(vec![]);
+    };
+    store.iter().skip(start as _).take(count as _).collect()
+}
+
+pub fn get_blocked_list_len<S: ReadonlyStorage>(storage: &S, blocker_addr: &CanonicalAddr) -> u32 {
+    let store = ReadonlyPrefixedStorage::multilevel(
+        &[PREFIX_BLOCKED_LIST, blocker_addr.as_slice()],
+        storage,
+    );
+    if let Some(result) = AppendStore::<CanonicalAddr, _>::attach(&store) {
+        result.map(|store| store.len()).unwrap_or(0_u32)
+    } else {
+        0_u32
+    }
 }
 
 // returns true if blocked_addr is blocked by blocker_addr
@@ -438,6 +976,44 @@ pub fn is_blocked_by<S: ReadonlyStorage>(
     get_bin_data(&storage, &blocked_addr.as_slice()).unwrap_or_else(|_| false)
 }
 
+//
+// Muted accounts and tags
+//   b"mutes" | {owner canonical addr} -> StoredMutes
+//
+// mutes only filter what the owner sees in authenticated list queries, muted accounts are not
+//   affected and cannot tell they have been muted. Tags are stored lowercase.
+//
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct StoredMutes {
+    pub accounts: Vec<CanonicalAddr>,
+    pub tags: Vec<String>,
+}
+
+impl StoredMutes {
+    // returns true if a fardel with the given carrier and tags should be filtered out
+    pub fn hides(&self, carrier: &CanonicalAddr, tags: &[String]) -> bool {
+        self.accounts.contains(carrier)
+            || tags
+                .iter()
+                .any(|tag| self.tags.contains(&tag.to_lowercase()))
+    }
+}
+
+pub fn set_mutes<S: Storage>(
+    storage: &mut S,
+    owner: &CanonicalAddr,
+    mutes: &StoredMutes,
+) -> StdResult<()> {
+    let mut storage = PrefixedStorage::new(PREFIX_MUTES, storage);
+    set_bin_data(&mut storage, owner.as_slice(), mutes)
+}
+
+pub fn get_mutes<S: ReadonlyStorage>(storage: &S, owner: &CanonicalAddr) -> StoredMutes {
+    let storage = ReadonlyPrefixedStorage::new(PREFIX_MUTES, storage);
+    get_bin_data(&storage, owner.as_slice()).unwrap_or_default()
+}
+
 //
 // Fardel rating and comments
 //   each user can only upvote or downvote a fardel once, and they must have unpacked it
@@ -500,6 +1076,11 @@ pub fn subtract_upvote_fardel<S: Storage>(store: &mut S, fardel_id: u128) -> Std
     set_bin_data(&mut store, &fardel_id.to_be_bytes(), &(upvotes - 1))
 }
 
+pub fn set_upvotes<S: Storage>(store: &mut S, fardel_id: u128, upvotes: u32) -> StdResult<()> {
+    let mut store = PrefixedStorage::new(PREFIX_UPVOTES, store);
+    set_bin_data(&mut store, &fardel_id.to_be_bytes(), &upvotes)
+}
+
 pub fn get_upvotes<S: ReadonlyStorage>(store: &S, fardel_id: u128) -> u32 {
     let store = ReadonlyPrefixedStorage::new(PREFIX_UPVOTES, store);
     get_bin_data(&store, &fardel_id.to_be_bytes()).unwrap_or_else(|_| 0_u32)
@@ -517,6 +1098,11 @@ pub fn subtract_downvote_fardel<S: Storage>(store: &mut S, fardel_id: u128) -> S
     set_bin_data(&mut store, &fardel_id.to_be_bytes(), &(downvotes - 1))
 }
 
+pub fn set_downvotes<S: Storage>(store: &mut S, fardel_id: u128, downvotes: u32) -> StdResult<()> {
+    let mut store = PrefixedStorage::new(PREFIX_DOWNVOTES, store);
+    set_bin_data(&mut store, &fardel_id.to_be_bytes(), &downvotes)
+}
+
 pub fn get_downvotes<S: ReadonlyStorage>(store: &S, fardel_id: u128) -> u32 {
     let store = ReadonlyPrefixedStorage::new(PREFIX_DOWNVOTES, store);
     get_bin_data(&store, &fardel_id.to_be_bytes()).unwrap_or_else(|_| 0_u32)
@@ -599,6 +1185,31 @@ pub fn get_comments<S: ReadonlyStorage>(
     comments
 }
 
+// gets raw comments in appendstore order with their deleted status, used for export
+pub fn get_comments_range<S: ReadonlyStorage>(
+    storage: &S,
+    fardel_id: u128,
+    start: u32,
+    count: u32,
+) -> StdResult<Vec<(Comment, bool)>> {
+    let store =
+        ReadonlyPrefixedStorage::multilevel(&[PREFIX_COMMENTS, &fardel_id.to_be_bytes()], storage);
+    let store = if let Some(result) = AppendStore::<Comment, _>::attach(&store) {
+        result?
+    } else {
+        return Ok(vec![]);
+    };
+    store
+        .iter()
+        .enumerate()
+        .skip(start as _)
+        .take(count as _)
+        .map(|(idx, comment)| {
+            comment.map(|comment| (comment, comment_is_deleted(storage, fardel_id, idx as u32)))
+        })
+        .collect()
+}
+
 // get total number of comments for a fardel
 pub fn get_number_of_comments<S: ReadonlyStorage>(storage: &S, fardel_id: u128) -> u32 {
     let store =
@@ -637,6 +1248,46 @@ pub fn get_comment_by_id<S: ReadonlyStorage>(
     store.get_at(comment_id)
 }
 
+// marks the comments the commenter made on the fardel as deleted, starting from comment *start.
+//   Each comment checked costs one unit of budget and moves start on. Returns true once every
+//   comment has been checked.
+pub fn purge_comments_by<S: Storage>(
+    storage: &mut S,
+    fardel_id: u128,
+    commenter: &CanonicalAddr,
+    start: &mut u32,
+    budget: &mut u32,
+) -> StdResult<bool> {
+    let number_of_comments = get_number_of_comments(storage, fardel_id);
+    let count = (*budget).min(number_of_comments.saturating_sub(*start));
+    let comments = get_comments_range(storage, fardel_id, *start, count)?;
+    for (comment, deleted) in comments.iter() {
+        if !deleted && comment.commenter == *commenter {
+            delete_comment(storage, fardel_id, *start)?;
+        }
+        *start += 1;
+        *budget -= 1;
+    }
+    Ok(*start >= number_of_comments)
+}
+
+// removes the rater's rating of the fardel along with its vote, if they rated it
+pub fn remove_rating_by<S: Storage>(
+    storage: &mut S,
+    fardel_id: u128,
+    rater: &CanonicalAddr,
+) -> StdResult<()> {
+    if let Ok(rating) = get_rating(storage, rater, fardel_id) {
+        remove_rated(storage, rater, fardel_id);
+        if rating {
+            subtract_upvote_fardel(storage, fardel_id)?;
+        } else {
+            subtract_downvote_fardel(storage, fardel_id)?;
+        }
+    }
+    Ok(())
+}
+
 pub fn delete_comment<S: Storage>(
     storage: &mut S,
     fardel_id: u128,
@@ -1,5 +1,7 @@
 use crate::msg::Fee;
-use cosmwasm_std::{CanonicalAddr, ReadonlyStorage, StdError, StdResult, Storage, Uint128};
+use cosmwasm_std::{
+    CanonicalAddr, HumanAddr, ReadonlyStorage, StdError, StdResult, Storage, Uint128,
+};
 use cosmwasm_storage::{PrefixedStorage, ReadonlyPrefixedStorage};
 use schemars::JsonSchema;
 use serde::de::DeserializeOwned;
@@ -9,12 +11,32 @@ use std::any::type_name;
 // Globals
 pub const PREFIX_CONFIG: &[u8] = b"config";
 pub const KEY_CONSTANTS: &[u8] = b"constants";
-// to change admin SetAdmin must be called 3 times with the same address
-//   to help prevent accidental change to wrong address
-pub const KEY_NEW_ADMIN: &[u8] = b"new-admin";
-pub const KEY_NEW_ADMIN_COUNT: &[u8] = b"new-admin-count";
+// to change admin the current admin proposes a new address with ChangeAdmin
+//   and the proposed address must send AcceptAdmin before the deadline
+pub const KEY_PENDING_ADMIN: &[u8] = b"pending-admin";
 pub const KEY_FARDEL_COUNT: &[u8] = b"fardel-count";
 pub const KEY_FROZEN: &[u8] = b"frozen";
+pub const KEY_PAUSE_FLAGS: &[u8] = b"pause-flags";
+pub const KEY_EMERGENCY: &[u8] = b"emergency";
+// storage layout version and progress of an in-progress migration
+pub const KEY_SCHEMA_VERSION: &[u8] = b"schema-version";
+pub const KEY_MIGRATION_CURSOR: &[u8] = b"migration-cursor";
+// next global fardel id for SweepExpired to check
+pub const KEY_SWEEP_CURSOR: &[u8] = b"sweep-cursor";
+// export/import to a new contract instance
+pub const KEY_EXPORT_KEY: &[u8] = b"export-key";
+// next registered account StartExport checks for pending unpacks
+pub const KEY_EXPORT_CURSOR: &[u8] = b"export-cursor";
+pub const KEY_IMPORT_SOURCE: &[u8] = b"import-source";
+pub const KEY_IMPORT_KEY: &[u8] = b"import-key";
+// most recent block time seen by handle, used for time-dependent checks in queries
+pub const KEY_BLOCK_TIME: &[u8] = b"block-time";
+// this contract's address, queries need it to check query permits
+pub const KEY_CONTRACT_ADDRESS: &[u8] = b"contract-address";
+// uscrt required with each direct message
+pub const KEY_MESSAGE_FEE: &[u8] = b"message-fee";
+// normalized handles only the admin can register
+pub const KEY_RESERVED_HANDLES: &[u8] = b"reserved-handles";
 
 // Fardel
 pub const PREFIX_FARDELS: &[u8] = b"fardel";
@@ -24,7 +46,17 @@ pub const PREFIX_HASH_ID_MAPPINGS: &[u8] = b"hash-to-id";
 pub const PREFIX_SEALED: &[u8] = b"sealed";
 pub const PREFIX_HIDDEN: &[u8] = b"hidden";
 pub const PREFIX_REMOVED: &[u8] = b"removed";
+pub const PREFIX_PUBLISH_TIME: &[u8] = b"publish-time";
+pub const PREFIX_AUDIENCE: &[u8] = b"audience";
 pub const PREFIX_FARDEL_NUM_UNPACKS: &[u8] = b"fardel-unpack-count";
+pub const PREFIX_FARDEL_KEYS: &[u8] = b"fardel-key";
+pub const PREFIX_FARDEL_REVISIONS: &[u8] = b"fardel-revisions";
+pub const PREFIX_FARDEL_PARTS: &[u8] = b"fardel-parts";
+pub const PREFIX_RESTOCKS: &[u8] = b"restocks";
+pub const PREFIX_PART_OF: &[u8] = b"part-of";
+pub const PREFIX_PART_COUNT: &[u8] = b"part-count";
+// how many of an owner's fardels Migrate has tagged with their layout version
+pub const PREFIX_TAGGED_FARDELS: &[u8] = b"fardels-tagged";
 
 // Fardel unpacking
 pub const PREFIX_UNPACKED: &[u8] = b"unpacked";
@@ -34,6 +66,9 @@ pub const PREFIX_PENDING_APPROVAL: &[u8] = b"pending";
 pub const PREFIX_PENDING_START: &[u8] = b"pending-start";
 // pending unpacks indexed by the unpacker of the fardel
 pub const PREFIX_ID_PENDING_UNPACKED_MAPPINGS: &[u8] = b"id-to-pending";
+// pubkeys sent with pending unpacks, and the contents encrypted to them once approved
+pub const PREFIX_PENDING_PUBKEYS: &[u8] = b"pending-pubkey";
+pub const PREFIX_ENCRYPTED_UNPACKS: &[u8] = b"encrypted-unpack";
 
 // Fardel rating/comments
 pub const PREFIX_RATED: &[u8] = b"rated";
@@ -48,27 +83,56 @@ pub const PREFIX_FOLLOWERS: &[u8] = b"followers";
 pub const PREFIX_LINK: &[u8] = b"link";
 pub const PREFIX_VEC: &[u8] = b"vec";
 pub const PREFIX_FOLLOWER_COUNT: &[u8] = b"follower-count";
+pub const PREFIX_FOLLOW_REQUESTS: &[u8] = b"follow-requests";
+pub const PREFIX_FOLLOW_REQUEST_LIST: &[u8] = b"follow-request-list";
 
 // Blocked
 pub const PREFIX_BLOCKED: &[u8] = b"blocked";
+pub const PREFIX_BLOCKED_LIST: &[u8] = b"blocked-list";
+pub const PREFIX_BLOCKED_LINK: &[u8] = b"blocked-link";
+pub const PREFIX_BLOCK_PURGE: &[u8] = b"block-purge";
+
+// Muted
+pub const PREFIX_MUTES: &[u8] = b"mutes";
 
 // Accounts
 pub const PREFIX_ACCOUNTS: &[u8] = b"account";
 pub const PREFIX_ACCOUNT_THUMBNAIL_IMGS: &[u8] = b"account-img";
+// accounts Migrate has tagged with their layout version
+pub const PREFIX_TAGGED_ACCOUNTS: &[u8] = b"account-tagged";
 pub const PREFIX_HANDLES: &[u8] = b"handle";
+pub const PREFIX_HANDLE_COLLISIONS: &[u8] = b"handle-collisions";
+pub const PREFIX_RELEASED_HANDLES: &[u8] = b"released-handles";
+pub const PREFIX_HANDLE_HISTORY: &[u8] = b"handle-history";
 pub const PREFIX_VIEWING_KEY: &[u8] = b"viewingkey";
+pub const PREFIX_NAMED_VIEWING_KEYS: &[u8] = b"named-viewing-keys";
+pub const PREFIX_DELEGATES: &[u8] = b"delegates";
+pub const PREFIX_DELEGATE_LIST: &[u8] = b"delegate-list";
+pub const PREFIX_REVOKED_PERMITS: &[u8] = b"revoked-permits";
 pub const PREFIX_DEACTIVATED: &[u8] = b"deactived";
+pub const PREFIX_PRIVATE_ACCOUNTS: &[u8] = b"private-account";
 
 // Registered addresses
 pub const PREFIX_REGISTERED_ADDRESSES: &[u8] = b"addresses";
 
 // Banned accounts
 pub const PREFIX_BANNED: &[u8] = b"banned";
+pub const PREFIX_BAN_LIST: &[u8] = b"ban-list";
 
 // Completed transactions
 pub const PREFIX_SALE_TX: &[u8] = b"sale-tx";
 pub const PREFIX_PURCHASE_TX: &[u8] = b"purchase-tx";
 
+// Direct messages
+pub const PREFIX_INBOX: &[u8] = b"inbox";
+pub const PREFIX_OUTBOX: &[u8] = b"outbox";
+pub const PREFIX_MESSAGE_COUNT: &[u8] = b"message-count";
+
+// Notifications
+pub const PREFIX_NOTIFICATIONS: &[u8] = b"notifications";
+pub const PREFIX_NOTIFICATIONS_READ: &[u8] = b"notifications-read";
+pub const PREFIX_NOTIFICATION_OPT_OUTS: &[u8] = b"notification-opt-outs";
+
 //
 // CONFIG
 //
@@ -177,22 +241,35 @@ impl StoredFee {
 }
 
 //
-// New admin
+// Pending admin proposal
+//   b"pending-admin" -> PendingAdmin
 //
-pub fn set_new_admin<S: Storage>(storage: &mut S, new_admin: &CanonicalAddr) -> StdResult<()> {
-    set_bin_data(storage, KEY_NEW_ADMIN, &new_admin)
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct PendingAdmin {
+    pub address: CanonicalAddr,
+    // block time after which the proposal can no longer be accepted
+    pub deadline: u64,
 }
 
-pub fn get_new_admin<S: Storage>(storage: &S) -> StdResult<CanonicalAddr> {
-    get_bin_data(storage, KEY_NEW_ADMIN)
+pub fn set_pending_admin<S: Storage>(
+    storage: &mut S,
+    address: &CanonicalAddr,
+    deadline: u64,
+) -> StdResult<()> {
+    let pending_admin = PendingAdmin {
+        address: address.clone(),
+        deadline,
+    };
+    set_bin_data(storage, KEY_PENDING_ADMIN, &pending_admin)
 }
 
-pub fn set_new_admin_count<S: Storage>(storage: &mut S, new_admin_count: u8) -> StdResult<()> {
-    set_bin_data(storage, KEY_NEW_ADMIN_COUNT, &new_admin_count)
+pub fn get_pending_admin<S: ReadonlyStorage>(storage: &S) -> StdResult<PendingAdmin> {
+    get_bin_data(storage, KEY_PENDING_ADMIN)
 }
 
-pub fn get_new_admin_count<S: Storage>(storage: &S) -> u8 {
-    get_bin_data(storage, KEY_NEW_ADMIN_COUNT).unwrap_or_else(|_| 0_u8)
+pub fn clear_pending_admin<S: Storage>(storage: &mut S) {
+    storage.remove(KEY_PENDING_ADMIN)
 }
 
 //
@@ -206,6 +283,177 @@ pub fn is_frozen<S: ReadonlyStorage>(storage: &S) -> bool {
     get_bin_data(storage, KEY_FROZEN).unwrap_or_else(|_| false)
 }
 
+//
+// Schema version
+//   contracts initialized before versioning was added have no stored version and are v1
+//   v2 tags fardels and accounts with their layout version, compacts follow lists (which
+//     kept removed relations as inactive entries), keys the handle map by normalized handle
+//     (it was case-sensitive) and takes reserved handles away from accounts other than the
+//     admin, rebuilds blocked lists to hold only current blocks, and adds accounts banned
+//     before the ban list existed to it. Handles other than Migrate and the freeze and
+//     admin change functions are rejected until Migrate is done.
+//
+pub const CURRENT_SCHEMA_VERSION: u16 = 2;
+
+pub fn set_schema_version<S: Storage>(storage: &mut S, version: u16) -> StdResult<()> {
+    set_bin_data(storage, KEY_SCHEMA_VERSION, &version)
+}
+
+pub fn get_schema_version<S: ReadonlyStorage>(storage: &S) -> u16 {
+    get_bin_data(storage, KEY_SCHEMA_VERSION).unwrap_or_else(|_| 1_u16)
+}
+
+// next fardel global id and registered address indexes to be upgraded by Migrate
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct MigrationCursor {
+    pub fardel: u128,
+    pub account: u32,
+    pub follows: u32,
+    pub follow_entries: FollowCompaction,
+    pub handles: u32,
+    pub blocks: u32,
+    pub block_entries: BlockBackfill,
+    pub bans: u32,
+}
+
+// progress through the follow lists of the account at MigrationCursor::follows
+//   read is the next entry to look at, write is where the next active entry goes
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct FollowCompaction {
+    pub followers: bool,
+    pub read: u32,
+    pub write: u32,
+}
+
+// progress through the blocked list of the account at MigrationCursor::blocks
+//   the old list is emptied first, then every registered account is checked in order
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct BlockBackfill {
+    pub cleared: bool,
+    pub candidate: u32,
+}
+
+pub fn set_migration_cursor<S: Storage>(
+    storage: &mut S,
+    cursor: &MigrationCursor,
+) -> StdResult<()> {
+    set_bin_data(storage, KEY_MIGRATION_CURSOR, cursor)
+}
+
+pub fn get_migration_cursor<S: ReadonlyStorage>(storage: &S) -> MigrationCursor {
+    get_bin_data(storage, KEY_MIGRATION_CURSOR).unwrap_or_default()
+}
+
+pub fn set_sweep_cursor<S: Storage>(storage: &mut S, cursor: u128) -> StdResult<()> {
+    set_bin_data(storage, KEY_SWEEP_CURSOR, &cursor)
+}
+
+pub fn get_sweep_cursor<S: ReadonlyStorage>(storage: &S) -> u128 {
+    get_bin_data(storage, KEY_SWEEP_CURSOR).unwrap_or_else(|_| 0_u128)
+}
+
+//
+// Export / import
+//   the exporting contract generates a random key and sends it directly to the importing
+//   contract, so private data can be exported encrypted without the admin ever seeing the key
+//
+pub fn set_export_key<S: Storage>(storage: &mut S, key: &[u8]) -> StdResult<()> {
+    set_bin_data(storage, KEY_EXPORT_KEY, &key.to_vec())
+}
+
+pub fn get_export_key<S: ReadonlyStorage>(storage: &S) -> StdResult<Vec<u8>> {
+    get_bin_data(storage, KEY_EXPORT_KEY)
+        .map_err(|_| StdError::generic_err("Export has not been started."))
+}
+
+pub fn set_export_cursor<S: Storage>(storage: &mut S, cursor: u32) -> StdResult<()> {
+    set_bin_data(storage, KEY_EXPORT_CURSOR, &cursor)
+}
+
+pub fn get_export_cursor<S: ReadonlyStorage>(storage: &S) -> u32 {
+    get_bin_data(storage, KEY_EXPORT_CURSOR).unwrap_or_else(|_| 0_u32)
+}
+
+pub fn set_import_source<S: Storage>(storage: &mut S, source: &CanonicalAddr) -> StdResult<()> {
+    set_bin_data(storage, KEY_IMPORT_SOURCE, source)
+}
+
+pub fn get_import_source<S: ReadonlyStorage>(storage: &S) -> StdResult<CanonicalAddr> {
+    get_bin_data(storage, KEY_IMPORT_SOURCE)
+}
+
+pub fn set_import_key<S: Storage>(storage: &mut S, key: &[u8]) -> StdResult<()> {
+    set_bin_data(storage, KEY_IMPORT_KEY, &key.to_vec())
+}
+
+pub fn get_import_key<S: ReadonlyStorage>(storage: &S) -> StdResult<Vec<u8>> {
+    get_bin_data(storage, KEY_IMPORT_KEY)
+        .map_err(|_| StdError::generic_err("Import key has not been received."))
+}
+
+// unfreezing the importing contract ends the import, so nothing can be replayed into it once
+//   users are writing to it
+pub fn clear_import_key<S: Storage>(storage: &mut S) {
+    storage.remove(KEY_IMPORT_KEY)
+}
+
+//
+// Emergency mode
+//   like frozen, but users can still cancel pending unpacks to get their escrow back
+//
+pub fn set_emergency<S: Storage>(storage: &mut S, value: bool) -> StdResult<()> {
+    set_bin_data(storage, KEY_EMERGENCY, &value)
+}
+
+pub fn is_emergency<S: ReadonlyStorage>(storage: &S) -> bool {
+    get_bin_data(storage, KEY_EMERGENCY).unwrap_or_else(|_| false)
+}
+
+//
+// Pause flags
+//   finer grained than frozen, each flag pauses one group of non-admin handle functions
+//     carry: CarryFardel, ReviseFardel, SchedulePublish, SetAudience, ExtendSealTime, Restock
+//     unpack: UnpackFardel, ApprovePendingUnpacks (CancelPending is always allowed)
+//     social: Follow, Unfollow, RemoveFollower, Mute, Unmute, RespondFollowRequest, RateFardel,
+//       UnrateFardel, CommentOnFardel, DeleteComment, SendMessage, DeleteMessage
+//
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct PauseFlags {
+    pub carry: bool,
+    pub unpack: bool,
+    pub social: bool,
+}
+
+pub fn set_pause_flags<S: Storage>(storage: &mut S, flags: &PauseFlags) -> StdResult<()> {
+    set_bin_data(storage, KEY_PAUSE_FLAGS, flags)
+}
+
+pub fn get_pause_flags<S: ReadonlyStorage>(storage: &S) -> PauseFlags {
+    get_bin_data(storage, KEY_PAUSE_FLAGS).unwrap_or_default()
+}
+
+//
+// Last seen block time
+//   queries do not have access to env, so time-dependent checks in queries use the block time
+//   recorded by the last handle. Handles have the block time, so ban checks in handles use it
+//   directly (see is_banned_at).
+//
+pub fn set_block_time<S: Storage>(storage: &mut S, time: u64) -> StdResult<()> {
+    set_bin_data(storage, KEY_BLOCK_TIME, &time)
+}
+
+pub fn get_block_time<S: ReadonlyStorage>(storage: &S) -> u64 {
+    get_bin_data(storage, KEY_BLOCK_TIME).unwrap_or_else(|_| 0_u64)
+}
+
+pub fn set_contract_address<S: Storage>(storage: &mut S, address: &HumanAddr) -> StdResult<()> {
+    set_bin_data(storage, KEY_CONTRACT_ADDRESS, address)
+}
+
+pub fn get_contract_address<S: ReadonlyStorage>(storage: &S) -> StdResult<HumanAddr> {
+    get_bin_data(storage, KEY_CONTRACT_ADDRESS)
+}
+
 //
 // Bin data storage setters and getters
 //
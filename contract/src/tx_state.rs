@@ -113,6 +113,22 @@ pub fn get_number_of_sales<S: ReadonlyStorage>(
     };
 }
 
+// gets raw sale txs in appendstore order, used for export
+pub fn get_stored_sale_txs<S: ReadonlyStorage>(
+    storage: &S,
+    owner: &CanonicalAddr,
+    start: u32,
+    count: u32,
+) -> StdResult<Vec<StoredSaleTx>> {
+    let store = ReadonlyPrefixedStorage::multilevel(&[PREFIX_SALE_TX, owner.as_slice()], storage);
+    let store = if let Some(result) = AppendStore::<StoredSaleTx, _>::attach(&store) {
+        result?
+    } else {
+        return Ok(vec![]);
+    };
+    store.iter().skip(start as _).take(count as _).collect()
+}
+
 //
 // Purchase transaction record
 //
@@ -224,4 +240,20 @@ pub fn get_number_of_purchases<S: ReadonlyStorage>(
     } else {
         return Ok(0_u32);
     };
-}
\ No newline at end of file
+}
+// gets raw purchase txs in appendstore order, used for export
+pub fn get_stored_purchase_txs<S: ReadonlyStorage>(
+    storage: &S,
+    unpacker: &CanonicalAddr,
+    start: u32,
+    count: u32,
+) -> StdResult<Vec<StoredPurchaseTx>> {
+    let store =
+        ReadonlyPrefixedStorage::multilevel(&[PREFIX_PURCHASE_TX, unpacker.as_slice()], storage);
+    let store = if let Some(result) = AppendStore::<StoredPurchaseTx, _>::attach(&store) {
+        result?
+    } else {
+        return Ok(vec![]);
+    };
+    store.iter().skip(start as _).take(count as _).collect()
+}
@@ -1,8 +1,11 @@
+use crate::fardel_state::get_number_of_revisions;
+use crate::msg::EncryptedContents;
 use crate::state::{
-    get_bin_data, set_bin_data, PREFIX_ID_PENDING_UNPACKED_MAPPINGS, PREFIX_ID_UNPACKED_MAPPINGS,
-    PREFIX_PENDING_APPROVAL, PREFIX_PENDING_START, PREFIX_UNPACKED,
+    get_bin_data, set_bin_data, PREFIX_ENCRYPTED_UNPACKS, PREFIX_ID_PENDING_UNPACKED_MAPPINGS,
+    PREFIX_ID_UNPACKED_MAPPINGS, PREFIX_PENDING_APPROVAL, PREFIX_PENDING_PUBKEYS,
+    PREFIX_PENDING_START, PREFIX_UNPACKED,
 };
-use cosmwasm_std::{CanonicalAddr, Coin, ReadonlyStorage, StdError, StdResult, Storage};
+use cosmwasm_std::{Binary, CanonicalAddr, Coin, ReadonlyStorage, StdError, StdResult, Storage};
 use cosmwasm_storage::{PrefixedStorage, ReadonlyPrefixedStorage};
 use secret_toolkit::storage::{AppendStore, AppendStoreMut};
 use serde::{Deserialize, Serialize};
@@ -108,6 +111,23 @@ pub fn get_unpacked_by_unpacker<S: ReadonlyStorage>(
     unpacked
 }
 
+// gets unpacked fardels in appendstore order, used for export
+pub fn get_unpacked_range<S: ReadonlyStorage>(
+    storage: &S,
+    unpacker: &CanonicalAddr,
+    start: u32,
+    count: u32,
+) -> StdResult<Vec<UnpackedFardel>> {
+    let storage =
+        ReadonlyPrefixedStorage::multilevel(&[PREFIX_UNPACKED, unpacker.as_slice()], storage);
+    let storage = if let Some(result) = AppendStore::<UnpackedFardel, _>::attach(&storage) {
+        result?
+    } else {
+        return Ok(vec![]);
+    };
+    storage.iter().skip(start as _).take(count as _).collect()
+}
+
 // gets number of unpacked fardels for a given unpacker canonical address
 pub fn get_number_of_unpacked_by_unpacker<S: ReadonlyStorage>(
     storage: &S,
@@ -274,6 +294,62 @@ pub fn get_pending_unpacked_status_by_fardel_id<S: ReadonlyStorage>(
     })
 }
 
+// cancels up to `number` pending unpacks for the owner starting from the pending start index,
+//   and moves the start index past them. Returns the unpacks that were canceled so that
+//   their escrow can be refunded.
+pub fn cancel_pending_unpacks_from_start<S: Storage>(
+    storage: &mut S,
+    owner: &CanonicalAddr,
+    number: u32,
+) -> StdResult<Vec<PendingUnpackApproval>> {
+    let start = get_pending_start(storage, owner);
+    let pending_unpacks = get_pending_approvals_from_start(storage, owner, number)?;
+    let mut canceled: Vec<PendingUnpackApproval> = vec![];
+
+    for (offset, pending_unpack) in pending_unpacks.iter().enumerate() {
+        if pending_unpack.canceled {
+            continue;
+        }
+        let idx = start + offset as u32;
+        let mut pending_unpack = pending_unpack.clone();
+        pending_unpack.canceled = true;
+        let mut store =
+            PrefixedStorage::multilevel(&[PREFIX_PENDING_APPROVAL, owner.as_slice()], storage);
+        let mut store = AppendStoreMut::<PendingUnpackApproval, _>::attach_or_create(&mut store)?;
+        store.set_at(idx, &pending_unpack)?;
+        map_global_id_to_pending_unpacked_by_unpacker(
+            storage,
+            pending_unpack.fardel_id,
+            &pending_unpack.unpacker,
+            idx,
+            false,
+        )?;
+        canceled.push(pending_unpack);
+    }
+
+    set_pending_start(storage, owner, start + pending_unpacks.len() as u32)?;
+    Ok(canceled)
+}
+
+// gets number of pending unpacks for the owner that have not been processed yet, including canceled
+pub fn get_number_of_pending_from_start<S: ReadonlyStorage>(
+    storage: &S,
+    owner: &CanonicalAddr,
+) -> StdResult<u32> {
+    let start = get_pending_start(storage, owner);
+    let store =
+        ReadonlyPrefixedStorage::multilevel(&[PREFIX_PENDING_APPROVAL, owner.as_slice()], storage);
+
+    // Try to access the storage of pending unpacks for the account.
+    // If it doesn't exist yet, return 0.
+    let store = if let Some(result) = AppendStore::<PendingUnpackApproval, _>::attach(&store) {
+        result?
+    } else {
+        return Ok(0_u32);
+    };
+    Ok(store.len().saturating_sub(start))
+}
+
 pub fn cancel_pending_unpack<S: Storage>(
     storage: &mut S,
     owner: &CanonicalAddr,
@@ -306,3 +382,78 @@ pub fn cancel_pending_unpack<S: Storage>(
         ));
     }
 }
+
+//
+// Pubkeys sent with pending unpacks
+//   b"pending-pubkey" | {unpacker canonical addr} | {global fardel id} -> pubkey
+// and the contents encrypted to them when the unpack is approved
+//   b"encrypted-unpack" | {unpacker canonical addr} | {global fardel id} -> EncryptedContents
+//
+
+// stores or clears the pubkey for an unpacker's pending unpack, so a pubkey left by a canceled
+//   unpack is never used for a later one
+pub fn set_pending_pubkey<S: Storage>(
+    storage: &mut S,
+    unpacker: &CanonicalAddr,
+    fardel_id: u128,
+    pubkey: Option<&Binary>,
+) -> StdResult<()> {
+    let mut storage =
+        PrefixedStorage::multilevel(&[PREFIX_PENDING_PUBKEYS, unpacker.as_slice()], storage);
+    match pubkey {
+        Some(pubkey) => set_bin_data(&mut storage, &fardel_id.to_be_bytes(), pubkey),
+        None => {
+            storage.remove(&fardel_id.to_be_bytes());
+            Ok(())
+        }
+    }
+}
+
+pub fn get_pending_pubkey<S: ReadonlyStorage>(
+    storage: &S,
+    unpacker: &CanonicalAddr,
+    fardel_id: u128,
+) -> Option<Binary> {
+    let storage = ReadonlyPrefixedStorage::multilevel(
+        &[PREFIX_PENDING_PUBKEYS, unpacker.as_slice()],
+        storage,
+    );
+    get_bin_data(&storage, &fardel_id.to_be_bytes()).ok()
+}
+
+// encrypted contents are keyed by the fardel's revision count when they were encrypted, so
+//   after a revision the ciphertext of the earlier contents is no longer found. A fardel that
+//   has never been revised keeps the plain fardel id key.
+fn encrypted_unpack_key<S: ReadonlyStorage>(storage: &S, fardel_id: u128) -> Vec<u8> {
+    let mut key = fardel_id.to_be_bytes().to_vec();
+    let revisions = get_number_of_revisions(storage, fardel_id);
+    if revisions > 0 {
+        key.extend_from_slice(&revisions.to_be_bytes());
+    }
+    key
+}
+
+pub fn store_encrypted_unpack<S: Storage>(
+    storage: &mut S,
+    unpacker: &CanonicalAddr,
+    fardel_id: u128,
+    encrypted_contents: &EncryptedContents,
+) -> StdResult<()> {
+    let key = encrypted_unpack_key(storage, fardel_id);
+    let mut storage =
+        PrefixedStorage::multilevel(&[PREFIX_ENCRYPTED_UNPACKS, unpacker.as_slice()], storage);
+    set_bin_data(&mut storage, &key, encrypted_contents)
+}
+
+pub fn get_encrypted_unpack<S: ReadonlyStorage>(
+    storage: &S,
+    unpacker: &CanonicalAddr,
+    fardel_id: u128,
+) -> Option<EncryptedContents> {
+    let key = encrypted_unpack_key(storage, fardel_id);
+    let storage = ReadonlyPrefixedStorage::multilevel(
+        &[PREFIX_ENCRYPTED_UNPACKS, unpacker.as_slice()],
+        storage,
+    );
+    get_bin_data(&storage, &key).ok()
+}
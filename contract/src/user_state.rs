@@ -1,19 +1,33 @@
+use crate::contract::HANDLE_COOLDOWN;
+use crate::permit::Permission;
 use crate::state::{
-    get_bin_data, set_bin_data, PREFIX_ACCOUNTS, PREFIX_ACCOUNT_THUMBNAIL_IMGS, PREFIX_BANNED,
-    PREFIX_DEACTIVATED, PREFIX_HANDLES, PREFIX_REGISTERED_ADDRESSES, PREFIX_VIEWING_KEY,
+    get_bin_data, get_block_time, get_schema_version, set_bin_data, ReadonlyConfig,
+    KEY_RESERVED_HANDLES, PREFIX_ACCOUNTS, PREFIX_ACCOUNT_THUMBNAIL_IMGS, PREFIX_BANNED,
+    PREFIX_BAN_LIST, PREFIX_DEACTIVATED, PREFIX_DELEGATES, PREFIX_DELEGATE_LIST, PREFIX_HANDLES,
+    PREFIX_HANDLE_COLLISIONS, PREFIX_HANDLE_HISTORY, PREFIX_NAMED_VIEWING_KEYS,
+    PREFIX_PRIVATE_ACCOUNTS, PREFIX_REGISTERED_ADDRESSES, PREFIX_RELEASED_HANDLES,
+    PREFIX_REVOKED_PERMITS, PREFIX_TAGGED_ACCOUNTS, PREFIX_VIEWING_KEY,
 };
 use crate::viewing_key::ViewingKey;
-use cosmwasm_std::{Api, CanonicalAddr, HumanAddr, ReadonlyStorage, StdResult, Storage, StdError,};
+use caseless::default_case_fold_str;
+use cosmwasm_std::{Api, CanonicalAddr, HumanAddr, ReadonlyStorage, StdError, StdResult, Storage};
 use cosmwasm_storage::{PrefixedStorage, ReadonlyPrefixedStorage};
 use schemars::JsonSchema;
 use secret_toolkit::storage::{AppendStore, AppendStoreMut};
 use serde::{Deserialize, Serialize};
+use unicode_normalization::UnicodeNormalization;
 
 //
 // User accounts
-//   b"account" | {owner canonical addr} -> StoredAccount
+//   b"account" | {owner canonical addr} -> VersionedAccount
 //   b"account-img" | {owner canonical addr} -> img
 //
+//   VersionedAccount leads with the layout version, reads branch on it and upgrade older
+//     layouts. A new layout gets a new variant.
+//   v1 accounts were stored untagged, before the contract is at schema v2 only accounts with
+//     b"account-tagged" | {owner canonical addr} -> true
+//     are tagged
+//
 
 #[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
 pub struct Account {
@@ -37,6 +51,22 @@ impl Account {
     }
 }
 
+// a stored account, the variant is its layout version
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub enum VersionedAccount {
+    V1(StoredAccountV1),
+    V2(StoredAccount),
+}
+
+impl VersionedAccount {
+    pub fn upgrade(self) -> StoredAccount {
+        match self {
+            VersionedAccount::V1(account) => account.upgrade(),
+            VersionedAccount::V2(account) => account,
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct StoredAccount {
     pub owner: CanonicalAddr,
@@ -46,6 +76,28 @@ pub struct StoredAccount {
     pub private_settings: Vec<u8>,
 }
 
+// account layout before versioning was added
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct StoredAccountV1 {
+    pub owner: CanonicalAddr,
+    pub handle: Vec<u8>,
+    pub description: Vec<u8>,
+    pub view_settings: Vec<u8>,
+    pub private_settings: Vec<u8>,
+}
+
+impl StoredAccountV1 {
+    pub fn upgrade(self) -> StoredAccount {
+        StoredAccount {
+            owner: self.owner,
+            handle: self.handle,
+            description: self.description,
+            view_settings: self.view_settings,
+            private_settings: self.private_settings,
+        }
+    }
+}
+
 impl StoredAccount {
     pub fn into_humanized<A: Api>(self, api: &A) -> StdResult<Account> {
         let account = Account {
@@ -68,43 +120,341 @@ pub fn store_account<S: Storage>(
     account: StoredAccount,
     owner: &CanonicalAddr,
 ) -> StdResult<()> {
+    if get_schema_version(store) < 2 {
+        let mut tagged_store = PrefixedStorage::new(PREFIX_TAGGED_ACCOUNTS, store);
+        set_bin_data(&mut tagged_store, &owner.as_slice(), &true)?;
+    }
     let mut store = PrefixedStorage::new(PREFIX_ACCOUNTS, store);
-    set_bin_data(&mut store, &owner.as_slice(), &account)
+    set_bin_data(
+        &mut store,
+        &owner.as_slice(),
+        &VersionedAccount::V2(account),
+    )
 }
 
 pub fn get_account<S: ReadonlyStorage>(
     store: &S,
     owner: &CanonicalAddr,
 ) -> StdResult<StoredAccount> {
+    let tagged = get_schema_version(store) >= 2 || {
+        let tagged_store = ReadonlyPrefixedStorage::new(PREFIX_TAGGED_ACCOUNTS, store);
+        get_bin_data(&tagged_store, &owner.as_slice()).unwrap_or_else(|_| false)
+    };
     let store = ReadonlyPrefixedStorage::new(PREFIX_ACCOUNTS, store);
-    get_bin_data(&store, &owner.as_slice())
+    if tagged {
+        let account: VersionedAccount = get_bin_data(&store, &owner.as_slice())?;
+        Ok(account.upgrade())
+    } else {
+        let account: StoredAccountV1 = get_bin_data(&store, &owner.as_slice())?;
+        Ok(account.upgrade())
+    }
+}
+
+// rewrites an account in the current layout, used by Migrate
+pub fn upgrade_stored_account<S: Storage>(store: &mut S, owner: &CanonicalAddr) -> StdResult<()> {
+    let account = get_account(store, owner)?;
+    store_account(store, account, owner)
 }
 
 //
 // Handle to account mapping -- allows look up by handle, not address
+//   b"handle" | {normalized handle} -> owner canonical addr
+//   b"handle-collisions" | {appendstore index} -> HandleCollision
 //
+//   b"released-handles" | {normalized handle} -> ReleasedHandle
+//   b"handle-history" | {owner canonical addr} | {appendstore index} -> ReleasedHandle
+//
+//   handles are unique ignoring case, so the map is keyed by the normalized handle (NFC, then
+//     full case folding, so "Straße" and "STRASSE" are the same) while the account keeps the
+//     handle as the user typed it. New handles are limited to ascii (see valid_handle_chars),
+//     older or imported handles may not be.
+//
+//   a handle given up by a rename is released: for HANDLE_COOLDOWN it still looks up its
+//     previous owner, which also keeps anyone else from taking it, and only the previous
+//     owner can take it back.
+//
+
+pub fn normalize_handle(handle: &str) -> String {
+    let composed: String = handle.nfc().collect();
+    // folding can leave the string decomposed, so compose again
+    default_case_fold_str(&composed).nfc().collect()
+}
 
 pub fn map_handle_to_account<S: Storage>(
     store: &mut S,
     owner: &CanonicalAddr,
     handle: String,
 ) -> StdResult<()> {
+    let normalized = normalize_handle(&handle);
+    // taking a handle ends any cooldown on it
+    let mut released_store = PrefixedStorage::new(PREFIX_RELEASED_HANDLES, store);
+    released_store.remove(normalized.as_bytes());
+
     let mut store = PrefixedStorage::new(PREFIX_HANDLES, store);
-    set_bin_data(&mut store, handle.as_bytes(), &owner)
+    set_bin_data(&mut store, normalized.as_bytes(), &owner)
 }
 
 // this is meant to be called after handle has been changed in account
 pub fn delete_handle_map<S: Storage>(store: &mut S, handle: String) {
     let mut store = PrefixedStorage::new(PREFIX_HANDLES, store);
-    store.remove(handle.as_bytes())
+    store.remove(normalize_handle(&handle).as_bytes())
 }
 
+// looks up the account for a handle, following a recently released handle to its
+//   previous owner
 pub fn get_account_for_handle<S: ReadonlyStorage>(
     store: &S,
     handle: &String,
 ) -> StdResult<CanonicalAddr> {
-    let store = ReadonlyPrefixedStorage::new(PREFIX_HANDLES, store);
-    get_bin_data(&store, handle.as_bytes())
+    let normalized = normalize_handle(handle);
+    let handles = ReadonlyPrefixedStorage::new(PREFIX_HANDLES, store);
+    let result = get_bin_data(&handles, normalized.as_bytes());
+    if result.is_ok() {
+        return result;
+    }
+    match get_released_handle(store, &normalized) {
+        Some(released) if released.in_cooldown(get_block_time(store)) => Ok(released.owner),
+        _ => result,
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ReleasedHandle {
+    pub owner: CanonicalAddr,
+    pub handle: Vec<u8>,
+    pub timestamp: u64,
+}
+
+impl ReleasedHandle {
+    pub fn in_cooldown(&self, time: u64) -> bool {
+        time < self.timestamp + HANDLE_COOLDOWN
+    }
+}
+
+// removes the owner's old handle from the map after a rename, releasing it and adding it
+//   to the owner's handle history
+pub fn release_handle<S: Storage>(
+    store: &mut S,
+    owner: &CanonicalAddr,
+    handle: String,
+    timestamp: u64,
+) -> StdResult<()> {
+    let released = ReleasedHandle {
+        owner: owner.clone(),
+        handle: handle.as_bytes().to_vec(),
+        timestamp,
+    };
+    let normalized = normalize_handle(&handle);
+    delete_handle_map(store, handle);
+
+    let mut released_store = PrefixedStorage::new(PREFIX_RELEASED_HANDLES, store);
+    set_bin_data(&mut released_store, normalized.as_bytes(), &released)?;
+
+    let mut history_store =
+        PrefixedStorage::multilevel(&[PREFIX_HANDLE_HISTORY, owner.as_slice()], store);
+    let mut history_store =
+        AppendStoreMut::<ReleasedHandle, _>::attach_or_create(&mut history_store)?;
+    history_store.push(&released)
+}
+
+fn get_released_handle<S: ReadonlyStorage>(store: &S, normalized: &str) -> Option<ReleasedHandle> {
+    let store = ReadonlyPrefixedStorage::new(PREFIX_RELEASED_HANDLES, store);
+    get_bin_data(&store, normalized.as_bytes()).ok()
+}
+
+// gets up to `count` of the owner's previous handles starting from the latest
+pub fn get_handle_history<S: ReadonlyStorage>(
+    store: &S,
+    owner: &CanonicalAddr,
+    count: u32,
+) -> StdResult<Vec<ReleasedHandle>> {
+    let store =
+        ReadonlyPrefixedStorage::multilevel(&[PREFIX_HANDLE_HISTORY, owner.as_slice()], store);
+    let store = if let Some(result) = AppendStore::<ReleasedHandle, _>::attach(&store) {
+        result?
+    } else {
+        return Ok(vec![]);
+    };
+    store.iter().rev().take(count as _).collect()
+}
+
+// gets the owner's previous handles in appendstore order (oldest first), used for export
+pub fn get_handle_history_range<S: ReadonlyStorage>(
+    store: &S,
+    owner: &CanonicalAddr,
+    start: u32,
+    count: u32,
+) -> StdResult<Vec<ReleasedHandle>> {
+    let store =
+        ReadonlyPrefixedStorage::multilevel(&[PREFIX_HANDLE_HISTORY, owner.as_slice()], store);
+    let store = if let Some(result) = AppendStore::<ReleasedHandle, _>::attach(&store) {
+        result?
+    } else {
+        return Ok(vec![]);
+    };
+    store.iter().skip(start as _).take(count as _).collect()
+}
+
+pub fn get_handle_history_len<S: ReadonlyStorage>(store: &S, owner: &CanonicalAddr) -> u32 {
+    let store =
+        ReadonlyPrefixedStorage::multilevel(&[PREFIX_HANDLE_HISTORY, owner.as_slice()], store);
+    match AppendStore::<ReleasedHandle, _>::attach(&store) {
+        Some(Ok(store)) => store.len(),
+        _ => 0_u32,
+    }
+}
+
+// adds an imported previous handle to the owner's history. It is released again (so its
+//   cooldown carries over) unless an imported account holds it or a later release of it has
+//   already been imported.
+pub fn import_released_handle<S: Storage>(
+    store: &mut S,
+    released: &ReleasedHandle,
+) -> StdResult<()> {
+    let handle = String::from_utf8(released.handle.clone())
+        .map_err(|_| StdError::generic_err("Invalid handle."))?;
+    let normalized = normalize_handle(&handle);
+    let handles = ReadonlyPrefixedStorage::new(PREFIX_HANDLES, store);
+    let held: bool = get_bin_data::<CanonicalAddr, _>(&handles, normalized.as_bytes()).is_ok();
+    let superseded = get_released_handle(store, &normalized)
+        .map_or(false, |existing| existing.timestamp > released.timestamp);
+    if !held && !superseded {
+        let mut released_store = PrefixedStorage::new(PREFIX_RELEASED_HANDLES, store);
+        set_bin_data(&mut released_store, normalized.as_bytes(), released)?;
+    }
+
+    let mut history_store =
+        PrefixedStorage::multilevel(&[PREFIX_HANDLE_HISTORY, released.owner.as_slice()], store);
+    let mut history_store =
+        AppendStoreMut::<ReleasedHandle, _>::attach_or_create(&mut history_store)?;
+    history_store.push(released)
+}
+
+// an account whose handle normalizes to one already held by another account (the holder).
+//   The account keeps its handle but cannot be looked up by it until it sets a new one.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct HandleCollision {
+    pub account: CanonicalAddr,
+    pub handle: Vec<u8>,
+    pub holder: CanonicalAddr,
+}
+
+// maps an account's handle under its normalized key, recording a collision instead if another
+//   account already holds it, or if it is reserved and the account is not the admin (who is
+//   then recorded as the holder). Used by Migrate for handles mapped before normalization and
+//   by import for handles from older contracts. Returns false on a collision.
+pub fn map_normalized_handle<S: Storage>(
+    store: &mut S,
+    owner: &CanonicalAddr,
+    handle: String,
+) -> StdResult<bool> {
+    let admin = ReadonlyConfig::from_storage(store).constants()?.admin;
+    let holder = if is_reserved_handle(store, &handle) && *owner != admin {
+        Some(admin)
+    } else {
+        match get_account_for_handle(store, &handle) {
+            Ok(holder) if holder != *owner => Some(holder),
+            _ => None,
+        }
+    };
+
+    match holder {
+        Some(holder) => {
+            // a reserved handle loses its entry
+            let map = ReadonlyPrefixedStorage::new(PREFIX_HANDLES, store);
+            let entry: StdResult<CanonicalAddr> =
+                get_bin_data(&map, normalize_handle(&handle).as_bytes());
+            if entry.map_or(false, |entry| entry == *owner) {
+                delete_handle_map(store, handle.clone());
+            }
+            let mut store = PrefixedStorage::new(PREFIX_HANDLE_COLLISIONS, store);
+            let mut store = AppendStoreMut::<HandleCollision, _>::attach_or_create(&mut store)?;
+            store.push(&HandleCollision {
+                account: owner.clone(),
+                handle: handle.as_bytes().to_vec(),
+                holder,
+            })?;
+            Ok(false)
+        }
+        None => {
+            map_handle_to_account(store, owner, handle)?;
+            Ok(true)
+        }
+    }
+}
+
+// rekeys an account's handle map entry from the handle as typed to its normalized handle, used
+//   by Migrate. Handles whose key does not change keep their entry, so they win any collision.
+pub fn upgrade_handle_map<S: Storage>(store: &mut S, owner: &CanonicalAddr) -> StdResult<()> {
+    let handle = match get_account(store, owner) {
+        Ok(account) => String::from_utf8(account.handle).unwrap_or_default(),
+        Err(_) => return Ok(()),
+    };
+    if handle.is_empty() {
+        return Ok(());
+    }
+    let normalized = normalize_handle(&handle);
+
+    let mut map = PrefixedStorage::new(PREFIX_HANDLES, store);
+    let mut mapped = false;
+    for key in &[handle.clone(), normalized.clone()] {
+        let entry: StdResult<CanonicalAddr> = get_bin_data(&map, key.as_bytes());
+        if entry.map_or(false, |entry| entry == *owner) {
+            mapped = true;
+            if *key != normalized {
+                map.remove(key.as_bytes());
+            }
+        }
+    }
+    // not in the handle map, nothing to rekey
+    if !mapped {
+        return Ok(());
+    }
+
+    map_normalized_handle(store, owner, handle)?;
+    Ok(())
+}
+
+// gets `count` handle collisions starting from `start`
+pub fn get_handle_collisions<S: ReadonlyStorage>(
+    storage: &S,
+    start: u32,
+    count: u32,
+) -> StdResult<Vec<HandleCollision>> {
+    let storage = ReadonlyPrefixedStorage::new(PREFIX_HANDLE_COLLISIONS, storage);
+    let storage = if let Some(result) = AppendStore::<HandleCollision, _>::attach(&storage) {
+        result?
+    } else {
+        return Ok(vec![]);
+    };
+    storage.iter().skip(start as _).take(count as _).collect()
+}
+
+pub fn get_handle_collisions_len<S: ReadonlyStorage>(storage: &S) -> StdResult<u32> {
+    let storage = ReadonlyPrefixedStorage::new(PREFIX_HANDLE_COLLISIONS, storage);
+    let storage = if let Some(result) = AppendStore::<HandleCollision, _>::attach(&storage) {
+        result?
+    } else {
+        return Ok(0_u32);
+    };
+    Ok(storage.len())
+}
+
+//
+// Reserved handles
+//   normalized handles that only the admin can register
+//
+
+pub fn set_reserved_handles<S: Storage>(store: &mut S, handles: &[String]) -> StdResult<()> {
+    set_bin_data(store, KEY_RESERVED_HANDLES, &handles.to_vec())
+}
+
+pub fn get_reserved_handles<S: ReadonlyStorage>(store: &S) -> Vec<String> {
+    get_bin_data(store, KEY_RESERVED_HANDLES).unwrap_or_else(|_| vec![])
+}
+
+pub fn is_reserved_handle<S: ReadonlyStorage>(store: &S, handle: &str) -> bool {
+    get_reserved_handles(store).contains(&normalize_handle(handle))
 }
 
 //
@@ -135,11 +485,264 @@ pub fn write_viewing_key<S: Storage>(store: &mut S, owner: &CanonicalAddr, key:
     user_key_store.set(owner.as_slice(), &key.to_hashed());
 }
 
+// writes an already hashed viewing key, used when importing accounts
+pub fn write_viewing_key_hash<S: Storage>(store: &mut S, owner: &CanonicalAddr, hashed_key: &[u8]) {
+    let mut user_key_store = PrefixedStorage::new(PREFIX_VIEWING_KEY, store);
+    user_key_store.set(owner.as_slice(), hashed_key);
+}
+
 pub fn read_viewing_key<S: Storage>(store: &S, owner: &CanonicalAddr) -> Option<Vec<u8>> {
     let user_key_store = ReadonlyPrefixedStorage::new(PREFIX_VIEWING_KEY, store);
     user_key_store.get(owner.as_slice())
 }
 
+//
+// Named viewing keys
+//   b"named-viewing-keys" | {owner canonical addr} | {appendstore idx} -> StoredNamedViewingKey
+//
+//   the unnamed key above is the default key and can run every query, named keys are limited
+//   to their scopes. Revoked slots are reused by the next new name.
+//
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct StoredNamedViewingKey {
+    pub name: Vec<u8>,
+    pub hashed_key: Vec<u8>,
+    pub scopes: Vec<Permission>,
+    pub timestamp: u64,
+    pub revoked: bool,
+}
+
+// stores a named key, replacing any existing key with the same name
+//   returns false if the owner already has the maximum number of named keys
+pub fn write_named_viewing_key<S: Storage>(
+    store: &mut S,
+    owner: &CanonicalAddr,
+    name: &str,
+    key: &ViewingKey,
+    scopes: Vec<Permission>,
+    timestamp: u64,
+    max_keys: u32,
+) -> StdResult<bool> {
+    let existing = get_named_viewing_keys(store, owner)?;
+    let idx = existing
+        .iter()
+        .position(|named| named.name == name.as_bytes())
+        .or_else(|| existing.iter().position(|named| named.revoked));
+
+    let named_key = StoredNamedViewingKey {
+        name: name.as_bytes().to_vec(),
+        hashed_key: key.to_hashed().to_vec(),
+        scopes,
+        timestamp,
+        revoked: false,
+    };
+    let mut store =
+        PrefixedStorage::multilevel(&[PREFIX_NAMED_VIEWING_KEYS, owner.as_slice()], store);
+    let mut store = AppendStoreMut::<StoredNamedViewingKey, _>::attach_or_create(&mut store)?;
+    match idx {
+        Some(idx) => store.set_at(idx as u32, &named_key)?,
+        None => {
+            if store.len() >= max_keys {
+                return Ok(false);
+            }
+            store.push(&named_key)?;
+        }
+    }
+    Ok(true)
+}
+
+// returns false if there is no active key with that name
+pub fn revoke_named_viewing_key<S: Storage>(
+    store: &mut S,
+    owner: &CanonicalAddr,
+    name: &str,
+) -> StdResult<bool> {
+    let existing = get_named_viewing_keys(store, owner)?;
+    let idx = existing
+        .iter()
+        .position(|named| !named.revoked && named.name == name.as_bytes());
+    let idx = match idx {
+        Some(idx) => idx as u32,
+        None => return Ok(false),
+    };
+
+    let mut store =
+        PrefixedStorage::multilevel(&[PREFIX_NAMED_VIEWING_KEYS, owner.as_slice()], store);
+    let mut store = AppendStoreMut::<StoredNamedViewingKey, _>::attach_or_create(&mut store)?;
+    let mut named_key = store.get_at(idx)?;
+    named_key.revoked = true;
+    store.set_at(idx, &named_key)?;
+    Ok(true)
+}
+
+// appends a named key slot as it was stored, used for import so slot order is kept
+pub fn import_named_viewing_key<S: Storage>(
+    store: &mut S,
+    owner: &CanonicalAddr,
+    named_key: &StoredNamedViewingKey,
+) -> StdResult<()> {
+    let mut store =
+        PrefixedStorage::multilevel(&[PREFIX_NAMED_VIEWING_KEYS, owner.as_slice()], store);
+    let mut store = AppendStoreMut::<StoredNamedViewingKey, _>::attach_or_create(&mut store)?;
+    store.push(named_key)
+}
+
+// gets all named key slots for the owner, including revoked ones
+pub fn get_named_viewing_keys<S: ReadonlyStorage>(
+    store: &S,
+    owner: &CanonicalAddr,
+) -> StdResult<Vec<StoredNamedViewingKey>> {
+    let store =
+        ReadonlyPrefixedStorage::multilevel(&[PREFIX_NAMED_VIEWING_KEYS, owner.as_slice()], store);
+    let store = if let Some(result) = AppendStore::<StoredNamedViewingKey, _>::attach(&store) {
+        result?
+    } else {
+        return Ok(vec![]);
+    };
+    store.iter().collect()
+}
+
+//
+// Delegates
+//   accounts that can call some handle functions on the owner's behalf (with on_behalf_of)
+//   b"delegates" | {owner canonical addr} | {delegate canonical addr} -> StoredDelegation
+//   b"delegate-list" | {owner canonical addr} | {appendstore idx} -> delegate canonical addr
+//
+//   delegations with expiry == 0 do not expire
+//
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum DelegatePermission {
+    Carry,
+    Seal,
+    Hide,
+    ApproveUnpacks,
+    ModerateComments,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct StoredDelegation {
+    pub permissions: Vec<DelegatePermission>,
+    pub expiry: u64,
+    pub revoked: bool,
+}
+
+impl StoredDelegation {
+    pub fn is_active(&self, time: u64) -> bool {
+        !self.revoked && (self.expiry == 0 || self.expiry > time)
+    }
+
+    pub fn allows(&self, permission: &DelegatePermission, time: u64) -> bool {
+        self.is_active(time) && self.permissions.contains(permission)
+    }
+}
+
+pub fn store_delegation<S: Storage>(
+    storage: &mut S,
+    owner: &CanonicalAddr,
+    delegate: &CanonicalAddr,
+    permissions: Vec<DelegatePermission>,
+    expiry: u64,
+) -> StdResult<()> {
+    // first delegation to this address, add it to the owner's list
+    if get_delegation(storage, owner, delegate).is_none() {
+        let mut list_storage =
+            PrefixedStorage::multilevel(&[PREFIX_DELEGATE_LIST, owner.as_slice()], storage);
+        let mut list_storage =
+            AppendStoreMut::<CanonicalAddr, _>::attach_or_create(&mut list_storage)?;
+        list_storage.push(delegate)?;
+    }
+
+    let delegation = StoredDelegation {
+        permissions,
+        expiry,
+        revoked: false,
+    };
+    let mut storage = PrefixedStorage::multilevel(&[PREFIX_DELEGATES, owner.as_slice()], storage);
+    set_bin_data(&mut storage, delegate.as_slice(), &delegation)
+}
+
+// returns false if the address is not an active delegate of the owner
+pub fn revoke_delegation<S: Storage>(
+    storage: &mut S,
+    owner: &CanonicalAddr,
+    delegate: &CanonicalAddr,
+    time: u64,
+) -> StdResult<bool> {
+    let mut delegation = match get_delegation(storage, owner, delegate) {
+        Some(delegation) if delegation.is_active(time) => delegation,
+        _ => return Ok(false),
+    };
+    delegation.revoked = true;
+    let mut storage = PrefixedStorage::multilevel(&[PREFIX_DELEGATES, owner.as_slice()], storage);
+    set_bin_data(&mut storage, delegate.as_slice(), &delegation)?;
+    Ok(true)
+}
+
+pub fn get_delegation<S: ReadonlyStorage>(
+    storage: &S,
+    owner: &CanonicalAddr,
+    delegate: &CanonicalAddr,
+) -> Option<StoredDelegation> {
+    let storage =
+        ReadonlyPrefixedStorage::multilevel(&[PREFIX_DELEGATES, owner.as_slice()], storage);
+    get_bin_data(&storage, delegate.as_slice()).ok()
+}
+
+// every address the owner has ever delegated to, check get_delegation for current status
+pub fn get_delegate_list<S: ReadonlyStorage>(
+    storage: &S,
+    owner: &CanonicalAddr,
+) -> StdResult<Vec<CanonicalAddr>> {
+    let storage =
+        ReadonlyPrefixedStorage::multilevel(&[PREFIX_DELEGATE_LIST, owner.as_slice()], storage);
+    let storage = if let Some(result) = AppendStore::<CanonicalAddr, _>::attach(&storage) {
+        result?
+    } else {
+        return Ok(vec![]);
+    };
+    storage.iter().collect()
+}
+
+// number of the owner's delegations that have not been revoked or expired
+pub fn get_number_of_active_delegates<S: ReadonlyStorage>(
+    storage: &S,
+    owner: &CanonicalAddr,
+    time: u64,
+) -> StdResult<usize> {
+    Ok(get_delegate_list(storage, owner)?
+        .iter()
+        .filter_map(|delegate| get_delegation(storage, owner, delegate))
+        .filter(|delegation| delegation.is_active(time))
+        .count())
+}
+
+//
+// Revoked query permits
+//   b"revoked-permits" | {owner canonical addr} | {permit name} -> true
+//
+
+pub fn revoke_permit<S: Storage>(
+    store: &mut S,
+    owner: &CanonicalAddr,
+    permit_name: &str,
+) -> StdResult<()> {
+    let mut store = PrefixedStorage::multilevel(&[PREFIX_REVOKED_PERMITS, owner.as_slice()], store);
+    set_bin_data(&mut store, permit_name.as_bytes(), &true)
+}
+
+pub fn is_permit_revoked<S: ReadonlyStorage>(
+    store: &S,
+    owner: &CanonicalAddr,
+    permit_name: &str,
+) -> bool {
+    let store =
+        ReadonlyPrefixedStorage::multilevel(&[PREFIX_REVOKED_PERMITS, owner.as_slice()], store);
+    get_bin_data(&store, permit_name.as_bytes()).unwrap_or_else(|_| false)
+}
+
 //
 // Deactivated accounts
 //
@@ -159,24 +762,182 @@ pub fn is_deactivated<S: ReadonlyStorage>(store: &S, account: &CanonicalAddr) ->
     get_bin_data(&store, &account.as_slice()).unwrap_or_else(|_| false)
 }
 
+//
+// Private accounts
+//   follows of a private account are requests until the account accepts them
+//
+
+pub fn store_account_private<S: Storage>(
+    store: &mut S,
+    account: &CanonicalAddr,
+    private: bool,
+) -> StdResult<()> {
+    let mut store = PrefixedStorage::new(PREFIX_PRIVATE_ACCOUNTS, store);
+    set_bin_data(&mut store, &account.as_slice(), &private)
+}
+
+// returns true if account is private
+pub fn is_private_account<S: ReadonlyStorage>(store: &S, account: &CanonicalAddr) -> bool {
+    let store = ReadonlyPrefixedStorage::new(PREFIX_PRIVATE_ACCOUNTS, store);
+    get_bin_data(&store, &account.as_slice()).unwrap_or_else(|_| false)
+}
+
 //
 // Banned accounts
-//   b"banned" | {owner canonical addr} -> bool
+//   b"banned" | {owner canonical addr} -> StoredBan
+//   b"ban-list" | {appendstore idx} -> canonical addr of every account that has been banned
+//
+//   bans with expiry == 0 are permanent, otherwise they lapse once the block time passes expiry
 //
 
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct StoredBan {
+    pub banned: bool,
+    pub expiry: u64,
+    pub reason: Vec<u8>,
+    pub timestamp: u64,
+}
+
+impl StoredBan {
+    pub fn is_active(&self, time: u64) -> bool {
+        self.banned && (self.expiry == 0 || self.expiry > time)
+    }
+}
+
 pub fn store_account_ban<S: Storage>(
     storage: &mut S,
     account: &CanonicalAddr,
     banned: bool,
+    expiry: u64,
+    reason: Vec<u8>,
+    timestamp: u64,
 ) -> StdResult<()> {
+    // first ban for this account, add it to the ban list
+    //   a plain bool ban was stored before the list existed, so it was never added
+    if banned && get_stored_ban(storage, account).is_err() {
+        push_ban_list(storage, account)?;
+    }
+
+    let ban = StoredBan {
+        banned,
+        expiry,
+        reason,
+        timestamp,
+    };
     let mut storage = PrefixedStorage::new(PREFIX_BANNED, storage);
-    set_bin_data(&mut storage, &account.as_slice(), &banned)
+    set_bin_data(&mut storage, &account.as_slice(), &ban)
 }
 
-// returns true is account is banned
-pub fn is_banned<S: ReadonlyStorage>(storage: &S, account: &CanonicalAddr) -> bool {
+fn push_ban_list<S: Storage>(storage: &mut S, account: &CanonicalAddr) -> StdResult<()> {
+    let mut list_storage = PrefixedStorage::new(PREFIX_BAN_LIST, storage);
+    let mut list_storage =
+        AppendStoreMut::<CanonicalAddr, _>::attach_or_create(&mut list_storage)?;
+    list_storage.push(account)
+}
+
+fn get_stored_ban<S: ReadonlyStorage>(
+    storage: &S,
+    account: &CanonicalAddr,
+) -> StdResult<StoredBan> {
     let storage = ReadonlyPrefixedStorage::new(PREFIX_BANNED, storage);
-    get_bin_data(&storage, &account.as_slice()).unwrap_or_else(|_| false)
+    get_bin_data(&storage, &account.as_slice())
+}
+
+// adds an account banned before the ban list existed to it and stores the ban as a StoredBan,
+//   used by Migrate. Bans of addresses that never registered are not found this way.
+pub fn upgrade_account_ban<S: Storage>(
+    storage: &mut S,
+    account: &CanonicalAddr,
+) -> StdResult<()> {
+    if get_stored_ban(storage, account).is_ok() {
+        return Ok(());
+    }
+    let legacy: bool = {
+        let storage = ReadonlyPrefixedStorage::new(PREFIX_BANNED, storage);
+        match get_bin_data(&storage, &account.as_slice()) {
+            Ok(banned) => banned,
+            // never banned
+            Err(_) => return Ok(()),
+        }
+    };
+    // an unbanned legacy record was still banned once
+    push_ban_list(storage, account)?;
+    let ban = StoredBan {
+        banned: legacy,
+        expiry: 0,
+        reason: vec![],
+        timestamp: 0,
+    };
+    let mut storage = PrefixedStorage::new(PREFIX_BANNED, storage);
+    set_bin_data(&mut storage, &account.as_slice(), &ban)
+}
+
+pub fn get_account_ban<S: ReadonlyStorage>(
+    storage: &S,
+    account: &CanonicalAddr,
+) -> StdResult<StoredBan> {
+    match get_stored_ban(storage, account) {
+        Ok(ban) => Ok(ban),
+        // bans stored before expiry and reason were added are a plain bool
+        Err(_) => {
+            let storage = ReadonlyPrefixedStorage::new(PREFIX_BANNED, storage);
+            let banned: bool = get_bin_data(&storage, &account.as_slice())?;
+            Ok(StoredBan {
+                banned,
+                expiry: 0,
+                reason: vec![],
+                timestamp: 0,
+            })
+        }
+    }
+}
+
+// returns true is account is banned and the ban has not expired by the last recorded block
+//   time, handles should use is_banned_at with the current block time
+pub fn is_banned<S: ReadonlyStorage>(storage: &S, account: &CanonicalAddr) -> bool {
+    is_banned_at(storage, account, get_block_time(storage))
+}
+
+// returns true is account is banned and the ban has not expired at `time`
+pub fn is_banned_at<S: ReadonlyStorage>(storage: &S, account: &CanonicalAddr, time: u64) -> bool {
+    match get_account_ban(storage, account) {
+        Ok(ban) => ban.is_active(time),
+        Err(_) => false,
+    }
+}
+
+// gets `count` entries from the ban list starting from `start`, including lapsed bans
+pub fn get_ban_list<S: ReadonlyStorage>(
+    storage: &S,
+    start: u32,
+    count: u32,
+) -> StdResult<Vec<CanonicalAddr>> {
+    let storage = ReadonlyPrefixedStorage::new(PREFIX_BAN_LIST, storage);
+
+    // Try to access the ban list.
+    // If it doesn't exist yet, return an empty list.
+    let storage = if let Some(result) = AppendStore::<CanonicalAddr, _>::attach(&storage) {
+        result?
+    } else {
+        return Ok(vec![]);
+    };
+
+    let address_iter = storage.iter().skip(start as _).take(count as _);
+    let addresses: StdResult<Vec<CanonicalAddr>> = address_iter.collect();
+    addresses
+}
+
+pub fn get_ban_list_len<S: ReadonlyStorage>(storage: &S) -> StdResult<u32> {
+    let storage = ReadonlyPrefixedStorage::new(PREFIX_BAN_LIST, storage);
+
+    // Try to access the ban list.
+    // If it doesn't exist yet, return 0.
+    let storage = if let Some(result) = AppendStore::<CanonicalAddr, _>::attach(&storage) {
+        result?
+    } else {
+        return Ok(0_u32);
+    };
+    Ok(storage.len())
 }
 
 //
@@ -243,3 +1004,85 @@ pub fn get_total_number_registered_accounts<S: ReadonlyStorage>(storage: &S) ->
     };
     Ok(storage.len())
 }
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use cosmwasm_std::testing::MockStorage;
+    use cosmwasm_std::Binary;
+
+    // stores and registers an account in the layout used before versioning, as a contract
+    //   that has not been migrated would have stored it
+    pub fn store_v1_account<S: Storage>(
+        storage: &mut S,
+        owner: &CanonicalAddr,
+        handle: &str,
+    ) -> StoredAccountV1 {
+        let account = StoredAccountV1 {
+            owner: owner.clone(),
+            handle: handle.as_bytes().to_vec(),
+            description: b"description".to_vec(),
+            view_settings: b"view settings".to_vec(),
+            private_settings: b"private settings".to_vec(),
+        };
+        {
+            let mut store = PrefixedStorage::new(PREFIX_ACCOUNTS, storage);
+            set_bin_data(&mut store, owner.as_slice(), &account).unwrap();
+        }
+        address_list_add(storage, owner).unwrap();
+        account
+    }
+
+    pub fn assert_upgraded_account(account: &StoredAccount, v1: &StoredAccountV1) {
+        assert_eq!(account.owner, v1.owner);
+        assert_eq!(account.handle, v1.handle);
+        assert_eq!(account.description, v1.description);
+        assert_eq!(account.view_settings, v1.view_settings);
+        assert_eq!(account.private_settings, v1.private_settings);
+    }
+
+    #[test]
+    fn v1_account_is_read_as_v2() {
+        let mut storage = MockStorage::new();
+        let owner = CanonicalAddr(Binary(b"owner".to_vec()));
+        let v1 = store_v1_account(&mut storage, &owner, "owner");
+
+        // the account has not been tagged by Migrate, so it is read in the v1 layout
+        assert_upgraded_account(&get_account(&storage, &owner).unwrap(), &v1);
+    }
+
+    #[test]
+    fn upgrade_stored_account_rewrites_v1_as_v2() {
+        let mut storage = MockStorage::new();
+        let owner = CanonicalAddr(Binary(b"owner".to_vec()));
+        let v1 = store_v1_account(&mut storage, &owner, "owner");
+
+        upgrade_stored_account(&mut storage, &owner).unwrap();
+
+        let store = ReadonlyPrefixedStorage::new(PREFIX_ACCOUNTS, &storage);
+        match get_bin_data(&store, owner.as_slice()).unwrap() {
+            VersionedAccount::V2(account) => assert_upgraded_account(&account, &v1),
+            _ => panic!("Upgraded account is not tagged as v2"),
+        }
+    }
+    #[test]
+    fn legacy_bans_are_added_to_the_ban_list() {
+        let mut storage = MockStorage::new();
+        let rebanned = CanonicalAddr(Binary(b"rebanned".to_vec()));
+        let migrated = CanonicalAddr(Binary(b"migrated".to_vec()));
+        for account in vec![&rebanned, &migrated] {
+            let mut store = PrefixedStorage::new(PREFIX_BANNED, &mut storage);
+            set_bin_data(&mut store, account.as_slice(), &true).unwrap();
+        }
+
+        // banning again counts the plain bool as no ban
+        store_account_ban(&mut storage, &rebanned, true, 0, vec![], 1).unwrap();
+        assert_eq!(get_ban_list(&storage, 0, 10).unwrap(), vec![rebanned.clone()]);
+
+        // Migrate adds the rest once, the second call finds a StoredBan
+        upgrade_account_ban(&mut storage, &migrated).unwrap();
+        upgrade_account_ban(&mut storage, &migrated).unwrap();
+        assert_eq!(get_ban_list(&storage, 0, 10).unwrap(), vec![rebanned, migrated.clone()]);
+        assert!(get_account_ban(&storage, &migrated).unwrap().banned);
+    }
+}
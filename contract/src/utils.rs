@@ -1,4 +1,12 @@
+use crate::msg::EncryptedContents;
 use crate::viewing_key::VIEWING_KEY_SIZE;
+use chacha20poly1305::aead::{Aead, NewAead, Payload};
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+use cosmwasm_std::{Binary, CanonicalAddr, StdError, StdResult};
+use k256::elliptic_curve::ecdh::diffie_hellman;
+use k256::elliptic_curve::sec1::ToEncodedPoint;
+use k256::{FieldBytes, NonZeroScalar, PublicKey, Scalar};
+use secret_toolkit::crypto::sha_256;
 use sha2::{Digest, Sha256};
 use std::convert::TryInto;
 use subtle::ConstantTimeEq;
@@ -27,3 +35,114 @@ pub fn space_pad(block_size: usize, message: &mut Vec<u8>) -> &mut Vec<u8> {
     message.extend(std::iter::repeat(b' ').take(missing));
     message
 }
+
+// Encrypts private data for export to a new contract instance with XChaCha20-Poly1305 under
+//   the export key. `label` names the record and field and is authenticated as associated
+//   data, so a ciphertext can't be moved to another field or record. Queries have no entropy,
+//   so the nonce is synthetic: the start of sha256 over the key, label and data, which only
+//   repeats when the same data is exported under the same label again. The nonce is
+//   prepended to the ciphertext.
+pub fn encrypt_export_data(key: &[u8], label: &[u8], data: &[u8]) -> StdResult<Vec<u8>> {
+    let mut nonce_data = Vec::with_capacity(key.len() + label.len() + data.len());
+    nonce_data.extend_from_slice(key);
+    nonce_data.extend_from_slice(label);
+    nonce_data.extend_from_slice(data);
+    let nonce = sha_256(&nonce_data);
+    let cipher = export_cipher(key);
+    let ciphertext = cipher
+        .encrypt(
+            XNonce::from_slice(&nonce[..24]),
+            Payload {
+                msg: data,
+                aad: label,
+            },
+        )
+        .map_err(|_| StdError::generic_err("Encryption failed."))?;
+    let mut output = Vec::with_capacity(24 + ciphertext.len());
+    output.extend_from_slice(&nonce[..24]);
+    output.extend_from_slice(&ciphertext);
+    Ok(output)
+}
+
+// label for an encrypted field of the entry at idx in one of the owner's exported lists
+pub fn export_entry_label(field: &[u8], owner: &CanonicalAddr, idx: u32) -> Vec<u8> {
+    [field, owner.as_slice(), &idx.to_be_bytes()].concat()
+}
+
+pub fn decrypt_export_data(key: &[u8], label: &[u8], data: &[u8]) -> StdResult<Vec<u8>> {
+    if data.len() < 24 {
+        return Err(StdError::generic_err("Encrypted export data is too short."));
+    }
+    let (nonce, ciphertext) = data.split_at(24);
+    export_cipher(key)
+        .decrypt(
+            XNonce::from_slice(nonce),
+            Payload {
+                msg: ciphertext,
+                aad: label,
+            },
+        )
+        .map_err(|_| StdError::generic_err("Encrypted export data failed verification."))
+}
+
+// Encrypts with XChaCha20-Poly1305. The cipher key is sha256 of `key` and the 24 byte nonce is
+// the start of sha256 of `nonce`, so any key material can be used as long as `nonce` is never
+// reused with the same key.
+pub fn aead_encrypt(key: &[u8], nonce: &[u8], data: &[u8]) -> StdResult<Vec<u8>> {
+    let cipher_key = sha_256(key);
+    let cipher = XChaCha20Poly1305::new(Key::from_slice(&cipher_key));
+    cipher
+        .encrypt(XNonce::from_slice(&sha_256(nonce)[..24]), data)
+        .map_err(|_| StdError::generic_err("Encryption failed."))
+}
+
+// returns true if the bytes are a valid sec1 encoded secp256k1 public key
+pub fn valid_pubkey(pubkey: &[u8]) -> bool {
+    PublicKey::from_sec1_bytes(pubkey).is_ok()
+}
+
+// Encrypts a fardel's contents for a buyer's secp256k1 public key. The contents are encrypted
+// with the fardel's contents key, and that key is wrapped with a secret derived by ecdh between
+// an ephemeral key and the buyer's key. The buyer repeats the ecdh with their private key and
+// the returned ephemeral public key to unwrap the contents key. Both layers use aead_encrypt
+// with the ephemeral public key as the nonce.
+//
+// The contents key belongs to the fardel, not the unpack, so every buyer of a fardel unwraps
+// the same key. A buyer who publishes it lets anyone decrypt what other buyers were sent, which
+// only reveals the contents they already bought.
+pub fn encrypt_contents_for_pubkey(
+    pubkey: &[u8],
+    ephemeral_seed: &[u8; 32],
+    contents_key: &[u8],
+    contents: &[u8],
+) -> StdResult<EncryptedContents> {
+    let buyer_key = PublicKey::from_sec1_bytes(pubkey)
+        .map_err(|_| StdError::generic_err("Invalid public key."))?;
+    let ephemeral_scalar: Option<NonZeroScalar> = NonZeroScalar::new(Scalar::from_bytes_reduced(
+        FieldBytes::from_slice(ephemeral_seed),
+    ))
+    .into();
+    let ephemeral_scalar = ephemeral_scalar
+        .ok_or_else(|| StdError::generic_err("Could not generate an ephemeral key."))?;
+    let ephemeral_pubkey = PublicKey::from_secret_scalar(&ephemeral_scalar)
+        .to_encoded_point(true)
+        .as_bytes()
+        .to_vec();
+
+    let shared_secret = diffie_hellman(&ephemeral_scalar, buyer_key.as_affine());
+    let wrapping_key = sha_256(shared_secret.as_bytes().as_slice());
+
+    Ok(EncryptedContents {
+        ephemeral_pubkey: Binary(ephemeral_pubkey.clone()),
+        encrypted_key: Binary(aead_encrypt(
+            &wrapping_key,
+            &ephemeral_pubkey,
+            contents_key,
+        )?),
+        encrypted_contents: Binary(aead_encrypt(contents_key, &ephemeral_pubkey, contents)?),
+    })
+}
+
+fn export_cipher(key: &[u8]) -> XChaCha20Poly1305 {
+    XChaCha20Poly1305::new(Key::from_slice(&sha_256(key)))
+}
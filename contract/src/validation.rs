@@ -181,8 +181,27 @@ pub fn valid_seal_time(val: Option<i32>) -> StdResult<u64> {
     }
 }
 
-pub fn has_whitespace(s: &String) -> bool {
-    let mut string_copy = s.clone();
-    string_copy.retain(|c| !c.is_whitespace());
-    return string_copy.len() != s.len();
+// check valid publish time for a fardel
+pub fn valid_publish_time(val: Option<i32>) -> StdResult<u64> {
+    match val {
+        Some(v) => u64::try_from(v).or_else(|_| Err(StdError::generic_err("invalid publish_time"))),
+        None => Ok(0_u64),
+    }
+}
+
+// check valid expiry time for a ban or delegation
+pub fn valid_expiry(val: Option<i32>) -> StdResult<u64> {
+    match val {
+        Some(v) => u64::try_from(v).or_else(|_| Err(StdError::generic_err("invalid expiry"))),
+        None => Ok(0_u64),
+    }
+}
+
+// handles may only use ascii letters, numbers, '_', '-' and '.', which also rules out
+//   lookalike characters from other scripts
+pub fn valid_handle_chars(handle: &str) -> bool {
+    !handle.is_empty()
+        && handle
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-' || c == '.')
 }